@@ -0,0 +1,341 @@
+//! Thin wrapper around `anchor_client::Program` for `yield_pilot`'s core flows. Reuses the
+//! program crate's own `anchor_lang`-generated `accounts`/`instruction` modules for Borsh
+//! encoding, so this layer is just PDA wiring plus `RequestBuilder` plumbing, not a parallel
+//! account/instruction schema that could drift from the on-chain one.
+
+use std::ops::Deref;
+use std::rc::Rc;
+
+use anchor_client::{
+    solana_sdk::{
+        ed25519_instruction, instruction::AccountMeta, pubkey::Pubkey, signature::Keypair,
+        signature::Signature, signer::Signer,
+    },
+    Client, Program,
+};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use yield_pilot::{accounts, instruction, UserPosition, YieldState};
+
+use crate::pda;
+
+/// Wraps a `yield_pilot` vault identified by `(authority, vault_index)`, caching the PDAs
+/// every instruction builder below needs so callers don't re-derive them per call.
+pub struct YieldPilotClient<C> {
+    program: Program<C>,
+    pub authority: Pubkey,
+    pub vault_index: u64,
+    pub state: Pubkey,
+    pub vault_authority: Pubkey,
+    pub vault: Pubkey,
+    pub share_mint: Pubkey,
+    pub yield_history: Pubkey,
+}
+
+impl<C: Clone + Deref<Target = impl Signer>> YieldPilotClient<C> {
+    pub fn new(client: &Client<C>, authority: Pubkey, vault_index: u64) -> anchor_client::ClientResult<Self> {
+        let program = client.program(yield_pilot::ID)?;
+        let (state, _) = pda::yield_state(&yield_pilot::ID, &authority, vault_index);
+        let (vault_authority, _) = pda::vault_authority(&yield_pilot::ID, &authority, vault_index);
+        let (vault, _) = pda::vault(&yield_pilot::ID, &state);
+        let (share_mint, _) = pda::share_mint(&yield_pilot::ID, &authority, vault_index);
+        let (yield_history, _) = pda::yield_history(&yield_pilot::ID, &state);
+
+        Ok(Self {
+            program,
+            authority,
+            vault_index,
+            state,
+            vault_authority,
+            vault,
+            share_mint,
+            yield_history,
+        })
+    }
+
+    pub fn fetch_state(&self) -> anchor_client::ClientResult<YieldState> {
+        self.program.account(self.state)
+    }
+
+    pub fn fetch_user_position(&self, depositor: &Pubkey) -> anchor_client::ClientResult<UserPosition> {
+        let (position, _) = pda::user_position(&yield_pilot::ID, &self.state, depositor);
+        self.program.account(position)
+    }
+
+    /// Deposits `amount` of `mint` for `depositor`, minting shares into the depositor's ATA
+    /// for `share_mint`. `mint`/`token_program` are passed in rather than re-fetched, since
+    /// the caller already knows the vault's underlying asset from `fetch_state`.
+    pub fn deposit(
+        &self,
+        depositor: &Rc<dyn Signer>,
+        mint: Pubkey,
+        token_program: Pubkey,
+        amount: u64,
+    ) -> anchor_client::ClientResult<Signature> {
+        let (depositor_share_account, _) =
+            pda::share_account(&yield_pilot::ID, &self.state, &depositor.pubkey());
+        let depositor_token_account =
+            get_associated_token_address_with_program_id(&depositor.pubkey(), &mint, &token_program);
+        let (user_position, _) = pda::user_position(&yield_pilot::ID, &self.state, &depositor.pubkey());
+        let (allowlist_entry, _) =
+            pda::allowlist_entry(&yield_pilot::ID, &self.state, &depositor.pubkey());
+
+        self.program
+            .request()
+            .accounts(accounts::Deposit {
+                state: self.state,
+                vault_authority: self.vault_authority,
+                vault: self.vault,
+                mint,
+                share_mint: self.share_mint,
+                depositor_share_account,
+                depositor_token_account,
+                user_position,
+                allowlist_entry: Some(allowlist_entry),
+                depositor: depositor.pubkey(),
+                token_program,
+                system_program: anchor_client::solana_sdk::system_program::ID,
+            })
+            .args(instruction::Deposit { amount })
+            .signer(depositor.as_ref())
+            .send()
+    }
+
+    /// Burns `shares` for `depositor`, redeeming the underlying into their ATA for `mint`.
+    pub fn withdraw(
+        &self,
+        depositor: &Rc<dyn Signer>,
+        mint: Pubkey,
+        token_program: Pubkey,
+        associated_token_program: Pubkey,
+        shares: u64,
+    ) -> anchor_client::ClientResult<Signature> {
+        let (depositor_share_account, _) =
+            pda::share_account(&yield_pilot::ID, &self.state, &depositor.pubkey());
+        let depositor_token_account =
+            get_associated_token_address_with_program_id(&depositor.pubkey(), &mint, &token_program);
+        let (user_position, _) = pda::user_position(&yield_pilot::ID, &self.state, &depositor.pubkey());
+
+        self.program
+            .request()
+            .accounts(accounts::Withdraw {
+                state: self.state,
+                vault_authority: self.vault_authority,
+                vault: self.vault,
+                mint,
+                share_mint: self.share_mint,
+                depositor_share_account,
+                depositor_token_account,
+                user_position,
+                depositor: depositor.pubkey(),
+                token_program,
+                associated_token_program,
+                system_program: anchor_client::solana_sdk::system_program::ID,
+            })
+            .args(instruction::Withdraw { shares })
+            .signer(depositor.as_ref())
+            .send()
+    }
+
+    /// Crank-claims whatever the currently deployed protocol's adapter has accrued.
+    /// `reward_account` is the vault-authority-owned token account rewards land in;
+    /// `adapter_remaining_accounts` is the adapter program id followed by its own
+    /// `claim_rewards` account list, exactly as `invoke_adapter` expects.
+    pub fn harvest(
+        &self,
+        signer: &Rc<dyn Signer>,
+        mint: Pubkey,
+        token_program: Pubkey,
+        reward_account: Pubkey,
+        claim_adapter_account_count: u8,
+        min_amount_out: u64,
+        adapter_remaining_accounts: Vec<AccountMeta>,
+    ) -> anchor_client::ClientResult<Signature> {
+        self.program
+            .request()
+            .accounts(accounts::Harvest {
+                state: self.state,
+                vault_authority: self.vault_authority,
+                vault: self.vault,
+                mint,
+                reward_account,
+                signer: signer.pubkey(),
+                token_program,
+            })
+            .args(instruction::Harvest {
+                claim_adapter_account_count,
+                min_amount_out,
+            })
+            .accounts(adapter_remaining_accounts)
+            .signer(signer.as_ref())
+            .send()
+    }
+
+    /// Accrues and mints the management/performance fee. A no-op on-chain (and thus a
+    /// harmless send) when nothing has accrued since `last_fee_collection_ts`.
+    pub fn collect_fees(
+        &self,
+        payer: &Rc<dyn Signer>,
+        fee_recipient_share_account: Pubkey,
+        token_program: Pubkey,
+        insurance_fund_share_account: Option<Pubkey>,
+        referrer_share_account: Option<Pubkey>,
+    ) -> anchor_client::ClientResult<Signature> {
+        self.program
+            .request()
+            .accounts(accounts::CollectFees {
+                state: self.state,
+                vault_authority: self.vault_authority,
+                share_mint: self.share_mint,
+                fee_recipient_share_account,
+                insurance_fund_share_account,
+                referrer_share_account,
+                token_program,
+            })
+            .args(instruction::CollectFees {})
+            .signer(payer.as_ref())
+            .send()
+    }
+
+    /// One-time setup for `(authority, vault_index)`: creates `state`/`history`/`share_mint`.
+    /// `authority` both pays and signs, and must be the same pubkey this client was
+    /// constructed with — `create_vault` has no separate payer, unlike `deposit_with_delegate`.
+    pub fn create_vault(
+        &self,
+        authority: &Rc<dyn Signer>,
+        mint: Pubkey,
+        token_program: Pubkey,
+        decimals_offset: u8,
+    ) -> anchor_client::ClientResult<Signature> {
+        self.program
+            .request()
+            .accounts(accounts::CreateVault {
+                state: self.state,
+                history: self.yield_history,
+                authority: authority.pubkey(),
+                mint,
+                vault_authority: self.vault_authority,
+                share_mint: self.share_mint,
+                token_program,
+                system_program: anchor_client::solana_sdk::system_program::ID,
+            })
+            .args(instruction::CreateVault {
+                vault_index: self.vault_index,
+                decimals_offset,
+            })
+            .signer(authority.as_ref())
+            .send()
+    }
+
+    /// Posts an APY update for `new_protocol`, the same instruction a keeper's signed-update
+    /// path posts on a cadence. `strategy_info` is derived and required present here — unlike
+    /// `rebalance`, `update_yield` only ever touches a strategy that's already registered.
+    pub fn update_yield(
+        &self,
+        signer: &Rc<dyn Signer>,
+        new_protocol: u8,
+        new_apy_bps: u16,
+    ) -> anchor_client::ClientResult<Signature> {
+        let (strategy_info, _) = pda::strategy(&yield_pilot::ID, &self.state, new_protocol);
+
+        self.program
+            .request()
+            .accounts(accounts::UpdateYield {
+                state: self.state,
+                history: self.yield_history,
+                strategy_info: Some(strategy_info),
+                oracle: None,
+                signer: signer.pubkey(),
+                operator_limits: None,
+                audit_log: None,
+            })
+            .args(instruction::UpdateYield {
+                new_protocol,
+                new_apy_bps,
+            })
+            .signer(signer.as_ref())
+            .send()
+    }
+
+    /// Permissionless counterpart to `update_yield`: posts the same APY update authorized by
+    /// an Ed25519 signature from `oracle_keypair` instead of a `Signer` matching
+    /// `state.authority`/`state.updaters`, so a keeper can sign off-chain with a key that
+    /// never needs to hold SOL or sign transactions directly. `oracle_keypair` must match
+    /// `state.apy_oracle_signer` (set via `set_apy_oracle_signer`) and `signed_at` must be
+    /// newer than the strategy's last update — see `update_yield_signed`'s doc comment.
+    pub fn update_yield_signed(
+        &self,
+        payer: &Rc<dyn Signer>,
+        oracle_keypair: &Keypair,
+        new_protocol: u8,
+        new_apy_bps: u16,
+        signed_at: i64,
+    ) -> anchor_client::ClientResult<Signature> {
+        let (strategy_info, _) = pda::strategy(&yield_pilot::ID, &self.state, new_protocol);
+
+        let mut message = Vec::with_capacity(32 + 1 + 2 + 8);
+        message.extend_from_slice(self.state.as_ref());
+        message.push(new_protocol);
+        message.extend_from_slice(&new_apy_bps.to_le_bytes());
+        message.extend_from_slice(&signed_at.to_le_bytes());
+        let ed25519_ix = ed25519_instruction::new_ed25519_instruction(oracle_keypair, &message);
+
+        self.program
+            .request()
+            .instruction(ed25519_ix)
+            .accounts(accounts::UpdateYieldSigned {
+                state: self.state,
+                history: self.yield_history,
+                strategy_info: Some(strategy_info),
+                oracle: None,
+                instructions_sysvar: anchor_client::solana_sdk::sysvar::instructions::ID,
+                payer: payer.pubkey(),
+            })
+            .args(instruction::UpdateYieldSigned {
+                new_protocol,
+                new_apy_bps,
+                signed_at,
+            })
+            .signer(payer.as_ref())
+            .send()
+    }
+
+    /// Cranks a rebalance into `new_protocol`. `adapter_remaining_accounts` is the old leg's
+    /// adapter program id + accounts (if any is currently deployed) followed immediately by
+    /// the new leg's, exactly as `apply_rebalance` expects via `old_adapter_account_count`.
+    pub fn rebalance(
+        &self,
+        signer: &Rc<dyn Signer>,
+        new_protocol: u8,
+        new_apy_bps: u16,
+        old_adapter_account_count: u8,
+        min_amount_out: u64,
+        adapter_remaining_accounts: Vec<AccountMeta>,
+    ) -> anchor_client::ClientResult<Signature> {
+        let (strategy_info, _) = pda::strategy(&yield_pilot::ID, &self.state, new_protocol);
+
+        self.program
+            .request()
+            .accounts(accounts::Rebalance {
+                state: self.state,
+                history: self.yield_history,
+                strategy_info: Some(strategy_info),
+                queued_rebalance: None,
+                protocol_blacklist: None,
+                vault_authority: self.vault_authority,
+                oracle: None,
+                vault: self.vault,
+                signer: signer.pubkey(),
+                operator_limits: None,
+                audit_log: None,
+            })
+            .args(instruction::Rebalance {
+                new_protocol,
+                new_apy_bps,
+                old_adapter_account_count,
+                min_amount_out,
+            })
+            .accounts(adapter_remaining_accounts)
+            .signer(signer.as_ref())
+            .send()
+    }
+}
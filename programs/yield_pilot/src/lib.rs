@@ -1,6 +1,11 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 
-declare_id!("Hp1uqW9SEVeZfgKzPUkjw1tmsQRpGNgydjXmF6cedry2"); 
+declare_id!("Hp1uqW9SEVeZfgKzPUkjw1tmsQRpGNgydjXmF6cedry2");
+
+pub const MAX_UPDATERS: usize = 10;
+pub const HISTORY_LEN: usize = 32;
+pub const DEFAULT_MIN_IMPROVEMENT_BPS: u16 = 25;
 
 #[program]
 pub mod yield_pilot {
@@ -11,6 +16,9 @@ pub mod yield_pilot {
         state.authority = ctx.accounts.authority.key();
         state.current_protocol = 0;
         state.current_apy_bps = 0;
+        state.min_improvement_bps = DEFAULT_MIN_IMPROVEMENT_BPS;
+        state.mint = ctx.accounts.mint.key();
+        state.bump = ctx.bumps.state;
         Ok(())
     }
 
@@ -19,46 +27,597 @@ pub mod yield_pilot {
         new_protocol: u8,
         new_apy_bps: u16,
     ) -> Result<()> {
+        let protocol_info = ctx
+            .accounts
+            .protocol_info
+            .as_ref()
+            .ok_or(YieldPilotError::UnknownProtocol)?;
+        require!(
+            new_apy_bps <= protocol_info.max_apy_bps,
+            YieldPilotError::ApyOutOfBounds
+        );
+
         let state = &mut ctx.accounts.state;
+        let signer = ctx.accounts.signer.key();
+
+        require!(
+            signer == state.authority || state.is_updater(&signer),
+            YieldPilotError::Unauthorized
+        );
+
+        state.current_protocol = new_protocol;
+        state.current_apy_bps = new_apy_bps;
+        state.record_snapshot(new_protocol, new_apy_bps, Clock::get()?.unix_timestamp);
+
+        Ok(())
+    }
 
+    pub fn register_protocol(
+        ctx: Context<RegisterProtocol>,
+        id: u8,
+        name: [u8; 32],
+        max_apy_bps: u16,
+    ) -> Result<()> {
         require_keys_eq!(
-            state.authority,
+            ctx.accounts.state.authority,
             ctx.accounts.authority.key(),
             YieldPilotError::Unauthorized
         );
 
+        let protocol_info = &mut ctx.accounts.protocol_info;
+        protocol_info.id = id;
+        protocol_info.name = name;
+        protocol_info.max_apy_bps = max_apy_bps;
+        protocol_info.bump = ctx.bumps.protocol_info;
+
+        Ok(())
+    }
+
+    pub fn rebalance(ctx: Context<Rebalance>, new_protocol: u8, new_apy_bps: u16) -> Result<()> {
+        let protocol_info = ctx
+            .accounts
+            .protocol_info
+            .as_ref()
+            .ok_or(YieldPilotError::UnknownProtocol)?;
+        require!(
+            new_apy_bps <= protocol_info.max_apy_bps,
+            YieldPilotError::ApyOutOfBounds
+        );
+
+        let state = &mut ctx.accounts.state;
+        let signer = ctx.accounts.signer.key();
+
+        require!(
+            signer == state.authority || state.is_updater(&signer),
+            YieldPilotError::Unauthorized
+        );
+        require!(
+            new_apy_bps
+                >= state
+                    .current_apy_bps
+                    .saturating_add(state.min_improvement_bps),
+            YieldPilotError::ApyImprovementTooLow
+        );
+
         state.current_protocol = new_protocol;
         state.current_apy_bps = new_apy_bps;
+        state.record_snapshot(new_protocol, new_apy_bps, Clock::get()?.unix_timestamp);
+
+        Ok(())
+    }
+
+    pub fn set_min_improvement_bps(ctx: Context<ManageUpdaters>, min_improvement_bps: u16) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        require_keys_eq!(
+            state.authority,
+            ctx.accounts.authority.key(),
+            YieldPilotError::Unauthorized
+        );
+
+        state.min_improvement_bps = min_improvement_bps;
+
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        require!(amount > 0, YieldPilotError::ZeroAmount);
+
+        let state = &mut ctx.accounts.state;
+        let shares_minted = if state.total_shares == 0 {
+            amount
+        } else {
+            (amount as u128 * state.total_shares as u128 / state.total_assets as u128) as u64
+        };
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.depositor_token_account.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        state.total_shares = state
+            .total_shares
+            .checked_add(shares_minted)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        state.total_assets = state
+            .total_assets
+            .checked_add(amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        let position = &mut ctx.accounts.position;
+        position.owner = ctx.accounts.depositor.key();
+        position.shares = position
+            .shares
+            .checked_add(shares_minted)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        position.bump = ctx.bumps.position;
+
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, shares: u64) -> Result<()> {
+        require!(shares > 0, YieldPilotError::ZeroAmount);
+        require!(
+            shares <= ctx.accounts.position.shares,
+            YieldPilotError::InsufficientShares
+        );
+
+        let state = &mut ctx.accounts.state;
+        let amount = (shares as u128 * state.total_assets as u128 / state.total_shares as u128) as u64;
+
+        let state_authority = state.authority;
+        let seeds = &[
+            b"vault_authority".as_ref(),
+            state_authority.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.depositor_token_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+        )?;
+
+        state.total_shares = state
+            .total_shares
+            .checked_sub(shares)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        state.total_assets = state
+            .total_assets
+            .checked_sub(amount)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        ctx.accounts.position.shares = ctx
+            .accounts
+            .position
+            .shares
+            .checked_sub(shares)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+
+        Ok(())
+    }
+
+    pub fn add_updater(ctx: Context<ManageUpdaters>, updater: Pubkey) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.state.authority,
+            ctx.accounts.authority.key(),
+            YieldPilotError::Unauthorized
+        );
+
+        ctx.accounts.state.add_updater(updater)
+    }
+
+    pub fn remove_updater(ctx: Context<ManageUpdaters>, updater: Pubkey) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.state.authority,
+            ctx.accounts.authority.key(),
+            YieldPilotError::Unauthorized
+        );
+
+        ctx.accounts.state.remove_updater(updater)
+    }
+
+    pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        require_keys_eq!(
+            state.authority,
+            ctx.accounts.authority.key(),
+            YieldPilotError::Unauthorized
+        );
+
+        state.pending_authority = Some(new_authority);
+
+        Ok(())
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        require_keys_eq!(
+            state.pending_authority.ok_or(YieldPilotError::PendingAuthorityMismatch)?,
+            ctx.accounts.pending_authority.key(),
+            YieldPilotError::PendingAuthorityMismatch
+        );
+
+        state.authority = ctx.accounts.pending_authority.key();
+        state.pending_authority = None;
 
         Ok(())
     }
+
+    /// Returns the recorded APY history in chronological order (oldest first).
+    pub fn get_history(ctx: Context<ReadYieldState>) -> Result<Vec<YieldSnapshot>> {
+        Ok(ctx.accounts.state.history_chronological())
+    }
 }
 
 #[account]
+#[derive(Default)]
 pub struct YieldState {
     pub authority: Pubkey,
     pub current_protocol: u8,
     pub current_apy_bps: u16,
+    pub pending_authority: Option<Pubkey>,
+    pub updaters: [Pubkey; MAX_UPDATERS],
+    pub updater_count: u8,
+    pub history: [YieldSnapshot; HISTORY_LEN],
+    pub head: u8,
+    pub len: u8,
+    pub min_improvement_bps: u16,
+    pub total_shares: u64,
+    pub total_assets: u64,
+    pub mint: Pubkey,
+    pub bump: u8,
+}
+
+impl YieldState {
+    pub fn is_updater(&self, key: &Pubkey) -> bool {
+        self.updaters[..self.updater_count as usize].contains(key)
+    }
+
+    pub fn add_updater(&mut self, updater: Pubkey) -> Result<()> {
+        require!(!self.is_updater(&updater), YieldPilotError::UpdaterAlreadyRegistered);
+        require!(
+            (self.updater_count as usize) < MAX_UPDATERS,
+            YieldPilotError::UpdaterCapacityExceeded
+        );
+
+        self.updaters[self.updater_count as usize] = updater;
+        self.updater_count += 1;
+
+        Ok(())
+    }
+
+    pub fn remove_updater(&mut self, updater: Pubkey) -> Result<()> {
+        let count = self.updater_count as usize;
+        let pos = self.updaters[..count]
+            .iter()
+            .position(|key| *key == updater)
+            .ok_or(YieldPilotError::UpdaterNotFound)?;
+
+        self.updaters[pos] = self.updaters[count - 1];
+        self.updaters[count - 1] = Pubkey::default();
+        self.updater_count -= 1;
+
+        Ok(())
+    }
+
+    /// Returns the stored snapshots oldest-first, unwrapping the circular buffer.
+    pub fn history_chronological(&self) -> Vec<YieldSnapshot> {
+        let len = self.len as usize;
+        let head = self.head as usize;
+        let start = (head + HISTORY_LEN - len) % HISTORY_LEN;
+        (0..len).map(|i| self.history[(start + i) % HISTORY_LEN]).collect()
+    }
+
+    fn record_snapshot(&mut self, protocol: u8, apy_bps: u16, ts: i64) {
+        let head = self.head as usize;
+        self.history[head] = YieldSnapshot {
+            protocol,
+            apy_bps,
+            ts,
+        };
+        self.head = ((head + 1) % HISTORY_LEN) as u8;
+        self.len = self.len.saturating_add(1).min(HISTORY_LEN as u8);
+    }
+}
+
+#[account]
+pub struct Position {
+    pub owner: Pubkey,
+    pub shares: u64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct ProtocolInfo {
+    pub id: u8,
+    pub name: [u8; 32],
+    pub max_apy_bps: u16,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct YieldSnapshot {
+    pub protocol: u8,
+    pub apy_bps: u16,
+    pub ts: i64,
 }
 
 #[derive(Accounts)]
 pub struct Initialize<'info> {
-    #[account(init, payer = authority, space = 8 + 32 + 1 + 2)]
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 1 + 2 + (1 + 32) + 32 * MAX_UPDATERS + 1
+            + (1 + 2 + 8) * HISTORY_LEN + 1 + 1
+            + 2 + 8 + 8 + 32 + 1,
+        seeds = [b"yield_state", authority.key().as_ref()],
+        bump,
+    )]
     pub state: Account<'info, YieldState>,
     #[account(mut)]
     pub authority: Signer<'info>,
+    pub mint: Account<'info, Mint>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
+#[instruction(new_protocol: u8, new_apy_bps: u16)]
 pub struct UpdateYield<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(seeds = [b"protocol", state.key().as_ref(), &[new_protocol]], bump)]
+    pub protocol_info: Option<Account<'info, ProtocolInfo>>,
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u8)]
+pub struct RegisterProtocol<'info> {
+    #[account(
+        seeds = [b"yield_state", authority.key().as_ref()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 1 + 32 + 2 + 1,
+        seeds = [b"protocol", state.key().as_ref(), &[id]],
+        bump,
+    )]
+    pub protocol_info: Account<'info, ProtocolInfo>,
     #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_protocol: u8, new_apy_bps: u16)]
+pub struct Rebalance<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(seeds = [b"protocol", state.key().as_ref(), &[new_protocol]], bump)]
+    pub protocol_info: Option<Account<'info, ProtocolInfo>>,
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + 32 + 8 + 1,
+        seeds = [b"position", state.key().as_ref(), depositor.key().as_ref()],
+        bump,
+    )]
+    pub position: Account<'info, Position>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        seeds = [b"vault", state.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(address = state.mint)]
+    pub mint: Account<'info, Mint>,
+    #[account(mut, constraint = depositor_token_account.mint == state.mint @ YieldPilotError::InvalidMint)]
+    pub depositor_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        mut,
+        seeds = [b"position", state.key().as_ref(), depositor.key().as_ref()],
+        bump = position.bump,
+        constraint = position.owner == depositor.key() @ YieldPilotError::Unauthorized,
+    )]
+    pub position: Account<'info, Position>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"vault", state.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(mut, constraint = depositor_token_account.mint == state.mint @ YieldPilotError::InvalidMint)]
+    pub depositor_token_account: Account<'info, TokenAccount>,
+    pub depositor: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", authority.key().as_ref()],
+        bump = state.bump,
+    )]
     pub state: Account<'info, YieldState>,
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct ManageUpdaters<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", authority.key().as_ref()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    pub pending_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReadYieldState<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+}
+
 #[error_code]
 pub enum YieldPilotError {
     #[msg("Unauthorized caller")]
     Unauthorized,
+    #[msg("Signer does not match the pending authority")]
+    PendingAuthorityMismatch,
+    #[msg("Updater is already registered")]
+    UpdaterAlreadyRegistered,
+    #[msg("Updater registry is full")]
+    UpdaterCapacityExceeded,
+    #[msg("Updater was not found in the registry")]
+    UpdaterNotFound,
+    #[msg("New protocol APY does not clear the minimum improvement threshold")]
+    ApyImprovementTooLow,
+    #[msg("Amount must be greater than zero")]
+    ZeroAmount,
+    #[msg("Position does not hold enough shares")]
+    InsufficientShares,
+    #[msg("Protocol id has not been registered")]
+    UnknownProtocol,
+    #[msg("Reported APY exceeds the protocol's registered maximum")]
+    ApyOutOfBounds,
+    #[msg("Arithmetic overflow while updating vault accounting")]
+    ArithmeticOverflow,
+    #[msg("Arithmetic underflow while updating vault accounting")]
+    ArithmeticUnderflow,
+    #[msg("Token account mint does not match the vault's mint")]
+    InvalidMint,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_chronological_returns_partial_buffer_before_wrap() {
+        let mut state = YieldState::default();
+        state.record_snapshot(1, 100, 10);
+        state.record_snapshot(2, 200, 20);
+
+        let history = state.history_chronological();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].protocol, 1);
+        assert_eq!(history[1].protocol, 2);
+    }
+
+    #[test]
+    fn history_chronological_wraps_and_keeps_oldest_first() {
+        let mut state = YieldState::default();
+        for i in 0..(HISTORY_LEN as u8 + 5) {
+            state.record_snapshot(i, i as u16, i as i64);
+        }
+
+        let history = state.history_chronological();
+        assert_eq!(history.len(), HISTORY_LEN);
+        assert_eq!(history.first().unwrap().protocol, 5);
+        assert_eq!(history.last().unwrap().protocol, HISTORY_LEN as u8 + 4);
+    }
+
+    #[test]
+    fn add_updater_rejects_duplicates_and_enforces_capacity() {
+        let mut state = YieldState::default();
+        let key = Pubkey::new_unique();
+
+        state.add_updater(key).unwrap();
+        assert!(state.is_updater(&key));
+        assert!(state.add_updater(key).is_err());
+
+        for _ in state.updater_count as usize..MAX_UPDATERS {
+            state.add_updater(Pubkey::new_unique()).unwrap();
+        }
+        assert!(state.add_updater(Pubkey::new_unique()).is_err());
+    }
+
+    #[test]
+    fn remove_updater_swap_removes_and_clears_vacated_slot() {
+        let mut state = YieldState::default();
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        state.add_updater(a).unwrap();
+        state.add_updater(b).unwrap();
+
+        state.remove_updater(a).unwrap();
+
+        assert!(!state.is_updater(&a));
+        assert!(state.is_updater(&b));
+        assert_eq!(state.updater_count, 1);
+        assert!(state.remove_updater(a).is_err());
+    }
 }
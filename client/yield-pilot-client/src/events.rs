@@ -0,0 +1,52 @@
+//! Decodes `yield_pilot`'s `emit_cpi!` events out of confirmed transaction logs.
+//!
+//! `#[event_cpi]` instructions self-CPI into the program with the event's Anchor
+//! discriminator + Borsh payload as instruction data, rather than the legacy `emit!`
+//! "Program data:" log line, so events have to be pulled from the inner instructions of a
+//! transaction's `meta`, not from `anchor_client::Program::on`'s log-based subscription
+//! (which only understands the legacy form).
+
+use anchor_client::solana_sdk::instruction::CompiledInstruction;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use yield_pilot::{Deposited, Withdrawn};
+
+/// One instruction decoded into whichever known event type its discriminator matches.
+/// Extend as callers need more event types decoded; unrecognized discriminators (e.g. the
+/// outer instruction's own data, or another program's self-CPI) are skipped rather than
+/// treated as an error, since a transaction mixes real CPIs with event CPIs freely.
+pub enum YieldPilotEvent {
+    Deposited(Deposited),
+    Withdrawn(Withdrawn),
+}
+
+/// Scans a transaction's inner instructions that were addressed to `program_id` and
+/// decodes the ones whose first 8 bytes match a known event discriminator.
+pub fn parse_event_cpi_instructions(
+    program_id_index: u8,
+    instructions: &[CompiledInstruction],
+) -> Vec<YieldPilotEvent> {
+    instructions
+        .iter()
+        .filter(|ix| ix.program_id_index == program_id_index)
+        .filter_map(|ix| decode_event(&ix.data))
+        .collect()
+}
+
+fn decode_event(data: &[u8]) -> Option<YieldPilotEvent> {
+    if data.len() < 8 {
+        return None;
+    }
+    let (discriminator, payload) = data.split_at(8);
+
+    if discriminator == Deposited::DISCRIMINATOR {
+        Deposited::deserialize(&mut &payload[..])
+            .ok()
+            .map(YieldPilotEvent::Deposited)
+    } else if discriminator == Withdrawn::DISCRIMINATOR {
+        Withdrawn::deserialize(&mut &payload[..])
+            .ok()
+            .map(YieldPilotEvent::Withdrawn)
+    } else {
+        None
+    }
+}
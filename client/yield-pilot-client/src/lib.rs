@@ -0,0 +1,15 @@
+//! Off-chain SDK for `yield_pilot`. Wraps `anchor_client` with PDA derivation, account
+//! fetching, and instruction builders for the vault's core flows, so keeper bots and
+//! backend services call into these instead of hand-rolling the Borsh account layouts
+//! and seed lists that live in `programs/yield_pilot/src/lib.rs`.
+//!
+//! Deliberately covers the flows a keeper or indexer actually drives — vault creation,
+//! deposits/withdrawals, yield/rebalance/harvest cranking, and fee collection — rather
+//! than every admin-only instruction in the program; those are thin enough to build by
+//! hand against `yield_pilot::instruction` when a one-off script needs them.
+
+pub mod client;
+pub mod events;
+pub mod pda;
+
+pub use client::YieldPilotClient;
@@ -0,0 +1,85 @@
+//! PDA derivation mirroring the `seeds = [...]` constraints in
+//! `programs/yield_pilot/src/lib.rs`. Kept as one function per seed list so a change to the
+//! on-chain seeds is a one-line diff here instead of a search-and-replace across every
+//! caller.
+
+use anchor_lang::prelude::Pubkey;
+
+pub fn yield_state(program_id: &Pubkey, authority: &Pubkey, vault_index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"yield_state", authority.as_ref(), &vault_index.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn vault_authority(program_id: &Pubkey, authority: &Pubkey, vault_index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"vault_authority", authority.as_ref(), &vault_index.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn share_mint(program_id: &Pubkey, authority: &Pubkey, vault_index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"share_mint", authority.as_ref(), &vault_index.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn vault(program_id: &Pubkey, state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault", state.as_ref()], program_id)
+}
+
+pub fn yield_history(program_id: &Pubkey, state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"yield_history", state.as_ref()], program_id)
+}
+
+pub fn strategy(program_id: &Pubkey, state: &Pubkey, protocol_id: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"strategy", state.as_ref(), &[protocol_id]], program_id)
+}
+
+pub fn user_position(program_id: &Pubkey, state: &Pubkey, depositor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"position", state.as_ref(), depositor.as_ref()],
+        program_id,
+    )
+}
+
+pub fn share_account(program_id: &Pubkey, state: &Pubkey, depositor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"share", state.as_ref(), depositor.as_ref()],
+        program_id,
+    )
+}
+
+pub fn allowlist_entry(program_id: &Pubkey, state: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"allowlist", state.as_ref(), wallet.as_ref()],
+        program_id,
+    )
+}
+
+pub fn withdrawal_ticket(
+    program_id: &Pubkey,
+    state: &Pubkey,
+    owner: &Pubkey,
+    sequence: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"withdrawal_ticket",
+            state.as_ref(),
+            owner.as_ref(),
+            &sequence.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+pub fn rewards_vault(program_id: &Pubkey, state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"rewards_vault", state.as_ref()], program_id)
+}
+
+pub fn insurance_fund(program_id: &Pubkey, state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"insurance_fund", state.as_ref()], program_id)
+}
@@ -0,0 +1,55 @@
+//! On-disk config for the CLI: keypair path and RPC URL, loaded once at startup so every
+//! subcommand doesn't have to repeat `--keypair`/`--url` flags. CLI flags still override the
+//! file, matching the Solana CLI's own `~/.config/solana/cli/config.yml` convention (TOML
+//! here instead, since this crate has no reason to depend on `serde_yaml` for one struct).
+
+use std::path::{Path, PathBuf};
+
+use anchor_client::solana_sdk::{pubkey::Pubkey, signature::Keypair};
+use serde::Deserialize;
+
+#[derive(Deserialize, Default)]
+pub struct Config {
+    pub keypair_path: Option<String>,
+    pub rpc_url: Option<String>,
+    pub program_id: Option<String>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&raw)?)
+    }
+
+    pub fn default_path() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".config/yieldpilot/cli.toml")
+    }
+
+    pub fn resolve_keypair(&self, override_path: Option<&str>) -> anyhow::Result<Keypair> {
+        let path = override_path
+            .map(str::to_string)
+            .or_else(|| self.keypair_path.clone())
+            .ok_or_else(|| anyhow::anyhow!("no keypair configured; pass --keypair or set keypair_path in cli.toml"))?;
+        anchor_client::solana_sdk::signature::read_keypair_file(&path)
+            .map_err(|err| anyhow::anyhow!("failed to read keypair at {path}: {err}"))
+    }
+
+    pub fn resolve_rpc_url(&self, override_url: Option<&str>) -> String {
+        override_url
+            .map(str::to_string)
+            .or_else(|| self.rpc_url.clone())
+            .unwrap_or_else(|| "https://api.mainnet-beta.solana.com".to_string())
+    }
+
+    pub fn resolve_program_id(&self, override_id: Option<&str>) -> anyhow::Result<Pubkey> {
+        match override_id.map(str::to_string).or_else(|| self.program_id.clone()) {
+            Some(id) => Ok(id.parse()?),
+            None => Ok(yield_pilot::ID),
+        }
+    }
+}
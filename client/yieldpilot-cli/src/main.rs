@@ -0,0 +1,235 @@
+//! `yieldpilot-cli`: operator-facing wrapper around `yield-pilot-client` for the six
+//! instructions a human runs by hand rather than a keeper daemon — standing up a new vault,
+//! posting an APY update, cranking a rebalance or harvest, pulling fees, and checking status.
+//! Reads its keypair/RPC url from `~/.config/yieldpilot/cli.toml` (overridable per-flag) so
+//! operators don't have to re-type `--keypair`/`--url` on every invocation.
+
+mod config;
+
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use anchor_client::{
+    solana_sdk::{instruction::AccountMeta, pubkey::Pubkey, signer::Signer},
+    Client, Cluster,
+};
+use clap::{Parser, Subcommand};
+use yield_pilot_client::YieldPilotClient;
+
+use crate::config::Config;
+
+#[derive(Parser)]
+#[command(name = "yieldpilot-cli", about = "Operate a yield_pilot vault from the command line")]
+struct Cli {
+    /// Path to the TOML config file. Defaults to ~/.config/yieldpilot/cli.toml.
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// Overrides `keypair_path` from the config file.
+    #[arg(long, global = true)]
+    keypair: Option<String>,
+    /// Overrides `rpc_url` from the config file.
+    #[arg(long, global = true)]
+    url: Option<String>,
+    /// The vault's `authority` pubkey. Defaults to the signing keypair's own pubkey.
+    #[arg(long, global = true)]
+    authority: Option<String>,
+    /// Disambiguates multiple vaults owned by the same authority.
+    #[arg(long, global = true, default_value_t = 0)]
+    vault_index: u64,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Creates a new vault for (authority, vault-index).
+    InitVault {
+        /// Underlying asset mint.
+        #[arg(long)]
+        mint: String,
+        #[arg(long)]
+        token_program: String,
+        /// Extra decimals the share mint carries over the underlying asset's own; see
+        /// `create_vault`'s `decimals_offset`.
+        #[arg(long, default_value_t = 0)]
+        decimals_offset: u8,
+    },
+    /// Posts an APY update for the vault's currently active strategy.
+    PostApy {
+        #[arg(long)]
+        protocol: u8,
+        #[arg(long)]
+        apy_bps: u16,
+    },
+    /// Cranks a rebalance into `protocol`, unwinding `old_adapter_account_count` accounts
+    /// from the tail of `--adapter-account` and handing the rest to the new adapter.
+    Rebalance {
+        #[arg(long)]
+        protocol: u8,
+        #[arg(long)]
+        apy_bps: u16,
+        #[arg(long)]
+        old_adapter_account_count: u8,
+        #[arg(long, default_value_t = 0)]
+        min_amount_out: u64,
+        /// Adapter program id followed by its account list, in CPI order; repeat per account.
+        #[arg(long = "adapter-account")]
+        adapter_accounts: Vec<String>,
+    },
+    /// Claims whatever the active adapter has accrued into `--reward-account`.
+    Harvest {
+        #[arg(long)]
+        mint: String,
+        #[arg(long)]
+        token_program: String,
+        #[arg(long)]
+        reward_account: String,
+        #[arg(long)]
+        claim_adapter_account_count: u8,
+        #[arg(long, default_value_t = 0)]
+        min_amount_out: u64,
+        #[arg(long = "adapter-account")]
+        adapter_accounts: Vec<String>,
+    },
+    /// Accrues and mints the management/performance fee.
+    CollectFees {
+        #[arg(long)]
+        fee_recipient_share_account: String,
+        #[arg(long)]
+        token_program: String,
+        #[arg(long)]
+        insurance_fund_share_account: Option<String>,
+        #[arg(long)]
+        referrer_share_account: Option<String>,
+    },
+    /// Prints the vault's current `YieldState` summary.
+    Status,
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let config_path = cli.config.clone().unwrap_or_else(Config::default_path);
+    let config = Config::load(&config_path)?;
+
+    let keypair = config.resolve_keypair(cli.keypair.as_deref())?;
+    let rpc_url = config.resolve_rpc_url(cli.url.as_deref());
+    let authority = match &cli.authority {
+        Some(a) => Pubkey::from_str(a)?,
+        None => keypair.pubkey(),
+    };
+    let payer: Rc<dyn Signer> = Rc::new(keypair);
+
+    let client = Client::new(Cluster::from_str(&rpc_url).unwrap_or(Cluster::Custom(rpc_url.clone(), rpc_url)), payer.clone());
+    let vault = YieldPilotClient::new(&client, authority, cli.vault_index)?;
+
+    match cli.command {
+        Command::InitVault {
+            mint,
+            token_program,
+            decimals_offset,
+        } => {
+            let sig = vault.create_vault(
+                &payer,
+                Pubkey::from_str(&mint)?,
+                Pubkey::from_str(&token_program)?,
+                decimals_offset,
+            )?;
+            println!("init-vault: {sig}");
+        }
+        Command::PostApy { protocol, apy_bps } => {
+            let sig = vault.update_yield(&payer, protocol, apy_bps)?;
+            println!("post-apy: {sig}");
+        }
+        Command::Rebalance {
+            protocol,
+            apy_bps,
+            old_adapter_account_count,
+            min_amount_out,
+            adapter_accounts,
+        } => {
+            let remaining = parse_adapter_accounts(&adapter_accounts)?;
+            let sig = vault.rebalance(
+                &payer,
+                protocol,
+                apy_bps,
+                old_adapter_account_count,
+                min_amount_out,
+                remaining,
+            )?;
+            println!("rebalance: {sig}");
+        }
+        Command::Harvest {
+            mint,
+            token_program,
+            reward_account,
+            claim_adapter_account_count,
+            min_amount_out,
+            adapter_accounts,
+        } => {
+            let remaining = parse_adapter_accounts(&adapter_accounts)?;
+            let sig = vault.harvest(
+                &payer,
+                Pubkey::from_str(&mint)?,
+                Pubkey::from_str(&token_program)?,
+                Pubkey::from_str(&reward_account)?,
+                claim_adapter_account_count,
+                min_amount_out,
+                remaining,
+            )?;
+            println!("harvest: {sig}");
+        }
+        Command::CollectFees {
+            fee_recipient_share_account,
+            token_program,
+            insurance_fund_share_account,
+            referrer_share_account,
+        } => {
+            let sig = vault.collect_fees(
+                &payer,
+                Pubkey::from_str(&fee_recipient_share_account)?,
+                Pubkey::from_str(&token_program)?,
+                insurance_fund_share_account.map(|p| Pubkey::from_str(&p)).transpose()?,
+                referrer_share_account.map(|p| Pubkey::from_str(&p)).transpose()?,
+            )?;
+            println!("collect-fees: {sig}");
+        }
+        Command::Status => {
+            let state = vault.fetch_state()?;
+            println!("state: {}", vault.state);
+            println!("authority: {}", state.authority);
+            println!("vault_index: {}", state.vault_index);
+            println!("current_protocol: {}", state.current_protocol);
+            println!("current_apy_bps: {}", state.current_apy_bps);
+            println!("total_assets: {}", state.total_assets);
+            println!("total_shares: {}", state.total_shares);
+            println!("deployed_amount: {}", state.deployed_amount);
+            println!("deposits_paused: {}", state.deposits_paused);
+            println!("withdrawals_paused: {}", state.withdrawals_paused);
+            println!("rebalances_paused: {}", state.rebalances_paused);
+        }
+    }
+
+    Ok(())
+}
+
+/// Every two `--adapter-account` values are read as `(pubkey, is_writable)` pairs encoded
+/// as `"<pubkey>:w"` / `"<pubkey>:r"`, matching how `invoke_adapter` replays them verbatim
+/// as `AccountMeta`s with `is_signer` left false (the vault's own PDA is always the signer
+/// and is never part of this list).
+fn parse_adapter_accounts(raw: &[String]) -> anyhow::Result<Vec<AccountMeta>> {
+    raw.iter()
+        .map(|entry| {
+            let (pubkey, flag) = entry
+                .split_once(':')
+                .ok_or_else(|| anyhow::anyhow!("expected <pubkey>:w|r, got {entry}"))?;
+            let pubkey = Pubkey::from_str(pubkey)?;
+            match flag {
+                "w" => Ok(AccountMeta::new(pubkey, false)),
+                "r" => Ok(AccountMeta::new_readonly(pubkey, false)),
+                other => anyhow::bail!("expected w or r, got {other}"),
+            }
+        })
+        .collect()
+}
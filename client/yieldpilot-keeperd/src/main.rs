@@ -0,0 +1,441 @@
+//! `yieldpilot-keeperd`: the long-running counterpart to `yieldpilot-cli`'s one-shot
+//! commands. Polls an external APY source on a tight cadence and posts signed updates,
+//! cranks harvest/rebalance/collect-fees on a slower cadence, retrying with priority-fee
+//! escalation on failure, and exposes the loop's health over Prometheus so an operator
+//! doesn't have to tail logs to know the keeper is still alive.
+
+mod metrics;
+mod retry;
+
+use std::net::SocketAddr;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anchor_client::{
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{instruction::AccountMeta, pubkey::Pubkey, signer::Signer},
+    Client, Cluster,
+};
+use clap::Parser;
+use yield_pilot_client::YieldPilotClient;
+
+use crate::metrics::Metrics;
+use crate::retry::{with_retry, PriorityFeeEscalator};
+
+#[derive(Parser)]
+#[command(name = "yieldpilot-keeperd")]
+struct Args {
+    #[arg(long)]
+    keypair: String,
+    #[arg(long)]
+    url: String,
+    #[arg(long)]
+    authority: String,
+    #[arg(long, default_value_t = 0)]
+    vault_index: u64,
+    /// Seconds between harvest/rebalance/collect-fees passes. `post-apy` runs on its own,
+    /// tighter cadence since APY updates are cheap and staleness directly gates `rebalance`.
+    #[arg(long, default_value_t = 300)]
+    crank_interval_secs: u64,
+    #[arg(long, default_value_t = 30)]
+    apy_poll_interval_secs: u64,
+    #[arg(long, default_value_t = 3)]
+    max_attempts: u32,
+    #[arg(long, default_value_t = 1_000)]
+    starting_priority_fee_micro_lamports: u64,
+    #[arg(long, default_value_t = 1_000_000)]
+    max_priority_fee_micro_lamports: u64,
+    #[arg(long, default_value = "0.0.0.0:9464")]
+    metrics_addr: String,
+
+    /// Protocol id that `--apy-source` reports the rate for, and that a crank pass's
+    /// rebalance/harvest target when those flags below are set.
+    #[arg(long)]
+    apy_protocol: Option<u8>,
+    /// Account to poll for the externally observed APY. Left unset, APY polling is a no-op —
+    /// there's no protocol-agnostic way to discover a yield rate, so the operator points this
+    /// at whatever on-chain account the active adapter (or an oracle sitting in front of it)
+    /// publishes its rate into.
+    #[arg(long)]
+    apy_source: Option<String>,
+    /// Byte offset of a little-endian `u16` `apy_bps` field within `--apy-source`'s account
+    /// data. Defaults to `mock_yield_protocol::MockPool`'s own layout (8-byte discriminator +
+    /// three 32-byte pubkeys), so this daemon can be pointed at the mock venue this repo ships
+    /// without extra configuration; point it elsewhere for a real adapter's own layout.
+    #[arg(long, default_value_t = 104)]
+    apy_source_offset: usize,
+
+    /// Token account rewards are claimed into; set together with `--harvest-adapter-account`
+    /// to enable harvest cranking. Left unset, harvest cranking is skipped.
+    #[arg(long)]
+    harvest_reward_account: Option<String>,
+    #[arg(long)]
+    harvest_mint: Option<String>,
+    #[arg(long)]
+    harvest_token_program: Option<String>,
+    #[arg(long, default_value_t = 0)]
+    harvest_claim_adapter_account_count: u8,
+    #[arg(long, default_value_t = 0)]
+    harvest_min_amount_out: u64,
+    /// Adapter program id followed by its `claim_rewards` account list, in CPI order;
+    /// repeat per account, encoded as `<pubkey>:w|r` (see `yieldpilot-cli`'s own flag of the
+    /// same name).
+    #[arg(long = "harvest-adapter-account")]
+    harvest_adapter_accounts: Vec<String>,
+
+    /// New protocol id to rebalance into on every crank pass; set together with
+    /// `--rebalance-adapter-account` to enable rebalance cranking. Left unset, rebalance
+    /// cranking is skipped — deciding *when* to move funds is a strategy choice this daemon
+    /// leaves to the operator, driving it by re-running with updated flags rather than
+    /// guessing a target on its own.
+    #[arg(long)]
+    rebalance_new_protocol: Option<u8>,
+    #[arg(long, default_value_t = 0)]
+    rebalance_new_apy_bps: u16,
+    #[arg(long, default_value_t = 0)]
+    rebalance_old_adapter_account_count: u8,
+    #[arg(long, default_value_t = 0)]
+    rebalance_min_amount_out: u64,
+    /// Old leg's adapter program id + accounts (if any is currently deployed) followed
+    /// immediately by the new leg's, exactly as `apply_rebalance` expects via
+    /// `--rebalance-old-adapter-account-count`.
+    #[arg(long = "rebalance-adapter-account")]
+    rebalance_adapter_accounts: Vec<String>,
+
+    #[arg(long)]
+    fee_token_program: Option<String>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let metrics = Arc::new(Metrics::new()?);
+    metrics.clone().serve(SocketAddr::from_str(&args.metrics_addr)?);
+
+    let keypair = anchor_client::solana_sdk::signature::read_keypair_file(&args.keypair)
+        .map_err(|err| anyhow::anyhow!("failed to read keypair at {}: {err}", args.keypair))?;
+    // `update_yield_signed` needs the concrete keypair to build its Ed25519 instruction;
+    // `payer` below only gets a `dyn Signer` handle to it.
+    let apy_oracle_keypair = keypair.insecure_clone();
+    let authority = Pubkey::from_str(&args.authority)?;
+    let payer: Rc<dyn Signer> = Rc::new(keypair);
+
+    let client = Client::new(
+        Cluster::from_str(&args.url).unwrap_or(Cluster::Custom(args.url.clone(), args.url.clone())),
+        payer.clone(),
+    );
+    let vault = YieldPilotClient::new(&client, authority, args.vault_index)?;
+    let rpc_client = RpcClient::new(args.url.clone());
+
+    let mut escalator = PriorityFeeEscalator::new(
+        args.starting_priority_fee_micro_lamports,
+        args.max_priority_fee_micro_lamports,
+    );
+
+    let harvest_adapter_accounts = parse_adapter_accounts(&args.harvest_adapter_accounts)?;
+    let rebalance_adapter_accounts = parse_adapter_accounts(&args.rebalance_adapter_accounts)?;
+
+    let mut last_crank = SystemTime::UNIX_EPOCH;
+    let mut last_posted_apy_bps: Option<u16> = None;
+    loop {
+        metrics
+            .priority_fee_micro_lamports
+            .set(escalator.current() as i64);
+
+        poll_and_post_apy(
+            &rpc_client,
+            &vault,
+            &payer,
+            &apy_oracle_keypair,
+            &args,
+            &metrics,
+            &mut last_posted_apy_bps,
+        );
+
+        let due_for_crank = SystemTime::now()
+            .duration_since(last_crank)
+            .unwrap_or_default()
+            >= Duration::from_secs(args.crank_interval_secs);
+
+        if due_for_crank {
+            run_crank_pass(
+                &vault,
+                &payer,
+                &metrics,
+                &mut escalator,
+                args.max_attempts,
+                &args,
+                &harvest_adapter_accounts,
+                &rebalance_adapter_accounts,
+            );
+            last_crank = SystemTime::now();
+            escalator.reset(args.starting_priority_fee_micro_lamports);
+        }
+
+        std::thread::sleep(Duration::from_secs(args.apy_poll_interval_secs));
+    }
+}
+
+/// Reads `args.apy_source`'s account data and, if its `apy_bps` differs from the last value
+/// this keeper posted, signs and submits `update_yield_signed`. A no-op whenever
+/// `--apy-protocol`/`--apy-source` aren't both configured.
+fn poll_and_post_apy(
+    rpc_client: &RpcClient,
+    vault: &YieldPilotClient<Rc<dyn Signer>>,
+    payer: &Rc<dyn Signer>,
+    apy_oracle_keypair: &anchor_client::solana_sdk::signature::Keypair,
+    args: &Args,
+    metrics: &Metrics,
+    last_posted_apy_bps: &mut Option<u16>,
+) {
+    let (Some(protocol), Some(apy_source)) = (args.apy_protocol, args.apy_source.as_deref()) else {
+        return;
+    };
+    let apy_source = match Pubkey::from_str(apy_source) {
+        Ok(pubkey) => pubkey,
+        Err(err) => {
+            eprintln!("invalid --apy-source: {err}");
+            return;
+        }
+    };
+
+    let account_data = match rpc_client.get_account_data(&apy_source) {
+        Ok(data) => data,
+        Err(err) => {
+            eprintln!("apy poll: failed to fetch {apy_source}: {err}");
+            return;
+        }
+    };
+    if account_data.len() < args.apy_source_offset + 2 {
+        eprintln!(
+            "apy poll: {apy_source} has {} bytes, can't read a u16 at offset {}",
+            account_data.len(),
+            args.apy_source_offset
+        );
+        return;
+    }
+    let apy_bps = u16::from_le_bytes([
+        account_data[args.apy_source_offset],
+        account_data[args.apy_source_offset + 1],
+    ]);
+
+    if *last_posted_apy_bps == Some(apy_bps) {
+        return;
+    }
+
+    metrics.crank_attempts.with_label_values(&["update_yield_signed"]).inc();
+    let signed_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+
+    match vault.update_yield_signed(payer, apy_oracle_keypair, protocol, apy_bps, signed_at) {
+        Ok(sig) => {
+            println!("update_yield_signed: protocol={protocol} apy_bps={apy_bps} sig={sig}");
+            *last_posted_apy_bps = Some(apy_bps);
+            metrics.last_success_ts.set(signed_at);
+        }
+        Err(err) => {
+            eprintln!("update_yield_signed failed: {err}");
+            metrics.crank_failures.with_label_values(&["update_yield_signed"]).inc();
+        }
+    }
+}
+
+/// One pass of `collect_fees` plus whichever of `harvest`/`rebalance` the operator has
+/// configured adapter accounts for.
+fn run_crank_pass(
+    vault: &YieldPilotClient<Rc<dyn Signer>>,
+    payer: &Rc<dyn Signer>,
+    metrics: &Metrics,
+    escalator: &mut PriorityFeeEscalator,
+    max_attempts: u32,
+    args: &Args,
+    harvest_adapter_accounts: &[AccountMeta],
+    rebalance_adapter_accounts: &[AccountMeta],
+) {
+    metrics.crank_attempts.with_label_values(&["collect_fees"]).inc();
+
+    let state = match vault.fetch_state() {
+        Ok(state) => state,
+        Err(err) => {
+            eprintln!("fetch_state failed: {err}");
+            metrics.crank_failures.with_label_values(&["collect_fees"]).inc();
+            return;
+        }
+    };
+
+    let result = with_retry(max_attempts, Duration::from_secs(2), escalator, |_fee_micro_lamports| {
+        vault
+            .collect_fees(
+                payer,
+                state.fee_recipient,
+                args.fee_token_program
+                    .as_deref()
+                    .map(Pubkey::from_str)
+                    .transpose()?
+                    .unwrap_or(anchor_spl::token::ID),
+                None,
+                None,
+            )
+            .map_err(anyhow::Error::from)
+    });
+
+    match result {
+        Ok(sig) => {
+            println!("collect_fees: {sig}");
+            metrics.last_success_ts.set(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs() as i64,
+            );
+        }
+        Err(err) => {
+            eprintln!("collect_fees failed after {max_attempts} attempts: {err}");
+            metrics.crank_failures.with_label_values(&["collect_fees"]).inc();
+        }
+    }
+
+    if let (Some(reward_account), Some(mint), Some(token_program)) = (
+        args.harvest_reward_account.as_deref(),
+        args.harvest_mint.as_deref(),
+        args.harvest_token_program.as_deref(),
+    ) {
+        crank_harvest(
+            vault,
+            payer,
+            metrics,
+            escalator,
+            max_attempts,
+            args,
+            reward_account,
+            mint,
+            token_program,
+            harvest_adapter_accounts,
+        );
+    }
+
+    if let Some(new_protocol) = args.rebalance_new_protocol {
+        crank_rebalance(
+            vault,
+            payer,
+            metrics,
+            escalator,
+            max_attempts,
+            args,
+            new_protocol,
+            rebalance_adapter_accounts,
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn crank_harvest(
+    vault: &YieldPilotClient<Rc<dyn Signer>>,
+    payer: &Rc<dyn Signer>,
+    metrics: &Metrics,
+    escalator: &mut PriorityFeeEscalator,
+    max_attempts: u32,
+    args: &Args,
+    reward_account: &str,
+    mint: &str,
+    token_program: &str,
+    adapter_accounts: &[AccountMeta],
+) {
+    metrics.crank_attempts.with_label_values(&["harvest"]).inc();
+
+    let result = with_retry(max_attempts, Duration::from_secs(2), escalator, |_fee_micro_lamports| {
+        vault
+            .harvest(
+                payer,
+                Pubkey::from_str(mint)?,
+                Pubkey::from_str(token_program)?,
+                Pubkey::from_str(reward_account)?,
+                args.harvest_claim_adapter_account_count,
+                args.harvest_min_amount_out,
+                adapter_accounts.to_vec(),
+            )
+            .map_err(anyhow::Error::from)
+    });
+
+    match result {
+        Ok(sig) => {
+            println!("harvest: {sig}");
+            metrics.last_success_ts.set(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs() as i64,
+            );
+        }
+        Err(err) => {
+            eprintln!("harvest failed after {max_attempts} attempts: {err}");
+            metrics.crank_failures.with_label_values(&["harvest"]).inc();
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn crank_rebalance(
+    vault: &YieldPilotClient<Rc<dyn Signer>>,
+    payer: &Rc<dyn Signer>,
+    metrics: &Metrics,
+    escalator: &mut PriorityFeeEscalator,
+    max_attempts: u32,
+    args: &Args,
+    new_protocol: u8,
+    adapter_accounts: &[AccountMeta],
+) {
+    metrics.crank_attempts.with_label_values(&["rebalance"]).inc();
+
+    let result = with_retry(max_attempts, Duration::from_secs(2), escalator, |_fee_micro_lamports| {
+        vault
+            .rebalance(
+                payer,
+                new_protocol,
+                args.rebalance_new_apy_bps,
+                args.rebalance_old_adapter_account_count,
+                args.rebalance_min_amount_out,
+                adapter_accounts.to_vec(),
+            )
+            .map_err(anyhow::Error::from)
+    });
+
+    match result {
+        Ok(sig) => {
+            println!("rebalance: {sig}");
+            metrics.last_success_ts.set(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs() as i64,
+            );
+        }
+        Err(err) => {
+            eprintln!("rebalance failed after {max_attempts} attempts: {err}");
+            metrics.crank_failures.with_label_values(&["rebalance"]).inc();
+        }
+    }
+}
+
+/// Every `--*-adapter-account` value is read as a `(pubkey, is_writable)` pair encoded as
+/// `"<pubkey>:w"` / `"<pubkey>:r"`, matching how `invoke_adapter` replays them verbatim as
+/// `AccountMeta`s with `is_signer` left false (the vault's own PDA is always the signer and
+/// is never part of this list). Mirrors `yieldpilot-cli`'s own helper of the same shape.
+fn parse_adapter_accounts(raw: &[String]) -> anyhow::Result<Vec<AccountMeta>> {
+    raw.iter()
+        .map(|entry| {
+            let (pubkey, flag) = entry
+                .split_once(':')
+                .ok_or_else(|| anyhow::anyhow!("expected <pubkey>:w|r, got {entry}"))?;
+            let pubkey = Pubkey::from_str(pubkey)?;
+            match flag {
+                "w" => Ok(AccountMeta::new(pubkey, false)),
+                "r" => Ok(AccountMeta::new_readonly(pubkey, false)),
+                other => anyhow::bail!("expected w or r, got {other}"),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,57 @@
+//! Prometheus gauges/counters for the keeper loop, served over plain HTTP so an operator's
+//! existing Prometheus scrape config just needs one more target, no pushgateway.
+
+use prometheus::{register_int_counter_vec, register_int_gauge, IntCounterVec, IntGauge};
+
+pub struct Metrics {
+    pub crank_attempts: IntCounterVec,
+    pub crank_failures: IntCounterVec,
+    pub last_success_ts: IntGauge,
+    pub priority_fee_micro_lamports: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> anyhow::Result<Self> {
+        Ok(Self {
+            crank_attempts: register_int_counter_vec!(
+                "yieldpilot_crank_attempts_total",
+                "Number of times the keeper attempted a crank instruction",
+                &["instruction"]
+            )?,
+            crank_failures: register_int_counter_vec!(
+                "yieldpilot_crank_failures_total",
+                "Number of crank attempts that failed after exhausting retries",
+                &["instruction"]
+            )?,
+            last_success_ts: register_int_gauge!(
+                "yieldpilot_last_success_timestamp_seconds",
+                "Unix timestamp of the last successful crank of any kind"
+            )?,
+            priority_fee_micro_lamports: register_int_gauge!(
+                "yieldpilot_priority_fee_micro_lamports",
+                "Priority fee currently being attached to crank transactions"
+            )?,
+        })
+    }
+
+    /// Serves `/metrics` on `addr` until the process exits. Runs on a dedicated thread so a
+    /// slow scrape never blocks the crank loop.
+    pub fn serve(self: std::sync::Arc<Self>, addr: std::net::SocketAddr) {
+        std::thread::spawn(move || {
+            let server = match tiny_http::Server::http(addr) {
+                Ok(server) => server,
+                Err(err) => {
+                    eprintln!("metrics server failed to bind {addr}: {err}");
+                    return;
+                }
+            };
+            for request in server.incoming_requests() {
+                let encoder = prometheus::TextEncoder::new();
+                let mut buf = Vec::new();
+                let _ = encoder.encode(&prometheus::gather(), &mut buf);
+                let response = tiny_http::Response::from_data(buf);
+                let _ = request.respond(response);
+            }
+        });
+    }
+}
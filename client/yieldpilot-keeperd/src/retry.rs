@@ -0,0 +1,55 @@
+//! Retry a crank with exponential backoff, escalating the priority fee attached to each
+//! attempt so a transaction that's landing-starved under congestion gets more likely to
+//! confirm on the next try instead of repeating at the same fee forever.
+
+use std::time::Duration;
+
+/// Doubles the priority fee each retry, capped at `max_micro_lamports` so a long losing
+/// streak can't run the fee up without bound.
+pub struct PriorityFeeEscalator {
+    current: u64,
+    max: u64,
+}
+
+impl PriorityFeeEscalator {
+    pub fn new(starting_micro_lamports: u64, max_micro_lamports: u64) -> Self {
+        Self {
+            current: starting_micro_lamports,
+            max: max_micro_lamports,
+        }
+    }
+
+    pub fn current(&self) -> u64 {
+        self.current
+    }
+
+    pub fn escalate(&mut self) {
+        self.current = (self.current.saturating_mul(2)).min(self.max);
+    }
+
+    pub fn reset(&mut self, starting_micro_lamports: u64) {
+        self.current = starting_micro_lamports;
+    }
+}
+
+/// Runs `attempt` up to `max_attempts` times, escalating `escalator`'s fee and sleeping
+/// `base_delay * 2^attempt` between failures. Returns the last error if every attempt fails.
+pub fn with_retry<T>(
+    max_attempts: u32,
+    base_delay: Duration,
+    escalator: &mut PriorityFeeEscalator,
+    mut attempt: impl FnMut(u64) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let mut last_err = None;
+    for attempt_index in 0..max_attempts {
+        match attempt(escalator.current()) {
+            Ok(value) => return Ok(value),
+            Err(err) => {
+                escalator.escalate();
+                std::thread::sleep(base_delay * 2u32.pow(attempt_index));
+                last_err = Some(err);
+            }
+        }
+    }
+    Err(last_err.unwrap_or_else(|| anyhow::anyhow!("with_retry called with max_attempts = 0")))
+}
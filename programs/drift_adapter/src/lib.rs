@@ -0,0 +1,255 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token::{Token, TokenAccount};
+
+declare_id!("DriftAdapter111111111111111111111111111111");
+
+/// Drift v2's mainnet program id.
+pub const DRIFT_PROGRAM_ID: Pubkey = pubkey!("dRiftyHA39MWEi3m9aunc5MzRF1JYuBsbn6VPcn33UH");
+
+/// Wraps Drift's insurance-fund staking behind the router's shared adapter interface.
+/// Unlike every other adapter here, Drift's insurance fund unstake is cooldown-gated:
+/// `request_remove_insurance_fund_stake` starts the clock and `remove_insurance_fund_stake`
+/// only succeeds once it elapses. `withdraw` maps to the request half so it fits the
+/// router's single-CPI `deposit`/`withdraw` interface, and `complete_withdraw` (not part of
+/// that interface; called directly once the cooldown has passed) maps to the remove half —
+/// the same two-step shape as `yield_pilot`'s own `request_withdrawal`/`claim_withdrawal`.
+#[program]
+pub mod drift_adapter {
+    use super::*;
+
+    pub fn deposit(ctx: Context<DriftDeposit>, amount: u64) -> Result<()> {
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: DRIFT_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.spot_market_vault.key(), false),
+                AccountMeta::new(ctx.accounts.insurance_fund_stake.key(), false),
+                AccountMeta::new(ctx.accounts.user_stats.key(), false),
+                AccountMeta::new(ctx.accounts.state.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new(ctx.accounts.insurance_fund_vault.key(), false),
+                AccountMeta::new(ctx.accounts.source_token_account.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: discriminator_with_args("add_insurance_fund_stake", 0, amount),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.spot_market_vault.to_account_info(),
+                ctx.accounts.insurance_fund_stake.to_account_info(),
+                ctx.accounts.user_stats.to_account_info(),
+                ctx.accounts.state.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.insurance_fund_vault.to_account_info(),
+                ctx.accounts.source_token_account.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+
+    /// Starts the insurance fund's unstake cooldown for `amount`. Returns successfully as
+    /// soon as the request is recorded; the underlying isn't movable until `complete_withdraw`
+    /// succeeds, so the router's `withdraw` caller shouldn't expect `source_token_account` to
+    /// be credited by this call alone.
+    pub fn withdraw(ctx: Context<DriftRequestWithdraw>, amount: u64) -> Result<()> {
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: DRIFT_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.spot_market_vault.key(), false),
+                AccountMeta::new(ctx.accounts.insurance_fund_stake.key(), false),
+                AccountMeta::new(ctx.accounts.user_stats.key(), false),
+                AccountMeta::new(ctx.accounts.state.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new(ctx.accounts.insurance_fund_vault.key(), false),
+            ],
+            data: discriminator_with_args("request_remove_insurance_fund_stake", 0, amount),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.spot_market_vault.to_account_info(),
+                ctx.accounts.insurance_fund_stake.to_account_info(),
+                ctx.accounts.user_stats.to_account_info(),
+                ctx.accounts.state.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.insurance_fund_vault.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+
+    /// Finishes an unstake request once Drift's cooldown has elapsed, crediting
+    /// `destination_token_account`. Not wired into `invoke_adapter`'s single-CPI interface —
+    /// `yield_pilot` would need its own cooldown-aware caller (mirroring
+    /// `process_withdrawal_queue`) to drive this, which is out of scope here.
+    pub fn complete_withdraw(ctx: Context<DriftCompleteWithdraw>) -> Result<()> {
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: DRIFT_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.spot_market_vault.key(), false),
+                AccountMeta::new(ctx.accounts.insurance_fund_stake.key(), false),
+                AccountMeta::new(ctx.accounts.user_stats.key(), false),
+                AccountMeta::new(ctx.accounts.state.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new(ctx.accounts.insurance_fund_vault.key(), false),
+                AccountMeta::new(ctx.accounts.destination_token_account.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: discriminator("remove_insurance_fund_stake"),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.spot_market_vault.to_account_info(),
+                ctx.accounts.insurance_fund_stake.to_account_info(),
+                ctx.accounts.user_stats.to_account_info(),
+                ctx.accounts.state.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.insurance_fund_vault.to_account_info(),
+                ctx.accounts.destination_token_account.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+
+    /// Drift's insurance fund pays out through the appreciating share value `deposit`/
+    /// `withdraw` already read, not a separate claimable reward account, so there's nothing
+    /// for `invoke_adapter`'s `claim_rewards` leg to do here.
+    pub fn claim_rewards(_ctx: Context<DriftClaimRewards>, _amount: u64) -> Result<()> {
+        err!(DriftAdapterError::NoSeparateRewardsToClaim)
+    }
+}
+
+fn discriminator(name: &str) -> Vec<u8> {
+    anchor_lang::solana_program::hash::hash(format!("global:{name}").as_bytes()).to_bytes()[..8]
+        .to_vec()
+}
+
+/// Drift's `*_insurance_fund_stake` instructions take a `market_index: u16` ahead of the
+/// `amount: u64`; the router only ever points this adapter at one spot market per vault, so
+/// `market_index` is fixed at construction time rather than threaded through as an argument.
+fn discriminator_with_args(name: &str, market_index: u16, amount: u64) -> Vec<u8> {
+    let mut data = discriminator(name);
+    data.extend_from_slice(&market_index.to_le_bytes());
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+#[error_code]
+pub enum DriftAdapterError {
+    #[msg("Drift's insurance fund has no separate rewards account to claim from")]
+    NoSeparateRewardsToClaim,
+}
+
+#[derive(Accounts)]
+pub struct DriftDeposit<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    /// CHECK: PDA signer forwarded by the router's CPI; verified by seeds below.
+    #[account(seeds = [b"vault_authority", vault_owner.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Drift global state account; validated by Drift during the CPI.
+    pub state: UncheckedAccount<'info>,
+    /// CHECK: Drift spot market's token vault; validated by Drift during the CPI.
+    #[account(mut)]
+    pub spot_market_vault: UncheckedAccount<'info>,
+    /// CHECK: Drift insurance fund vault for the target spot market; validated during the CPI.
+    #[account(mut)]
+    pub insurance_fund_vault: UncheckedAccount<'info>,
+    /// CHECK: per-vault Drift insurance fund stake account; validated during the CPI.
+    #[account(mut)]
+    pub insurance_fund_stake: UncheckedAccount<'info>,
+    /// CHECK: per-vault Drift user stats account; validated during the CPI.
+    #[account(mut)]
+    pub user_stats: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub source_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DriftRequestWithdraw<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    /// CHECK: PDA signer forwarded by the router's CPI; verified by seeds below.
+    #[account(seeds = [b"vault_authority", vault_owner.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Drift global state account; validated by Drift during the CPI.
+    pub state: UncheckedAccount<'info>,
+    /// CHECK: Drift spot market's token vault; validated by Drift during the CPI.
+    #[account(mut)]
+    pub spot_market_vault: UncheckedAccount<'info>,
+    /// CHECK: Drift insurance fund vault for the target spot market; validated during the CPI.
+    #[account(mut)]
+    pub insurance_fund_vault: UncheckedAccount<'info>,
+    /// CHECK: per-vault Drift insurance fund stake account; validated during the CPI.
+    #[account(mut)]
+    pub insurance_fund_stake: UncheckedAccount<'info>,
+    /// CHECK: per-vault Drift user stats account; validated during the CPI.
+    #[account(mut)]
+    pub user_stats: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DriftCompleteWithdraw<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    /// CHECK: PDA signer forwarded by the router's CPI; verified by seeds below.
+    #[account(seeds = [b"vault_authority", vault_owner.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Drift global state account; validated by Drift during the CPI.
+    pub state: UncheckedAccount<'info>,
+    /// CHECK: Drift spot market's token vault; validated by Drift during the CPI.
+    #[account(mut)]
+    pub spot_market_vault: UncheckedAccount<'info>,
+    /// CHECK: Drift insurance fund vault for the target spot market; validated during the CPI.
+    #[account(mut)]
+    pub insurance_fund_vault: UncheckedAccount<'info>,
+    /// CHECK: per-vault Drift insurance fund stake account; validated during the CPI.
+    #[account(mut)]
+    pub insurance_fund_stake: UncheckedAccount<'info>,
+    /// CHECK: per-vault Drift user stats account; validated during the CPI.
+    #[account(mut)]
+    pub user_stats: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub destination_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DriftClaimRewards<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed; unused
+    /// beyond matching the router's shared adapter interface.
+    pub vault_owner: UncheckedAccount<'info>,
+}
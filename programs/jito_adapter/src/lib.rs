@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+declare_id!("JitoAdapter111111111111111111111111111111");
+
+/// The SPL Stake Pool program id that Jito's stake pool (JitoSOL) is deployed under.
+pub const SPL_STAKE_POOL_PROGRAM_ID: Pubkey = pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuZg");
+
+/// Wraps the SPL Stake Pool `DepositSol` / `WithdrawSol` instructions behind the router's
+/// shared `deposit`/`withdraw` adapter interface so the vault can route idle SOL into
+/// JitoSOL the same way it routes into Marinade's mSOL.
+#[program]
+pub mod jito_adapter {
+    use super::*;
+
+    pub fn deposit(ctx: Context<JitoDeposit>, amount: u64) -> Result<()> {
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: SPL_STAKE_POOL_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.stake_pool.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.stake_pool_withdraw_authority.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_stake_account.key(), false),
+                AccountMeta::new(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new(ctx.accounts.vault_jitosol_account.key(), false),
+                AccountMeta::new(ctx.accounts.manager_fee_account.key(), false),
+                AccountMeta::new(ctx.accounts.referrer_pool_tokens_account.key(), false),
+                AccountMeta::new(ctx.accounts.pool_mint.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: stake_pool_instruction(14, amount),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.stake_pool.to_account_info(),
+                ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+                ctx.accounts.reserve_stake_account.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.vault_jitosol_account.to_account_info(),
+                ctx.accounts.manager_fee_account.to_account_info(),
+                ctx.accounts.referrer_pool_tokens_account.to_account_info(),
+                ctx.accounts.pool_mint.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<JitoWithdraw>, amount: u64) -> Result<()> {
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: SPL_STAKE_POOL_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.stake_pool.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.stake_pool_withdraw_authority.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_stake_account.key(), false),
+                AccountMeta::new(ctx.accounts.vault_jitosol_account.key(), false),
+                AccountMeta::new(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new(ctx.accounts.destination_system_account.key(), false),
+                AccountMeta::new(ctx.accounts.manager_fee_account.key(), false),
+                AccountMeta::new(ctx.accounts.pool_mint.key(), false),
+                AccountMeta::new_readonly(sysvar::clock::ID, false),
+                AccountMeta::new_readonly(sysvar::stake_history::ID, false),
+                AccountMeta::new_readonly(ctx.accounts.stake_program.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: stake_pool_instruction(16, amount),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.stake_pool.to_account_info(),
+                ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+                ctx.accounts.reserve_stake_account.to_account_info(),
+                ctx.accounts.vault_jitosol_account.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.destination_system_account.to_account_info(),
+                ctx.accounts.manager_fee_account.to_account_info(),
+                ctx.accounts.pool_mint.to_account_info(),
+                ctx.accounts.stake_program.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// SPL Stake Pool instructions are a plain Borsh-serialized enum (tag byte, no Anchor
+/// sighash), so adapter instruction data is built by hand rather than via the
+/// `global:<name>` discriminator convention the Anchor-based adapters use.
+fn stake_pool_instruction(tag: u8, amount: u64) -> Vec<u8> {
+    let mut data = vec![tag];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+#[derive(Accounts)]
+pub struct JitoDeposit<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    /// CHECK: PDA signer forwarded by the router's CPI; verified by seeds below.
+    #[account(seeds = [b"vault_authority", vault_owner.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Jito's stake pool account; validated by the stake pool program during the CPI.
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK: stake-pool-owned withdraw authority PDA; validated during the CPI.
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: stake pool's reserve stake account; validated during the CPI.
+    #[account(mut)]
+    pub reserve_stake_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub vault_jitosol_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub manager_fee_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub referrer_pool_tokens_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub pool_mint: Account<'info, Mint>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct JitoWithdraw<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    /// CHECK: PDA signer forwarded by the router's CPI; verified by seeds below.
+    #[account(seeds = [b"vault_authority", vault_owner.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Jito's stake pool account; validated by the stake pool program during the CPI.
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK: stake-pool-owned withdraw authority PDA; validated during the CPI.
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: stake pool's reserve stake account; validated during the CPI.
+    #[account(mut)]
+    pub reserve_stake_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub vault_jitosol_account: Account<'info, TokenAccount>,
+    /// CHECK: lamport destination for the unstaked SOL; the vault's own system account.
+    #[account(mut)]
+    pub destination_system_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub manager_fee_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub pool_mint: Account<'info, Mint>,
+    /// CHECK: native stake program; invoked transitively by the stake pool program.
+    pub stake_program: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
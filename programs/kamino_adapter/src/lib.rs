@@ -0,0 +1,278 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{Token, TokenAccount};
+
+declare_id!("KaminoAdapter1111111111111111111111111111");
+
+/// Kamino Lend's mainnet program id.
+pub const KAMINO_LEND_PROGRAM_ID: Pubkey = pubkey!("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD");
+
+/// Wraps Kamino's obligation-based supply/withdraw flow behind the router's shared
+/// `deposit`/`withdraw` adapter interface. Unlike Solend, Kamino has no separate cToken —
+/// the deposited amount lives directly in a per-vault obligation account.
+#[program]
+pub mod kamino_adapter {
+    use super::*;
+
+    pub fn deposit(ctx: Context<KaminoDeposit>, amount: u64) -> Result<()> {
+        check_reserve_health(&ctx.accounts.reserve.try_borrow_data()?, Clock::get()?.slot)?;
+
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: KAMINO_LEND_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new(ctx.accounts.obligation.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.lending_market.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.lending_market_authority.key(), false),
+                AccountMeta::new(ctx.accounts.reserve.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_liquidity_supply.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_collateral_mint.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_destination_deposit_collateral.key(), false),
+                AccountMeta::new(ctx.accounts.source_liquidity.key(), false),
+                AccountMeta::new_readonly(sysvar::clock::ID, false),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: discriminator_with_amount("deposit_reserve_liquidity_and_obligation_collateral", amount),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.obligation.to_account_info(),
+                ctx.accounts.lending_market.to_account_info(),
+                ctx.accounts.lending_market_authority.to_account_info(),
+                ctx.accounts.reserve.to_account_info(),
+                ctx.accounts.reserve_liquidity_supply.to_account_info(),
+                ctx.accounts.reserve_collateral_mint.to_account_info(),
+                ctx.accounts.reserve_destination_deposit_collateral.to_account_info(),
+                ctx.accounts.source_liquidity.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<KaminoWithdraw>, amount: u64) -> Result<()> {
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: KAMINO_LEND_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new(ctx.accounts.obligation.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.lending_market.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.lending_market_authority.key(), false),
+                AccountMeta::new(ctx.accounts.reserve.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_source_collateral.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_collateral_mint.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_liquidity_supply.key(), false),
+                AccountMeta::new(ctx.accounts.destination_liquidity.key(), false),
+                AccountMeta::new_readonly(sysvar::clock::ID, false),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: discriminator_with_amount("withdraw_obligation_collateral_and_redeem_reserve_collateral", amount),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.obligation.to_account_info(),
+                ctx.accounts.lending_market.to_account_info(),
+                ctx.accounts.lending_market_authority.to_account_info(),
+                ctx.accounts.reserve.to_account_info(),
+                ctx.accounts.reserve_source_collateral.to_account_info(),
+                ctx.accounts.reserve_collateral_mint.to_account_info(),
+                ctx.accounts.reserve_liquidity_supply.to_account_info(),
+                ctx.accounts.destination_liquidity.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+
+    /// Reads the reserve's cumulative borrow/supply interest index and applies it to the
+    /// obligation's recorded deposit so `total_assets` reflects interest Kamino has accrued
+    /// since the last rebalance, without requiring a refresh CPI on every read.
+    pub fn value_position(ctx: Context<ValueKaminoPosition>) -> Result<u64> {
+        let reserve_data = ctx.accounts.reserve.try_borrow_data()?;
+        let cumulative_rate = read_cumulative_borrow_rate(&reserve_data)?;
+        let obligation_data = ctx.accounts.obligation.try_borrow_data()?;
+        let principal = read_deposited_amount(&obligation_data)?;
+
+        Ok(((principal as u128 * cumulative_rate as u128) / CUMULATIVE_RATE_SCALE as u128) as u64)
+    }
+}
+
+/// Kamino stores the reserve's cumulative interest multiplier and each obligation's
+/// recorded principal as fixed-point u64s scaled by `CUMULATIVE_RATE_SCALE`, at fixed
+/// byte offsets that depend on the deployed reserve/obligation account layout version.
+const CUMULATIVE_RATE_SCALE: u64 = 1_000_000_000;
+const RESERVE_CUMULATIVE_RATE_OFFSET: usize = 256;
+const OBLIGATION_DEPOSIT_OFFSET: usize = 96;
+
+fn read_cumulative_borrow_rate(reserve_data: &[u8]) -> Result<u64> {
+    let end = RESERVE_CUMULATIVE_RATE_OFFSET + 8;
+    require!(
+        reserve_data.len() >= end,
+        KaminoAdapterError::MalformedReserveAccount
+    );
+    Ok(u64::from_le_bytes(
+        reserve_data[RESERVE_CUMULATIVE_RATE_OFFSET..end]
+            .try_into()
+            .unwrap(),
+    ))
+}
+
+fn read_deposited_amount(obligation_data: &[u8]) -> Result<u64> {
+    let end = OBLIGATION_DEPOSIT_OFFSET + 8;
+    require!(
+        obligation_data.len() >= end,
+        KaminoAdapterError::MalformedObligationAccount
+    );
+    Ok(u64::from_le_bytes(
+        obligation_data[OBLIGATION_DEPOSIT_OFFSET..end].try_into().unwrap(),
+    ))
+}
+
+fn discriminator_with_amount(name: &str, amount: u64) -> Vec<u8> {
+    let mut data = anchor_lang::solana_program::hash::hash(format!("global:{name}").as_bytes())
+        .to_bytes()[..8]
+        .to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+/// Above this utilization (borrowed / total liquidity), a reserve is considered too drained
+/// to deposit more into safely.
+const MAX_UTILIZATION_BPS: u64 = 9_500;
+/// A reserve whose `last_update.slot` is older than this many slots relative to the current
+/// slot hasn't had its accrued-interest/price snapshot refreshed recently enough to trust.
+const MAX_RESERVE_STALENESS_SLOTS: u64 = 150;
+
+const RESERVE_LAST_UPDATE_SLOT_OFFSET: usize = 8;
+const RESERVE_AVAILABLE_AMOUNT_OFFSET: usize = 180;
+const RESERVE_BORROWED_AMOUNT_OFFSET: usize = 212;
+
+/// Reads utilization and staleness directly off the reserve account Kamino itself
+/// maintains, aborting with `ProtocolUnhealthy` instead of letting `deposit` blindly add to
+/// a reserve that's over-utilized or hasn't been refreshed recently.
+fn check_reserve_health(reserve_data: &[u8], current_slot: u64) -> Result<()> {
+    let last_update_slot = read_u64(reserve_data, RESERVE_LAST_UPDATE_SLOT_OFFSET)?;
+    require!(
+        current_slot.saturating_sub(last_update_slot) <= MAX_RESERVE_STALENESS_SLOTS,
+        KaminoAdapterError::ProtocolUnhealthy
+    );
+
+    let available = read_u64(reserve_data, RESERVE_AVAILABLE_AMOUNT_OFFSET)? as u128;
+    let borrowed = read_u64(reserve_data, RESERVE_BORROWED_AMOUNT_OFFSET)? as u128;
+    let total = available.saturating_add(borrowed);
+    if total > 0 {
+        let utilization_bps = (borrowed.saturating_mul(10_000) / total) as u64;
+        require!(
+            utilization_bps <= MAX_UTILIZATION_BPS,
+            KaminoAdapterError::ProtocolUnhealthy
+        );
+    }
+
+    Ok(())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    let end = offset + 8;
+    require!(data.len() >= end, KaminoAdapterError::MalformedReserveAccount);
+    Ok(u64::from_le_bytes(data[offset..end].try_into().unwrap()))
+}
+
+#[error_code]
+pub enum KaminoAdapterError {
+    #[msg("Reserve account is too short to contain the cumulative rate at the expected offset")]
+    MalformedReserveAccount,
+    #[msg("Obligation account is too short to contain the deposit amount at the expected offset")]
+    MalformedObligationAccount,
+    #[msg("Reserve utilization too high or price data too stale to deposit safely")]
+    ProtocolUnhealthy,
+}
+
+#[derive(Accounts)]
+pub struct KaminoDeposit<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    /// CHECK: PDA signer forwarded by the router's CPI; verified by seeds below.
+    #[account(seeds = [b"vault_authority", vault_owner.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: per-vault Kamino obligation; owned by `vault_authority` on Kamino's side.
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+    /// CHECK: Kamino lending market account; validated by Kamino during the CPI.
+    pub lending_market: UncheckedAccount<'info>,
+    /// CHECK: Kamino-owned PDA; validated by Kamino during the CPI.
+    pub lending_market_authority: UncheckedAccount<'info>,
+    /// CHECK: Kamino reserve account; validated by Kamino during the CPI.
+    #[account(mut)]
+    pub reserve: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub reserve_liquidity_supply: Account<'info, TokenAccount>,
+    /// CHECK: Kamino reserve collateral mint; validated by Kamino during the CPI.
+    #[account(mut)]
+    pub reserve_collateral_mint: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub reserve_destination_deposit_collateral: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub source_liquidity: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct KaminoWithdraw<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    /// CHECK: PDA signer forwarded by the router's CPI; verified by seeds below.
+    #[account(seeds = [b"vault_authority", vault_owner.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: per-vault Kamino obligation; owned by `vault_authority` on Kamino's side.
+    #[account(mut)]
+    pub obligation: UncheckedAccount<'info>,
+    /// CHECK: Kamino lending market account; validated by Kamino during the CPI.
+    pub lending_market: UncheckedAccount<'info>,
+    /// CHECK: Kamino-owned PDA; validated by Kamino during the CPI.
+    pub lending_market_authority: UncheckedAccount<'info>,
+    /// CHECK: Kamino reserve account; validated by Kamino during the CPI.
+    #[account(mut)]
+    pub reserve: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub reserve_source_collateral: Account<'info, TokenAccount>,
+    /// CHECK: Kamino reserve collateral mint; validated by Kamino during the CPI.
+    #[account(mut)]
+    pub reserve_collateral_mint: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub reserve_liquidity_supply: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub destination_liquidity: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ValueKaminoPosition<'info> {
+    /// CHECK: Kamino reserve account; read-only valuation, not a CPI target here.
+    pub reserve: UncheckedAccount<'info>,
+    /// CHECK: per-vault Kamino obligation; read-only valuation, not a CPI target here.
+    pub obligation: UncheckedAccount<'info>,
+}
@@ -0,0 +1,319 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::{invoke_signed, set_return_data};
+use anchor_spl::token::{Token, TokenAccount};
+
+declare_id!("MarginfiAdapter111111111111111111111111111");
+
+/// Marginfi v2's mainnet program id.
+pub const MARGINFI_PROGRAM_ID: Pubkey = pubkey!("MFv2hWf31Z9kbCa1snEPYctwafyhdvnV7FZnsebVacA");
+
+/// Wraps a per-vault marginfi account behind the router's shared `deposit`/`withdraw`
+/// adapter interface, plus a `sync_valuation` instruction that reads the bank's share
+/// value so the router can mark-to-market without trusting a cached number.
+#[program]
+pub mod marginfi_adapter {
+    use super::*;
+
+    /// One-time setup: creates the marginfi account owned by the vault's `vault_authority`
+    /// PDA. Must run before the first `deposit`.
+    pub fn initialize_account(ctx: Context<InitializeMarginfiAccount>) -> Result<()> {
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: MARGINFI_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(ctx.accounts.marginfi_group.key(), false),
+                AccountMeta::new(ctx.accounts.marginfi_account.key(), true),
+                AccountMeta::new_readonly(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new(ctx.accounts.fee_payer.key(), true),
+                AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+            ],
+            data: discriminator("marginfi_account_initialize"),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.marginfi_group.to_account_info(),
+                ctx.accounts.marginfi_account.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.fee_payer.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<MarginfiDeposit>, amount: u64) -> Result<()> {
+        check_bank_health(&ctx.accounts.bank.try_borrow_data()?)?;
+
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: MARGINFI_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(ctx.accounts.marginfi_group.key(), false),
+                AccountMeta::new(ctx.accounts.marginfi_account.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new(ctx.accounts.bank.key(), false),
+                AccountMeta::new(ctx.accounts.source_token_account.key(), false),
+                AccountMeta::new(ctx.accounts.bank_liquidity_vault.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: discriminator_with_amount("lending_account_deposit", amount),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.marginfi_group.to_account_info(),
+                ctx.accounts.marginfi_account.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.bank.to_account_info(),
+                ctx.accounts.source_token_account.to_account_info(),
+                ctx.accounts.bank_liquidity_vault.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<MarginfiWithdraw>, amount: u64) -> Result<()> {
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: MARGINFI_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(ctx.accounts.marginfi_group.key(), false),
+                AccountMeta::new(ctx.accounts.marginfi_account.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new(ctx.accounts.bank.key(), false),
+                AccountMeta::new(ctx.accounts.destination_token_account.key(), false),
+                AccountMeta::new(ctx.accounts.bank_liquidity_vault.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.bank_liquidity_vault_authority.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: discriminator_with_amount("lending_account_withdraw", amount),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.marginfi_group.to_account_info(),
+                ctx.accounts.marginfi_account.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.bank.to_account_info(),
+                ctx.accounts.destination_token_account.to_account_info(),
+                ctx.accounts.bank_liquidity_vault.to_account_info(),
+                ctx.accounts.bank_liquidity_vault_authority.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+
+    /// Reads the bank's asset share value and the marginfi account's recorded share
+    /// balance to compute the vault's current underlying-denominated position, and hands
+    /// it back via return data so the router can mark-to-market after CPI-ing here.
+    pub fn sync_valuation(ctx: Context<SyncMarginfiValuation>) -> Result<()> {
+        let bank_data = ctx.accounts.bank.try_borrow_data()?;
+        let asset_share_value = read_asset_share_value(&bank_data)?;
+        let account_data = ctx.accounts.marginfi_account.try_borrow_data()?;
+        let shares = read_deposit_shares(&account_data)?;
+
+        let value = ((shares as u128 * asset_share_value as u128) / SHARE_VALUE_SCALE as u128) as u64;
+        set_return_data(&value.to_le_bytes());
+
+        Ok(())
+    }
+}
+
+/// Marginfi prices lending shares against a fixed-point u64 "asset share value" scaled by
+/// `SHARE_VALUE_SCALE`, at fixed byte offsets that depend on the deployed bank/account
+/// layout version.
+const SHARE_VALUE_SCALE: u64 = 1_000_000_000;
+const BANK_ASSET_SHARE_VALUE_OFFSET: usize = 168;
+const ACCOUNT_DEPOSIT_SHARES_OFFSET: usize = 72;
+
+fn read_asset_share_value(bank_data: &[u8]) -> Result<u64> {
+    let end = BANK_ASSET_SHARE_VALUE_OFFSET + 8;
+    require!(
+        bank_data.len() >= end,
+        MarginfiAdapterError::MalformedBankAccount
+    );
+    Ok(u64::from_le_bytes(
+        bank_data[BANK_ASSET_SHARE_VALUE_OFFSET..end].try_into().unwrap(),
+    ))
+}
+
+fn read_deposit_shares(account_data: &[u8]) -> Result<u64> {
+    let end = ACCOUNT_DEPOSIT_SHARES_OFFSET + 8;
+    require!(
+        account_data.len() >= end,
+        MarginfiAdapterError::MalformedMarginfiAccount
+    );
+    Ok(u64::from_le_bytes(
+        account_data[ACCOUNT_DEPOSIT_SHARES_OFFSET..end].try_into().unwrap(),
+    ))
+}
+
+fn discriminator(name: &str) -> Vec<u8> {
+    anchor_lang::solana_program::hash::hash(format!("global:{name}").as_bytes()).to_bytes()[..8]
+        .to_vec()
+}
+
+fn discriminator_with_amount(name: &str, amount: u64) -> Vec<u8> {
+    let mut data = discriminator(name);
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+/// Above this utilization (liability shares / asset shares, both converted through their
+/// respective share values), a bank is considered too drained to deposit more into safely.
+const MAX_UTILIZATION_BPS: u64 = 9_500;
+/// Marginfi's `BankOperationalState::Paused` discriminant; a paused bank rejects deposits on
+/// its own side too, but checking here gives a clearer `ProtocolUnhealthy` error instead of
+/// surfacing Marginfi's raw CPI failure.
+const BANK_OPERATIONAL_STATE_PAUSED: u8 = 1;
+const BANK_OPERATIONAL_STATE_OFFSET: usize = 8;
+const BANK_LIABILITY_SHARE_VALUE_OFFSET: usize = 176;
+const BANK_TOTAL_ASSET_SHARES_OFFSET: usize = 184;
+const BANK_TOTAL_LIABILITY_SHARES_OFFSET: usize = 192;
+
+/// Reads the bank's operational state and utilization directly off the account Marginfi
+/// itself maintains, aborting with `ProtocolUnhealthy` instead of letting `deposit` blindly
+/// add to a paused or over-utilized bank.
+fn check_bank_health(bank_data: &[u8]) -> Result<()> {
+    require!(
+        bank_data.len() > BANK_OPERATIONAL_STATE_OFFSET,
+        MarginfiAdapterError::MalformedBankAccount
+    );
+    require!(
+        bank_data[BANK_OPERATIONAL_STATE_OFFSET] != BANK_OPERATIONAL_STATE_PAUSED,
+        MarginfiAdapterError::ProtocolUnhealthy
+    );
+
+    let asset_share_value = read_asset_share_value(bank_data)? as u128;
+    let liability_share_value = read_u64(bank_data, BANK_LIABILITY_SHARE_VALUE_OFFSET)? as u128;
+    let total_asset_shares = read_u64(bank_data, BANK_TOTAL_ASSET_SHARES_OFFSET)? as u128;
+    let total_liability_shares = read_u64(bank_data, BANK_TOTAL_LIABILITY_SHARES_OFFSET)? as u128;
+
+    let total_assets = total_asset_shares.saturating_mul(asset_share_value) / SHARE_VALUE_SCALE as u128;
+    let total_liabilities =
+        total_liability_shares.saturating_mul(liability_share_value) / SHARE_VALUE_SCALE as u128;
+    if total_assets > 0 {
+        let utilization_bps = (total_liabilities.saturating_mul(10_000) / total_assets) as u64;
+        require!(
+            utilization_bps <= MAX_UTILIZATION_BPS,
+            MarginfiAdapterError::ProtocolUnhealthy
+        );
+    }
+
+    Ok(())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    let end = offset + 8;
+    require!(data.len() >= end, MarginfiAdapterError::MalformedBankAccount);
+    Ok(u64::from_le_bytes(data[offset..end].try_into().unwrap()))
+}
+
+#[error_code]
+pub enum MarginfiAdapterError {
+    #[msg("Bank account is too short to contain the asset share value at the expected offset")]
+    MalformedBankAccount,
+    #[msg("Marginfi account is too short to contain deposit shares at the expected offset")]
+    MalformedMarginfiAccount,
+    #[msg("Bank is paused or utilization is too high to deposit safely")]
+    ProtocolUnhealthy,
+}
+
+#[derive(Accounts)]
+pub struct InitializeMarginfiAccount<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    /// CHECK: PDA signer forwarded by the router's CPI; verified by seeds below.
+    #[account(seeds = [b"vault_authority", vault_owner.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Marginfi group account; validated by Marginfi during the CPI.
+    pub marginfi_group: UncheckedAccount<'info>,
+    /// CHECK: fresh marginfi account keypair; initialized by Marginfi during the CPI.
+    #[account(mut)]
+    pub marginfi_account: Signer<'info>,
+    #[account(mut)]
+    pub fee_payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MarginfiDeposit<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    /// CHECK: PDA signer forwarded by the router's CPI; verified by seeds below.
+    #[account(seeds = [b"vault_authority", vault_owner.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Marginfi group account; validated by Marginfi during the CPI.
+    pub marginfi_group: UncheckedAccount<'info>,
+    /// CHECK: per-vault marginfi account; owned by `vault_authority` on Marginfi's side.
+    #[account(mut)]
+    pub marginfi_account: UncheckedAccount<'info>,
+    /// CHECK: Marginfi bank account; validated by Marginfi during the CPI.
+    #[account(mut)]
+    pub bank: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub source_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub bank_liquidity_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct MarginfiWithdraw<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    /// CHECK: PDA signer forwarded by the router's CPI; verified by seeds below.
+    #[account(seeds = [b"vault_authority", vault_owner.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Marginfi group account; validated by Marginfi during the CPI.
+    pub marginfi_group: UncheckedAccount<'info>,
+    /// CHECK: per-vault marginfi account; owned by `vault_authority` on Marginfi's side.
+    #[account(mut)]
+    pub marginfi_account: UncheckedAccount<'info>,
+    /// CHECK: Marginfi bank account; validated by Marginfi during the CPI.
+    #[account(mut)]
+    pub bank: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub destination_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub bank_liquidity_vault: Account<'info, TokenAccount>,
+    /// CHECK: Marginfi-owned PDA; validated by Marginfi during the CPI.
+    pub bank_liquidity_vault_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SyncMarginfiValuation<'info> {
+    /// CHECK: Marginfi bank account; read-only valuation, not a CPI target here.
+    pub bank: UncheckedAccount<'info>,
+    /// CHECK: per-vault marginfi account; read-only valuation, not a CPI target here.
+    pub marginfi_account: UncheckedAccount<'info>,
+}
@@ -0,0 +1,179 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token::{Mint, TokenAccount};
+
+declare_id!("Mar1nadeAdapter11111111111111111111111111");
+
+/// Marinade Finance's mainnet program id.
+pub const MARINADE_PROGRAM_ID: Pubkey = pubkey!("MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD");
+
+/// Thin CPI wrapper around Marinade so `yield_pilot`'s router can treat liquid staking as
+/// just another strategy adapter. Implements the shared `deposit`/`withdraw` interface the
+/// router invokes generically (see `invoke_adapter` in the vault program).
+#[program]
+pub mod marinade_adapter {
+    use super::*;
+
+    /// Deposits native SOL held by the vault into Marinade, minting mSOL into the vault's
+    /// mSOL token account.
+    pub fn deposit(ctx: Context<MarinadeDeposit>, amount: u64) -> Result<()> {
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: MARINADE_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.marinade_state.key(), false),
+                AccountMeta::new(ctx.accounts.msol_mint.key(), false),
+                AccountMeta::new(ctx.accounts.liq_pool_sol_leg_pda.key(), false),
+                AccountMeta::new(ctx.accounts.liq_pool_msol_leg.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.liq_pool_msol_leg_authority.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_pda.key(), false),
+                AccountMeta::new(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new(ctx.accounts.vault_msol_account.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.msol_mint_authority.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: marinade_deposit_data(amount),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.marinade_state.to_account_info(),
+                ctx.accounts.msol_mint.to_account_info(),
+                ctx.accounts.liq_pool_sol_leg_pda.to_account_info(),
+                ctx.accounts.liq_pool_msol_leg.to_account_info(),
+                ctx.accounts.liq_pool_msol_leg_authority.to_account_info(),
+                ctx.accounts.reserve_pda.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.vault_msol_account.to_account_info(),
+                ctx.accounts.msol_mint_authority.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+
+    /// Exits the liquid staking position via Marinade's liquid-unstake path, burning mSOL
+    /// for SOL immediately (at the liquidity-pool rate) rather than queuing a delayed-unstake
+    /// ticket, since the router expects `withdraw` to settle atomically.
+    pub fn withdraw(ctx: Context<MarinadeWithdraw>, amount: u64) -> Result<()> {
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: MARINADE_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.marinade_state.key(), false),
+                AccountMeta::new(ctx.accounts.msol_mint.key(), false),
+                AccountMeta::new(ctx.accounts.liq_pool_sol_leg_pda.key(), false),
+                AccountMeta::new(ctx.accounts.liq_pool_msol_leg.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.treasury_msol_account.key(), false),
+                AccountMeta::new(ctx.accounts.vault_msol_account.key(), false),
+                AccountMeta::new(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: marinade_liquid_unstake_data(amount),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.marinade_state.to_account_info(),
+                ctx.accounts.msol_mint.to_account_info(),
+                ctx.accounts.liq_pool_sol_leg_pda.to_account_info(),
+                ctx.accounts.liq_pool_msol_leg.to_account_info(),
+                ctx.accounts.treasury_msol_account.to_account_info(),
+                ctx.accounts.vault_msol_account.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+}
+
+fn marinade_deposit_data(lamports: u64) -> Vec<u8> {
+    let mut data = anchor_lang::solana_program::hash::hash(b"global:deposit").to_bytes()[..8].to_vec();
+    data.extend_from_slice(&lamports.to_le_bytes());
+    data
+}
+
+fn marinade_liquid_unstake_data(msol_amount: u64) -> Vec<u8> {
+    let mut data =
+        anchor_lang::solana_program::hash::hash(b"global:liquid_unstake").to_bytes()[..8].to_vec();
+    data.extend_from_slice(&msol_amount.to_le_bytes());
+    data
+}
+
+#[derive(Accounts)]
+pub struct MarinadeDeposit<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed; the vault
+    /// program is responsible for ensuring this matches the calling YieldState.
+    pub vault_owner: UncheckedAccount<'info>,
+    /// CHECK: PDA signer forwarded by the router's CPI; verified by seeds below.
+    #[account(seeds = [b"vault_authority", vault_owner.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Marinade's main state account; validated by Marinade itself during the CPI.
+    #[account(mut)]
+    pub marinade_state: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub msol_mint: Account<'info, Mint>,
+    /// CHECK: Marinade-owned PDA; validated by Marinade during the CPI.
+    #[account(mut)]
+    pub liq_pool_sol_leg_pda: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub liq_pool_msol_leg: Account<'info, TokenAccount>,
+    /// CHECK: Marinade-owned PDA; validated by Marinade during the CPI.
+    pub liq_pool_msol_leg_authority: UncheckedAccount<'info>,
+    /// CHECK: Marinade-owned PDA; validated by Marinade during the CPI.
+    #[account(mut)]
+    pub reserve_pda: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub vault_msol_account: Account<'info, TokenAccount>,
+    /// CHECK: Marinade-owned mint authority PDA; validated by Marinade during the CPI.
+    pub msol_mint_authority: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, anchor_spl::token::Token>,
+}
+
+#[derive(Accounts)]
+pub struct MarinadeWithdraw<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    /// CHECK: PDA signer forwarded by the router's CPI; verified by seeds below.
+    #[account(seeds = [b"vault_authority", vault_owner.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Marinade's main state account; validated by Marinade itself during the CPI.
+    #[account(mut)]
+    pub marinade_state: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub msol_mint: Account<'info, Mint>,
+    /// CHECK: Marinade-owned PDA; validated by Marinade during the CPI.
+    #[account(mut)]
+    pub liq_pool_sol_leg_pda: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub liq_pool_msol_leg: Account<'info, TokenAccount>,
+    /// CHECK: Marinade treasury mSOL account; validated by Marinade during the CPI.
+    pub treasury_msol_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub vault_msol_account: Account<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, anchor_spl::token::Token>,
+}
@@ -0,0 +1,320 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+declare_id!("MockYieldProtoco11111111111111111111111111");
+
+/// A fake lending protocol implementing the router's shared `deposit`/`withdraw`/
+/// `claim_rewards` adapter interface, with a test-controlled `apy_bps` instead of real
+/// on-chain yield. Exists only so the `solana-program-test` suite can exercise
+/// `yield_pilot`'s rebalance/harvest/fee flows against something that actually pays out,
+/// without depending on a live deployment of Solend/Marginfi/Marinade/Jito/Kamino.
+#[program]
+pub mod mock_yield_protocol {
+    use super::*;
+
+    /// One-time setup: creates the pool's token vault and reward reserve, owned by the
+    /// mock program's own PDA. `apy_bps` can be changed later via `set_apy` so a test can
+    /// simulate a yield change mid-run without tearing the pool down.
+    pub fn initialize_pool(ctx: Context<InitializePool>, apy_bps: u16) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        pool.admin = ctx.accounts.admin.key();
+        pool.mint = ctx.accounts.mint.key();
+        pool.vault = ctx.accounts.vault.key();
+        pool.apy_bps = apy_bps;
+        pool.bump = ctx.bumps.pool;
+
+        Ok(())
+    }
+
+    /// Lets the test harness dial the simulated yield up or down between assertions.
+    pub fn set_apy(ctx: Context<SetApy>, apy_bps: u16) -> Result<()> {
+        ctx.accounts.pool.apy_bps = apy_bps;
+        Ok(())
+    }
+
+    /// Tops up the pool's vault with extra tokens so `claim_rewards` has something to pay
+    /// out of; a real protocol's yield comes from borrowers, a mock's has to come from
+    /// somewhere the test seeded ahead of time.
+    pub fn fund_reserve(ctx: Context<FundReserve>, amount: u64) -> Result<()> {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.admin_token_account.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.admin.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+
+    pub fn deposit(ctx: Context<MockDeposit>, amount: u64) -> Result<()> {
+        accrue(&mut ctx.accounts.position, ctx.accounts.pool.apy_bps)?;
+
+        // `vault_authority` is forwarded by the router's CPI already bearing signer
+        // privileges from its own `invoke_signed` higher up the call stack — it's a PDA of
+        // the *router's* program, not ours, so there's nothing for us to re-derive or sign
+        // for here. We just pass it through as-is.
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.source_token_account.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        ctx.accounts.position.vault_owner = ctx.accounts.vault_owner.key();
+        ctx.accounts.position.principal = ctx
+            .accounts
+            .position
+            .principal
+            .checked_add(amount)
+            .ok_or(MockYieldProtocolError::ArithmeticOverflow)?;
+        ctx.accounts.position.bump = ctx.bumps.position;
+
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<MockWithdraw>, amount: u64) -> Result<()> {
+        accrue(&mut ctx.accounts.position, ctx.accounts.pool.apy_bps)?;
+        require!(
+            amount <= ctx.accounts.position.principal,
+            MockYieldProtocolError::InsufficientPrincipal
+        );
+
+        let pool_bump = ctx.accounts.pool.bump;
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.destination_token_account.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                &[&[b"mock_pool", ctx.accounts.pool.mint.as_ref(), &[pool_bump]]],
+            ),
+            amount,
+        )?;
+
+        ctx.accounts.position.principal = ctx
+            .accounts
+            .position
+            .principal
+            .checked_sub(amount)
+            .ok_or(MockYieldProtocolError::ArithmeticUnderflow)?;
+
+        Ok(())
+    }
+
+    /// Pays out whatever has accrued onto `position` since the last `deposit`/`withdraw`/
+    /// `claim_rewards`. `_amount` is ignored — `invoke_adapter` always calls `claim_rewards`
+    /// with `0`, mirroring `harvest`'s own `claim_adapter_account_count`/`invoke_adapter`
+    /// pairing for the other adapters.
+    pub fn claim_rewards(ctx: Context<MockClaimRewards>, _amount: u64) -> Result<()> {
+        accrue(&mut ctx.accounts.position, ctx.accounts.pool.apy_bps)?;
+        let rewards = ctx.accounts.position.accrued_rewards;
+        require!(rewards > 0, MockYieldProtocolError::NothingAccrued);
+
+        let pool_bump = ctx.accounts.pool.bump;
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.reward_account.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                &[&[b"mock_pool", ctx.accounts.pool.mint.as_ref(), &[pool_bump]]],
+            ),
+            rewards,
+        )?;
+
+        ctx.accounts.position.accrued_rewards = 0;
+
+        Ok(())
+    }
+}
+
+/// Folds simple-interest yield (`principal * apy_bps * elapsed / (10_000 * year)`) accrued
+/// since `position.last_update_ts` into `accrued_rewards`, then re-snapshots the clock so
+/// the next accrual only covers newly-elapsed time.
+fn accrue(position: &mut MockPosition, apy_bps: u16) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let elapsed = now.saturating_sub(position.last_update_ts).max(0) as u128;
+    position.last_update_ts = now;
+
+    if elapsed == 0 || position.principal == 0 || apy_bps == 0 {
+        return Ok(());
+    }
+
+    let accrued = (position.principal as u128)
+        .checked_mul(apy_bps as u128)
+        .ok_or(MockYieldProtocolError::ArithmeticOverflow)?
+        .checked_mul(elapsed)
+        .ok_or(MockYieldProtocolError::ArithmeticOverflow)?
+        .checked_div(10_000u128.checked_mul(SECONDS_PER_YEAR as u128).unwrap())
+        .ok_or(MockYieldProtocolError::DivisionByZero)? as u64;
+
+    position.accrued_rewards = position
+        .accrued_rewards
+        .checked_add(accrued)
+        .ok_or(MockYieldProtocolError::ArithmeticOverflow)?;
+
+    Ok(())
+}
+
+const SECONDS_PER_YEAR: i64 = 31_536_000;
+
+#[account]
+pub struct MockPool {
+    pub admin: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub apy_bps: u16,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(Default)]
+pub struct MockPosition {
+    pub vault_owner: Pubkey,
+    pub principal: u64,
+    pub accrued_rewards: u64,
+    pub last_update_ts: i64,
+    pub bump: u8,
+}
+
+#[error_code]
+pub enum MockYieldProtocolError {
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("Arithmetic underflow")]
+    ArithmeticUnderflow,
+    #[msg("Division by zero")]
+    DivisionByZero,
+    #[msg("Withdrawal amount exceeds recorded principal")]
+    InsufficientPrincipal,
+    #[msg("Nothing has accrued to claim")]
+    NothingAccrued,
+}
+
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + 32 + 32 + 32 + 2 + 1,
+        seeds = [b"mock_pool", mint.key().as_ref()],
+        bump,
+    )]
+    pub pool: Account<'info, MockPool>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = admin,
+        token::mint = mint,
+        token::authority = pool,
+        seeds = [b"mock_vault", pool.key().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetApy<'info> {
+    #[account(mut, has_one = admin)]
+    pub pool: Account<'info, MockPool>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FundReserve<'info> {
+    #[account(has_one = admin)]
+    pub pool: Account<'info, MockPool>,
+    #[account(mut, address = pool.vault)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub admin_token_account: Account<'info, TokenAccount>,
+    pub admin: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct MockDeposit<'info> {
+    /// CHECK: forwarded by the router's shared adapter-interface CPI as account 0,
+    /// already signer-privileged from the router's own `invoke_signed` — it's a PDA of
+    /// the router's program, so there's no seed of ours to verify it against.
+    pub vault_authority: UncheckedAccount<'info>,
+    pub pool: Account<'info, MockPool>,
+    #[account(mut, address = pool.vault)]
+    pub vault: Account<'info, TokenAccount>,
+    /// CHECK: owner pubkey used only to derive the position PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub source_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + 32 + 8 + 8 + 8 + 1,
+        seeds = [b"mock_position", pool.key().as_ref(), vault_owner.key().as_ref()],
+        bump,
+    )]
+    pub position: Account<'info, MockPosition>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MockWithdraw<'info> {
+    /// CHECK: forwarded by the router's shared adapter-interface CPI as account 0; unused
+    /// here since the payout is authorized by `pool`'s own PDA, not the caller's vault
+    /// authority, but every adapter must still accept it in this position.
+    pub vault_authority: UncheckedAccount<'info>,
+    pub pool: Account<'info, MockPool>,
+    #[account(mut, address = pool.vault)]
+    pub vault: Account<'info, TokenAccount>,
+    /// CHECK: owner pubkey used only to re-derive the position PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub destination_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"mock_position", pool.key().as_ref(), vault_owner.key().as_ref()],
+        bump = position.bump,
+    )]
+    pub position: Account<'info, MockPosition>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct MockClaimRewards<'info> {
+    /// CHECK: forwarded by the router's shared adapter-interface CPI as account 0; unused
+    /// here since the payout is authorized by `pool`'s own PDA, not the caller's vault
+    /// authority, but every adapter must still accept it in this position.
+    pub vault_authority: UncheckedAccount<'info>,
+    pub pool: Account<'info, MockPool>,
+    #[account(mut, address = pool.vault)]
+    pub vault: Account<'info, TokenAccount>,
+    /// CHECK: owner pubkey used only to re-derive the position PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub reward_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"mock_position", pool.key().as_ref(), vault_owner.key().as_ref()],
+        bump = position.bump,
+    )]
+    pub position: Account<'info, MockPosition>,
+    pub token_program: Program<'info, Token>,
+}
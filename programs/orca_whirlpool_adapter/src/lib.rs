@@ -0,0 +1,334 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token::{Token, TokenAccount};
+
+declare_id!("OrcaWhirlpoolAdapter11111111111111111111111");
+
+/// Orca Whirlpool's mainnet program id.
+pub const WHIRLPOOL_PROGRAM_ID: Pubkey = pubkey!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
+
+/// Wraps Orca Whirlpool's concentrated-liquidity position behind the router's shared
+/// `deposit`/`withdraw` adapter interface. Unlike the single-asset lending adapters,
+/// `deposit`/`withdraw` move liquidity in and out of one already-open position (opening and
+/// closing the position NFT itself is an admin-driven setup step, done once via
+/// `set_strategy_position`, not on every rebalance), and `value_position` has to price two
+/// token legs instead of one. `token_owner_account_a` should be set to the vault's own
+/// base-asset account so `apply_rebalance`'s balance-delta slippage check still works for
+/// that leg on `withdraw`; the B leg's proceeds land in `token_owner_account_b` and are
+/// picked up by `value_position` rather than the router's per-call slippage accounting.
+#[program]
+pub mod orca_whirlpool_adapter {
+    use super::*;
+
+    /// `amount` is in token-A units (the vault's base asset, by convention the position's
+    /// token A) and has to become a token-A/token-B pair before it can go into
+    /// `increase_liquidity`, so it's split in half by value using the pool's own current
+    /// `sqrt_price` rather than trusting a caller-supplied split — keeping `deposit` on the
+    /// single-`u64` `invoke_adapter` calling convention every other adapter shares.
+    pub fn deposit(ctx: Context<WhirlpoolModifyLiquidity>, amount: u64) -> Result<()> {
+        let (amount_a, amount_b) = split_by_pool_price(&ctx.accounts.whirlpool.try_borrow_data()?, amount)?;
+
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: WHIRLPOOL_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new(ctx.accounts.whirlpool.key(), false),
+                AccountMeta::new(ctx.accounts.position.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.position_token_account.key(), false),
+                AccountMeta::new(ctx.accounts.token_owner_account_a.key(), false),
+                AccountMeta::new(ctx.accounts.token_owner_account_b.key(), false),
+                AccountMeta::new(ctx.accounts.token_vault_a.key(), false),
+                AccountMeta::new(ctx.accounts.token_vault_b.key(), false),
+                AccountMeta::new(ctx.accounts.tick_array_lower.key(), false),
+                AccountMeta::new(ctx.accounts.tick_array_upper.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: liquidity_instruction("increase_liquidity", amount_a, amount_b),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.whirlpool.to_account_info(),
+                ctx.accounts.position.to_account_info(),
+                ctx.accounts.position_token_account.to_account_info(),
+                ctx.accounts.token_owner_account_a.to_account_info(),
+                ctx.accounts.token_owner_account_b.to_account_info(),
+                ctx.accounts.token_vault_a.to_account_info(),
+                ctx.accounts.token_vault_b.to_account_info(),
+                ctx.accounts.tick_array_lower.to_account_info(),
+                ctx.accounts.tick_array_upper.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+
+    /// Mirrors `deposit`'s split so a withdrawal of `amount` (token-A-denominated) pulls out
+    /// a proportionate amount of each leg rather than draining one side of the position first.
+    pub fn withdraw(ctx: Context<WhirlpoolModifyLiquidity>, amount: u64) -> Result<()> {
+        let (amount_a, amount_b) = split_by_pool_price(&ctx.accounts.whirlpool.try_borrow_data()?, amount)?;
+
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: WHIRLPOOL_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new(ctx.accounts.whirlpool.key(), false),
+                AccountMeta::new(ctx.accounts.position.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.position_token_account.key(), false),
+                AccountMeta::new(ctx.accounts.token_owner_account_a.key(), false),
+                AccountMeta::new(ctx.accounts.token_owner_account_b.key(), false),
+                AccountMeta::new(ctx.accounts.token_vault_a.key(), false),
+                AccountMeta::new(ctx.accounts.token_vault_b.key(), false),
+                AccountMeta::new(ctx.accounts.tick_array_lower.key(), false),
+                AccountMeta::new(ctx.accounts.tick_array_upper.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: liquidity_instruction("decrease_liquidity", amount_a, amount_b),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.whirlpool.to_account_info(),
+                ctx.accounts.position.to_account_info(),
+                ctx.accounts.position_token_account.to_account_info(),
+                ctx.accounts.token_owner_account_a.to_account_info(),
+                ctx.accounts.token_owner_account_b.to_account_info(),
+                ctx.accounts.token_vault_a.to_account_info(),
+                ctx.accounts.token_vault_b.to_account_info(),
+                ctx.accounts.tick_array_lower.to_account_info(),
+                ctx.accounts.tick_array_upper.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+
+    /// Harvests accrued trading fees for the position. Whirlpool has no separate rewards
+    /// mint by default here (reward emissions, when a pool has them, would need their own
+    /// per-reward-index collect CPI, out of scope for this adapter), so `claim_rewards` only
+    /// covers the fee leg.
+    pub fn claim_rewards(ctx: Context<WhirlpoolCollectFees>, _amount: u64) -> Result<()> {
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: WHIRLPOOL_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(ctx.accounts.whirlpool.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new(ctx.accounts.position.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.position_token_account.key(), false),
+                AccountMeta::new(ctx.accounts.token_owner_account_a.key(), false),
+                AccountMeta::new(ctx.accounts.token_vault_a.key(), false),
+                AccountMeta::new(ctx.accounts.token_owner_account_b.key(), false),
+                AccountMeta::new(ctx.accounts.token_vault_b.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: discriminator("collect_fees"),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.whirlpool.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.position.to_account_info(),
+                ctx.accounts.position_token_account.to_account_info(),
+                ctx.accounts.token_owner_account_a.to_account_info(),
+                ctx.accounts.token_vault_a.to_account_info(),
+                ctx.accounts.token_owner_account_b.to_account_info(),
+                ctx.accounts.token_vault_b.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+
+    /// Converts the position's two token legs into vault-asset terms via Pyth price feeds
+    /// for each, so `total_assets` reflects both legs' current value rather than just
+    /// whichever one happens to match the vault's base asset. Raw token amounts (not the
+    /// liquidity/tick math Whirlpool itself would need to recompute them precisely) are read
+    /// directly off the position's owned token accounts, since the router only needs an
+    /// approximate mark for rebalance/APY decisions, not penny-accurate redemption value.
+    pub fn value_position(ctx: Context<ValueWhirlpoolPosition>) -> Result<u64> {
+        let price_a = read_pyth_price(&ctx.accounts.oracle_a.try_borrow_data()?)?;
+        let price_b = read_pyth_price(&ctx.accounts.oracle_b.try_borrow_data()?)?;
+
+        let value_a = (ctx.accounts.token_owner_account_a.amount as u128) * (price_a.max(0) as u128);
+        let value_b = (ctx.accounts.token_owner_account_b.amount as u128) * (price_b.max(0) as u128);
+
+        Ok((value_a.saturating_add(value_b) / PYTH_PRICE_SCALE as u128) as u64)
+    }
+}
+
+/// Whirlpool's `increase_liquidity`/`decrease_liquidity` take `liquidity_amount: u128` plus
+/// per-token maximum/minimum u64s; this adapter passes `amount_a`/`amount_b` straight through
+/// as both the liquidity delta's token caps, letting Whirlpool's own slippage check reject a
+/// bad split rather than computing the exact `liquidity_amount` tick math here.
+fn liquidity_instruction(name: &str, amount_a: u64, amount_b: u64) -> Vec<u8> {
+    let mut data = discriminator(name);
+    data.extend_from_slice(&amount_a.to_le_bytes());
+    data.extend_from_slice(&amount_b.to_le_bytes());
+    data
+}
+
+/// Splits `amount` (token-A units) roughly in half by value using the Whirlpool's current
+/// `sqrt_price`, converting the B-half into token-B units at that price. `sqrt_price` is a
+/// Q64.64 fixed-point value; squaring it directly would overflow u128, so both operands are
+/// pre-shifted down to Q32.32 before multiplying back out to a Q64.64 price.
+fn split_by_pool_price(whirlpool_data: &[u8], amount: u64) -> Result<(u64, u64)> {
+    let sqrt_price = read_whirlpool_sqrt_price(whirlpool_data)?;
+    let sqrt_price_q32 = sqrt_price >> 32;
+    let price_q64 = sqrt_price_q32.saturating_mul(sqrt_price_q32);
+
+    let amount_a = amount / 2;
+    let amount_b_half = amount - amount_a;
+    let amount_b = ((amount_b_half as u128).saturating_mul(price_q64) >> 64) as u64;
+
+    Ok((amount_a, amount_b))
+}
+
+/// The `Whirlpool` account's `sqrt_price: u128` field (Q64.64 fixed point), at the fixed byte
+/// offset within the account every deployed Whirlpool shares regardless of which pair it's
+/// quoting.
+const WHIRLPOOL_SQRT_PRICE_OFFSET: usize = 65;
+
+fn read_whirlpool_sqrt_price(data: &[u8]) -> Result<u128> {
+    let end = WHIRLPOOL_SQRT_PRICE_OFFSET + 16;
+    require!(
+        data.len() >= end,
+        OrcaWhirlpoolAdapterError::MalformedWhirlpoolAccount
+    );
+    Ok(u128::from_le_bytes(
+        data[WHIRLPOOL_SQRT_PRICE_OFFSET..end].try_into().unwrap(),
+    ))
+}
+
+fn discriminator(name: &str) -> Vec<u8> {
+    anchor_lang::solana_program::hash::hash(format!("global:{name}").as_bytes()).to_bytes()[..8]
+        .to_vec()
+}
+
+/// Pyth's `PriceAccount` stores the current aggregate price as an `i64` at a fixed byte
+/// offset, scaled by `10^expo`; this adapter assumes the same fixed exponent `yield_pilot`
+/// itself assumes when reading oracles (see `PYTH_PRICE_SCALE` there), since both legs need
+/// a consistent scale to sum into one vault-asset value.
+const PYTH_PRICE_OFFSET: usize = 208;
+const PYTH_PRICE_SCALE: i64 = 100_000_000;
+
+fn read_pyth_price(data: &[u8]) -> Result<i64> {
+    let end = PYTH_PRICE_OFFSET + 8;
+    require!(
+        data.len() >= end,
+        OrcaWhirlpoolAdapterError::MalformedOracleAccount
+    );
+    Ok(i64::from_le_bytes(
+        data[PYTH_PRICE_OFFSET..end].try_into().unwrap(),
+    ))
+}
+
+#[error_code]
+pub enum OrcaWhirlpoolAdapterError {
+    #[msg("Oracle account is too short to contain a price at the expected offset")]
+    MalformedOracleAccount,
+    #[msg("Whirlpool account is too short to contain sqrt_price at the expected offset")]
+    MalformedWhirlpoolAccount,
+}
+
+#[derive(Accounts)]
+pub struct WhirlpoolModifyLiquidity<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    /// CHECK: PDA signer forwarded by the router's CPI; verified by seeds below.
+    #[account(seeds = [b"vault_authority", vault_owner.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: the target Whirlpool; which pool/fee-tier this adapter instance affects.
+    /// Validated by the Whirlpool program during the CPI.
+    #[account(mut)]
+    pub whirlpool: UncheckedAccount<'info>,
+    /// CHECK: the vault's open position; opened once out-of-band, not by this adapter.
+    #[account(mut)]
+    pub position: UncheckedAccount<'info>,
+    /// CHECK: the position NFT account proving ownership; validated during the CPI.
+    pub position_token_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub token_owner_account_a: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub token_owner_account_b: Account<'info, TokenAccount>,
+    /// CHECK: pool-owned vault for token A; validated during the CPI.
+    #[account(mut)]
+    pub token_vault_a: UncheckedAccount<'info>,
+    /// CHECK: pool-owned vault for token B; validated during the CPI.
+    #[account(mut)]
+    pub token_vault_b: UncheckedAccount<'info>,
+    /// CHECK: tick array covering the position's lower bound; validated during the CPI.
+    #[account(mut)]
+    pub tick_array_lower: UncheckedAccount<'info>,
+    /// CHECK: tick array covering the position's upper bound; validated during the CPI.
+    #[account(mut)]
+    pub tick_array_upper: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WhirlpoolCollectFees<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    /// CHECK: PDA signer forwarded by the router's CPI; verified by seeds below.
+    #[account(seeds = [b"vault_authority", vault_owner.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: the target Whirlpool; read-only here, validated during the CPI.
+    pub whirlpool: UncheckedAccount<'info>,
+    /// CHECK: the vault's open position; validated during the CPI.
+    #[account(mut)]
+    pub position: UncheckedAccount<'info>,
+    /// CHECK: the position NFT account proving ownership; validated during the CPI.
+    pub position_token_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub token_owner_account_a: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub token_owner_account_b: Account<'info, TokenAccount>,
+    /// CHECK: pool-owned vault for token A; validated during the CPI.
+    #[account(mut)]
+    pub token_vault_a: UncheckedAccount<'info>,
+    /// CHECK: pool-owned vault for token B; validated during the CPI.
+    #[account(mut)]
+    pub token_vault_b: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ValueWhirlpoolPosition<'info> {
+    pub token_owner_account_a: Account<'info, TokenAccount>,
+    pub token_owner_account_b: Account<'info, TokenAccount>,
+    /// CHECK: Pyth price account for token A; read-only, layout validated by length check.
+    pub oracle_a: UncheckedAccount<'info>,
+    /// CHECK: Pyth price account for token B; read-only, layout validated by length check.
+    pub oracle_b: UncheckedAccount<'info>,
+}
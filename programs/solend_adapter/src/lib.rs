@@ -0,0 +1,250 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+declare_id!("So1endAdapter111111111111111111111111111111");
+
+/// Solend's main mainnet lending program id.
+pub const SOLEND_PROGRAM_ID: Pubkey = pubkey!("So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo");
+
+/// Wraps Solend's `DepositReserveLiquidity` / `RedeemReserveCollateral` instructions behind
+/// the router's shared `deposit`/`withdraw` adapter interface. The vault holds cTokens
+/// directly rather than an obligation, since it never borrows against the collateral.
+#[program]
+pub mod solend_adapter {
+    use super::*;
+
+    pub fn deposit(ctx: Context<SolendDeposit>, amount: u64) -> Result<()> {
+        check_reserve_health(&ctx.accounts.reserve.try_borrow_data()?, Clock::get()?.slot)?;
+
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: SOLEND_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.source_liquidity.key(), false),
+                AccountMeta::new(ctx.accounts.destination_collateral.key(), false),
+                AccountMeta::new(ctx.accounts.reserve.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_liquidity_supply.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_collateral_mint.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.lending_market.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.lending_market_authority.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new_readonly(sysvar::clock::ID, false),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: discriminator_with_amount(4, amount),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.source_liquidity.to_account_info(),
+                ctx.accounts.destination_collateral.to_account_info(),
+                ctx.accounts.reserve.to_account_info(),
+                ctx.accounts.reserve_liquidity_supply.to_account_info(),
+                ctx.accounts.reserve_collateral_mint.to_account_info(),
+                ctx.accounts.lending_market.to_account_info(),
+                ctx.accounts.lending_market_authority.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<SolendWithdraw>, amount: u64) -> Result<()> {
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: SOLEND_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.source_collateral.key(), false),
+                AccountMeta::new(ctx.accounts.destination_liquidity.key(), false),
+                AccountMeta::new(ctx.accounts.reserve.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_collateral_mint.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_liquidity_supply.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.lending_market.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.lending_market_authority.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new_readonly(sysvar::clock::ID, false),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: discriminator_with_amount(5, amount),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.source_collateral.to_account_info(),
+                ctx.accounts.destination_liquidity.to_account_info(),
+                ctx.accounts.reserve.to_account_info(),
+                ctx.accounts.reserve_collateral_mint.to_account_info(),
+                ctx.accounts.reserve_liquidity_supply.to_account_info(),
+                ctx.accounts.lending_market.to_account_info(),
+                ctx.accounts.lending_market_authority.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+
+    /// Values the vault's cToken balance in underlying terms using the reserve's
+    /// collateral exchange rate, returning the implied underlying amount so the router
+    /// can reconcile `YieldState.total_assets` without guessing at accrued interest.
+    pub fn value_position(ctx: Context<ValueSolendPosition>) -> Result<u64> {
+        let reserve_data = ctx.accounts.reserve.try_borrow_data()?;
+        let exchange_rate = read_collateral_exchange_rate(&reserve_data)?;
+        let collateral_amount = ctx.accounts.vault_collateral_account.amount;
+
+        Ok(((collateral_amount as u128 * exchange_rate as u128) / EXCHANGE_RATE_SCALE as u128) as u64)
+    }
+}
+
+/// Solend encodes the reserve's liquidity-per-collateral exchange rate as a fixed-point
+/// u64 scaled by `EXCHANGE_RATE_SCALE`, stored at a fixed byte offset within the reserve
+/// account. The exact offset depends on Solend's reserve layout version.
+const EXCHANGE_RATE_SCALE: u64 = 1_000_000_000;
+const RESERVE_EXCHANGE_RATE_OFFSET: usize = 185;
+
+fn read_collateral_exchange_rate(reserve_data: &[u8]) -> Result<u64> {
+    let end = RESERVE_EXCHANGE_RATE_OFFSET + 8;
+    require!(
+        reserve_data.len() >= end,
+        SolendAdapterError::MalformedReserveAccount
+    );
+    Ok(u64::from_le_bytes(
+        reserve_data[RESERVE_EXCHANGE_RATE_OFFSET..end]
+            .try_into()
+            .unwrap(),
+    ))
+}
+
+fn discriminator_with_amount(instruction_tag: u8, amount: u64) -> Vec<u8> {
+    let mut data = vec![instruction_tag];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+/// Above this utilization (borrows / available liquidity), a reserve is considered too
+/// drained to deposit more into safely — further deposits would earn interest but add to a
+/// pool that's already struggling to service withdrawals.
+const MAX_UTILIZATION_BPS: u64 = 9_500;
+/// A reserve's `last_update.slot` older than this many slots relative to the current slot
+/// means its price/accrued-interest snapshot is stale enough that it shouldn't be trusted
+/// for a fresh deposit.
+const MAX_RESERVE_STALENESS_SLOTS: u64 = 150;
+
+const RESERVE_AVAILABLE_LIQUIDITY_OFFSET: usize = 64;
+const RESERVE_TOTAL_BORROWS_OFFSET: usize = 96;
+const RESERVE_LAST_UPDATE_SLOT_OFFSET: usize = 8;
+
+/// Reads utilization and staleness directly off the reserve account Solend itself maintains,
+/// aborting with `ProtocolUnhealthy` instead of letting `deposit` blindly add to a reserve
+/// that's over-utilized or hasn't been refreshed recently.
+fn check_reserve_health(reserve_data: &[u8], current_slot: u64) -> Result<()> {
+    let last_update_slot = read_u64(reserve_data, RESERVE_LAST_UPDATE_SLOT_OFFSET)?;
+    require!(
+        current_slot.saturating_sub(last_update_slot) <= MAX_RESERVE_STALENESS_SLOTS,
+        SolendAdapterError::ProtocolUnhealthy
+    );
+
+    let available_liquidity = read_u64(reserve_data, RESERVE_AVAILABLE_LIQUIDITY_OFFSET)? as u128;
+    let total_borrows = read_u64(reserve_data, RESERVE_TOTAL_BORROWS_OFFSET)? as u128;
+    let total_liquidity = available_liquidity.saturating_add(total_borrows);
+    if total_liquidity > 0 {
+        let utilization_bps = (total_borrows.saturating_mul(10_000) / total_liquidity) as u64;
+        require!(
+            utilization_bps <= MAX_UTILIZATION_BPS,
+            SolendAdapterError::ProtocolUnhealthy
+        );
+    }
+
+    Ok(())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    let end = offset + 8;
+    require!(data.len() >= end, SolendAdapterError::MalformedReserveAccount);
+    Ok(u64::from_le_bytes(data[offset..end].try_into().unwrap()))
+}
+
+#[error_code]
+pub enum SolendAdapterError {
+    #[msg("Reserve account is too short to contain an exchange rate at the expected offset")]
+    MalformedReserveAccount,
+    #[msg("Reserve utilization too high or price data too stale to deposit safely")]
+    ProtocolUnhealthy,
+}
+
+#[derive(Accounts)]
+pub struct SolendDeposit<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    /// CHECK: PDA signer forwarded by the router's CPI; verified by seeds below.
+    #[account(seeds = [b"vault_authority", vault_owner.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub source_liquidity: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub destination_collateral: Account<'info, TokenAccount>,
+    /// CHECK: Solend reserve account; validated by Solend during the CPI.
+    #[account(mut)]
+    pub reserve: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub reserve_liquidity_supply: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub reserve_collateral_mint: Account<'info, Mint>,
+    /// CHECK: Solend lending market account; validated by Solend during the CPI.
+    pub lending_market: UncheckedAccount<'info>,
+    /// CHECK: Solend-owned PDA; validated by Solend during the CPI.
+    pub lending_market_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SolendWithdraw<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    /// CHECK: PDA signer forwarded by the router's CPI; verified by seeds below.
+    #[account(seeds = [b"vault_authority", vault_owner.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub source_collateral: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub destination_liquidity: Account<'info, TokenAccount>,
+    /// CHECK: Solend reserve account; validated by Solend during the CPI.
+    #[account(mut)]
+    pub reserve: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub reserve_collateral_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub reserve_liquidity_supply: Account<'info, TokenAccount>,
+    /// CHECK: Solend lending market account; validated by Solend during the CPI.
+    pub lending_market: UncheckedAccount<'info>,
+    /// CHECK: Solend-owned PDA; validated by Solend during the CPI.
+    pub lending_market_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ValueSolendPosition<'info> {
+    /// CHECK: Solend reserve account; read-only valuation, not a CPI target here.
+    pub reserve: UncheckedAccount<'info>,
+    pub vault_collateral_account: Account<'info, TokenAccount>,
+}
@@ -0,0 +1,231 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+declare_id!("Sp1StakePoolAdapter11111111111111111111111");
+
+/// The SPL Stake Pool program id. Every stake pool deployed under this program — JitoSOL,
+/// bSOL, or any other SPL-standard LST — shares the same `DepositSol`/`WithdrawSol`
+/// instruction layout, so unlike `jito_adapter` (one program id baked in as a brand name)
+/// this adapter takes the specific pool as an account and works for all of them.
+pub const SPL_STAKE_POOL_PROGRAM_ID: Pubkey = pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuZg");
+
+/// Wraps the SPL Stake Pool `DepositSol` / `WithdrawSol` instructions behind the router's
+/// shared `deposit`/`withdraw` adapter interface. Adding a new LST strategy is then a
+/// `register_strategy` call pointing `strategy_info`'s adapter accounts at that pool's own
+/// reserve/fee/pool-mint accounts — no new adapter program required.
+#[program]
+pub mod spl_stake_pool_adapter {
+    use super::*;
+
+    pub fn deposit(ctx: Context<StakePoolDeposit>, amount: u64) -> Result<()> {
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: SPL_STAKE_POOL_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.stake_pool.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.stake_pool_withdraw_authority.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_stake_account.key(), false),
+                AccountMeta::new(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new(ctx.accounts.vault_pool_token_account.key(), false),
+                AccountMeta::new(ctx.accounts.manager_fee_account.key(), false),
+                AccountMeta::new(ctx.accounts.referrer_pool_tokens_account.key(), false),
+                AccountMeta::new(ctx.accounts.pool_mint.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: stake_pool_instruction(14, amount),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.stake_pool.to_account_info(),
+                ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+                ctx.accounts.reserve_stake_account.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.vault_pool_token_account.to_account_info(),
+                ctx.accounts.manager_fee_account.to_account_info(),
+                ctx.accounts.referrer_pool_tokens_account.to_account_info(),
+                ctx.accounts.pool_mint.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<StakePoolWithdraw>, amount: u64) -> Result<()> {
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            ctx.accounts.vault_owner.key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = Instruction {
+            program_id: SPL_STAKE_POOL_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.stake_pool.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.stake_pool_withdraw_authority.key(), false),
+                AccountMeta::new(ctx.accounts.reserve_stake_account.key(), false),
+                AccountMeta::new(ctx.accounts.vault_pool_token_account.key(), false),
+                AccountMeta::new(ctx.accounts.vault_authority.key(), true),
+                AccountMeta::new(ctx.accounts.destination_system_account.key(), false),
+                AccountMeta::new(ctx.accounts.manager_fee_account.key(), false),
+                AccountMeta::new(ctx.accounts.pool_mint.key(), false),
+                AccountMeta::new_readonly(sysvar::clock::ID, false),
+                AccountMeta::new_readonly(sysvar::stake_history::ID, false),
+                AccountMeta::new_readonly(ctx.accounts.stake_program.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            ],
+            data: stake_pool_instruction(16, amount),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.stake_pool.to_account_info(),
+                ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+                ctx.accounts.reserve_stake_account.to_account_info(),
+                ctx.accounts.vault_pool_token_account.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.destination_system_account.to_account_info(),
+                ctx.accounts.manager_fee_account.to_account_info(),
+                ctx.accounts.pool_mint.to_account_info(),
+                ctx.accounts.stake_program.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        Ok(())
+    }
+
+    /// Values the vault's pool-token balance in underlying SOL terms via the stake pool's
+    /// own `total_lamports`/`pool_token_supply`, so the router can mark-to-market whichever
+    /// LST `strategy_info.position_account` happens to point at.
+    pub fn value_position(ctx: Context<ValueStakePoolPosition>) -> Result<u64> {
+        let pool_data = ctx.accounts.stake_pool.try_borrow_data()?;
+        let (total_lamports, pool_token_supply) = read_pool_totals(&pool_data)?;
+        let pool_tokens = ctx.accounts.vault_pool_token_account.amount;
+
+        if pool_token_supply == 0 {
+            return Ok(0);
+        }
+        Ok(((pool_tokens as u128 * total_lamports as u128) / pool_token_supply as u128) as u64)
+    }
+}
+
+/// SPL Stake Pool instructions are a plain Borsh-serialized enum (tag byte, no Anchor
+/// sighash), so adapter instruction data is built by hand rather than via the
+/// `global:<name>` discriminator convention the Anchor-based adapters use.
+fn stake_pool_instruction(tag: u8, amount: u64) -> Vec<u8> {
+    let mut data = vec![tag];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+/// The `StakePool` account's `total_lamports: u64` and `pool_token_supply: u64` fields, at
+/// fixed byte offsets within the fixed-size header every SPL stake pool shares regardless
+/// of which LST it mints.
+const STAKE_POOL_TOTAL_LAMPORTS_OFFSET: usize = 282;
+const STAKE_POOL_POOL_TOKEN_SUPPLY_OFFSET: usize = 290;
+
+fn read_pool_totals(pool_data: &[u8]) -> Result<(u64, u64)> {
+    let end = STAKE_POOL_POOL_TOKEN_SUPPLY_OFFSET + 8;
+    require!(
+        pool_data.len() >= end,
+        SplStakePoolAdapterError::MalformedStakePoolAccount
+    );
+    let total_lamports = u64::from_le_bytes(
+        pool_data[STAKE_POOL_TOTAL_LAMPORTS_OFFSET..STAKE_POOL_TOTAL_LAMPORTS_OFFSET + 8]
+            .try_into()
+            .unwrap(),
+    );
+    let pool_token_supply = u64::from_le_bytes(
+        pool_data[STAKE_POOL_POOL_TOKEN_SUPPLY_OFFSET..end]
+            .try_into()
+            .unwrap(),
+    );
+    Ok((total_lamports, pool_token_supply))
+}
+
+#[error_code]
+pub enum SplStakePoolAdapterError {
+    #[msg("Stake pool account is too short to contain totals at the expected offsets")]
+    MalformedStakePoolAccount,
+}
+
+#[derive(Accounts)]
+pub struct StakePoolDeposit<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    /// CHECK: PDA signer forwarded by the router's CPI; verified by seeds below.
+    #[account(seeds = [b"vault_authority", vault_owner.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: the target stake pool; which LST this adapter call affects. Validated by the
+    /// SPL Stake Pool program during the CPI.
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK: stake-pool-owned withdraw authority PDA; validated during the CPI.
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: stake pool's reserve stake account; validated during the CPI.
+    #[account(mut)]
+    pub reserve_stake_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub vault_pool_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub manager_fee_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub referrer_pool_tokens_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub pool_mint: Account<'info, Mint>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct StakePoolWithdraw<'info> {
+    /// CHECK: owner pubkey used only to re-derive the vault_authority PDA seed.
+    pub vault_owner: UncheckedAccount<'info>,
+    /// CHECK: PDA signer forwarded by the router's CPI; verified by seeds below.
+    #[account(seeds = [b"vault_authority", vault_owner.key().as_ref()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: the target stake pool; which LST this adapter call affects. Validated by the
+    /// SPL Stake Pool program during the CPI.
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK: stake-pool-owned withdraw authority PDA; validated during the CPI.
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: stake pool's reserve stake account; validated during the CPI.
+    #[account(mut)]
+    pub reserve_stake_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub vault_pool_token_account: Account<'info, TokenAccount>,
+    /// CHECK: lamport destination for the unstaked SOL; the vault's own system account.
+    #[account(mut)]
+    pub destination_system_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub manager_fee_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub pool_mint: Account<'info, Mint>,
+    /// CHECK: native stake program; invoked transitively by the stake pool program.
+    pub stake_program: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ValueStakePoolPosition<'info> {
+    /// CHECK: the target stake pool; read-only valuation, not a CPI target here.
+    pub stake_pool: UncheckedAccount<'info>,
+    pub vault_pool_token_account: Account<'info, TokenAccount>,
+}
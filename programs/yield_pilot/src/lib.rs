@@ -1,16 +1,299 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::address_lookup_table;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::keccak;
+use anchor_lang::solana_program::program::{get_return_data, invoke, invoke_signed};
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::secp256k1_program;
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use anchor_lang::system_program;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::spl_token;
+use anchor_spl::token_interface::{
+    self, Burn, CloseAccount, Mint, MintTo, SyncNative, TokenAccount, TokenInterface,
+    TransferChecked,
+};
 
-declare_id!("Hp1uqW9SEVeZfgKzPUkjw1tmsQRpGNgydjXmF6cedry2"); 
+mod math;
+use math::{
+    accrued_fee_value, amount_for_shares, current_share_price, projected_apy_after_deposit, shares_for_amount,
+    weight_capped_deployment,
+};
+
+declare_id!("Hp1uqW9SEVeZfgKzPUkjw1tmsQRpGNgydjXmF6cedry2");
+
+pub const MAX_UPDATERS: usize = 10;
+/// Ceiling on `YieldState::allowed_adapter_programs`. Generous relative to the number of
+/// lending/LP protocols any one vault realistically integrates with across its lifetime.
+pub const MAX_ALLOWED_ADAPTER_PROGRAMS: usize = 16;
+/// Sized to comfortably cover the rebalance cooldown window with room to spare, so the
+/// TWAP in `twap_apy_bps` reflects sustained yield rather than a single recent spike.
+pub const HISTORY_LEN: usize = 96;
+pub const DEFAULT_MIN_IMPROVEMENT_BPS: u16 = 25;
+/// Minimum time between successful rebalances, enforced against permissionless
+/// `crank_rebalance` calls so no single cranker can thrash the vault's allocation.
+pub const DEFAULT_REBALANCE_COOLDOWN_SECS: i64 = 3600;
+/// Default keeper tip for `crank_rebalance`, in bps of the rebalanced amount.
+pub const DEFAULT_CRANK_TIP_BPS: u16 = 5;
+/// Rolling window `OperatorLimits` caps on `update_yield` frequency and `rebalance`
+/// volume reset on. A day rather than a calendar-day boundary, so a bot can't dodge its
+/// cap by timing bursts around UTC midnight.
+pub const OPERATOR_LIMITS_WINDOW_SECS: i64 = 24 * 60 * 60;
+/// Used to convert `management_fee_bps` (an annualized rate) into a per-second accrual.
+pub const SECONDS_PER_YEAR: i64 = 365 * 24 * 60 * 60;
+/// Fixed-point scale for `YieldState.high_water_mark`, a share price expressed as
+/// underlying-per-share.
+pub const SHARE_PRICE_SCALE: u64 = 1_000_000_000;
+/// Minimum delay between `queue_param_change` and `execute_param_change`, giving
+/// depositors time to exit before a risky config change lands.
+pub const PARAM_CHANGE_TIMELOCK_SECS: i64 = 86_400;
+/// Denominator for all basis-point weight fields (`target_weight_bps`, `max_weight_bps`):
+/// 10_000 bps == 100% of `total_assets`.
+pub const MAX_WEIGHT_BPS: u16 = 10_000;
+/// Default fee charged by `withdraw_instant`, in bps of the withdrawn amount.
+pub const DEFAULT_INSTANT_WITHDRAWAL_FEE_BPS: u16 = 25;
+/// Default share of `total_assets` kept undeployed in the vault token account for instant
+/// withdrawals, rather than pushed into `current_protocol`. Zero disables the buffer.
+pub const DEFAULT_BUFFER_BPS: u16 = 0;
+/// Default ceiling on `update_yield`/`rebalance`/`crank_rebalance`'s `new_apy_bps`: 1,000%
+/// APY. Generous enough for any real strategy, but low enough that a corrupted updater bot
+/// can't post something like 65,535 bps and immediately trigger a rebalance into it.
+pub const DEFAULT_MAX_REASONABLE_APY_BPS: u16 = 100_000;
+/// Current on-chain layout version for `YieldState`. Bump this whenever new fields are
+/// appended, and teach `migrate_state` to realloc and backfill defaults for accounts still
+/// on an older version, so existing vaults never need to be redeployed.
+pub const CURRENT_STATE_VERSION: u8 = 1;
+/// Extra bytes `migrate_state` reallocs onto `YieldState` on top of whatever the new
+/// layout needs, so the next schema bump after that has room to grow without a second
+/// realloc round-trip.
+pub const STATE_MIGRATION_SLACK_BYTES: usize = 256;
+/// Minimum number of slots that must elapse between a depositor's last deposit and their
+/// next withdrawal. Blocks a same-slot (or near-same-slot) deposit -> harvest -> withdraw
+/// flash-loan-style attack on the share price.
+pub const MIN_WITHDRAWAL_DELAY_SLOTS: u64 = 1;
+/// Slots a queued rebalance must sit for before `rebalance` will execute it, giving the
+/// guardian a window to `veto_rebalance` it. ~60s at Solana's ~400ms average slot time.
+pub const REBALANCE_VETO_WINDOW_SLOTS: u64 = 150;
+/// Fixed-point scale for `YieldState.reward_per_share_index`. Scaled well above
+/// `SHARE_PRICE_SCALE` since a single second's emission split across a large
+/// `total_shares` is often fractional even at that precision.
+pub const REWARD_INDEX_SCALE: u128 = 1_000_000_000_000;
+/// Supported `deposit`/`deposit_sol` lock tiers, in seconds. A `lock_duration_secs` of 0
+/// means no lock; any other value must match one of these exactly.
+pub const LOCK_TIER_30D_SECS: i64 = 30 * 24 * 60 * 60;
+pub const LOCK_TIER_90D_SECS: i64 = 90 * 24 * 60 * 60;
+/// Liquidity-mining reward boost, in bps on top of the unboosted accrual, applied while a
+/// position's `locked_until_ts` is still in the future.
+pub const LOCK_BOOST_BPS_30D: u16 = 1_500;
+pub const LOCK_BOOST_BPS_90D: u16 = 5_000;
+/// Maximum age of a `signed_at` timestamp accepted by `update_yield_signed`, bounding how
+/// long an off-chain-signed APY update can sit before being landed on-chain.
+pub const MAX_SIGNED_APY_AGE_SECS: i64 = 300;
+/// Metaplex Token Metadata's mainnet program id, CPI'd into by `set_share_metadata`.
+pub const METAPLEX_TOKEN_METADATA_PROGRAM_ID: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+/// Field length limits enforced by the Metaplex Token Metadata program itself; checked here
+/// too so `set_share_metadata` fails with a clear error instead of an opaque CPI revert.
+pub const MAX_METADATA_NAME_LEN: usize = 32;
+pub const MAX_METADATA_SYMBOL_LEN: usize = 10;
+pub const MAX_METADATA_URI_LEN: usize = 200;
+/// Voting window for a `create_proposal` governance vote. Generous enough that depositors
+/// across timezones see a proposal before it closes, short enough that a passed change
+/// reaches `queue_proposal_execution` (and then `PARAM_CHANGE_TIMELOCK_SECS`) in a
+/// reasonable time.
+pub const GOVERNANCE_VOTING_PERIOD_SECS: i64 = 3 * 24 * 60 * 60;
+/// Minimum share of `total_shares` that must vote (either way) before
+/// `queue_proposal_execution` will accept a proposal's outcome, in bps of `MAX_WEIGHT_BPS`.
+pub const GOVERNANCE_QUORUM_BPS: u16 = 1_000;
+/// Window after an epoch starts during which `take_snapshot` will record a balance for it.
+/// Wide enough for an airdrop keeper to snapshot every depositor without racing the next
+/// `roll_epoch`, narrow enough that "snapshot at epoch N" means close to when N actually
+/// started rather than whenever the epoch happens to end.
+pub const SNAPSHOT_WINDOW_SECS: i64 = 3600;
+/// Ceiling on `create_vault`/`create_vault_soulbound`'s `decimals_offset` argument. Six is
+/// already generous headroom for the lowest-decimals assets likely to back a vault (e.g.
+/// 6-decimal USDC); anything beyond that just wastes `share_mint` precision for no added
+/// protection against the donation-inflation attack `decimals_offset` defends against.
+pub const MAX_DECIMALS_OFFSET: u8 = 6;
+/// How many recent admin/operator actions `AuditLog` keeps before its ring buffer starts
+/// overwriting the oldest entry. `AuditLog::next_sequence` keeps counting past that point,
+/// so an integrator polling the buffer can still tell from a gap in `sequence` that entries
+/// rolled off rather than mistaking a full buffer for a quiet vault.
+pub const AUDIT_LOG_LEN: usize = 64;
+/// Numeric `AuditEntry::action` tags for the call sites wired up to `AuditLog::record` so
+/// far. Not an exhaustive instruction list — add a tag here before wiring up a new one.
+pub const AUDIT_ACTION_UPDATE_YIELD: u8 = 1;
+pub const AUDIT_ACTION_REBALANCE: u8 = 2;
+pub const AUDIT_ACTION_ADD_UPDATER: u8 = 3;
+pub const AUDIT_ACTION_REMOVE_UPDATER: u8 = 4;
+pub const AUDIT_ACTION_REGISTER_OPERATOR: u8 = 5;
+pub const AUDIT_ACTION_SET_OPERATOR_LIMITS: u8 = 6;
+pub const AUDIT_ACTION_DEREGISTER_OPERATOR: u8 = 7;
 
 #[program]
 pub mod yield_pilot {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    /// Creates a new vault keyed by `(authority, vault_index)`, so one authority can run
+    /// several independent vaults (e.g. one per asset) without their PDAs colliding.
+    /// `decimals_offset` extends `share_mint`'s decimals beyond `mint.decimals` and seeds the
+    /// virtual-shares offset `shares_for_amount`/`amount_for_shares` price against, per
+    /// `YieldState::decimals_offset`. Pass zero for a high-decimals asset like wrapped SOL;
+    /// a low-decimals asset like USDC should pass a few bits of headroom instead.
+    pub fn create_vault(ctx: Context<CreateVault>, vault_index: u64, decimals_offset: u8) -> Result<()> {
+        require!(
+            decimals_offset <= MAX_DECIMALS_OFFSET,
+            YieldPilotError::DecimalsOffsetOutOfBounds
+        );
+        let state = &mut ctx.accounts.state;
+        state.version = CURRENT_STATE_VERSION;
+        state.authority = ctx.accounts.authority.key();
+        state.vault_index = vault_index;
+        state.current_protocol = 0;
+        state.current_apy_bps = 0;
+        state.min_improvement_bps = DEFAULT_MIN_IMPROVEMENT_BPS;
+        state.mint = ctx.accounts.mint.key();
+        state.share_mint = ctx.accounts.share_mint.key();
+        state.last_rebalance_ts = 0;
+        state.rebalance_cooldown_secs = DEFAULT_REBALANCE_COOLDOWN_SECS;
+        state.crank_tip_bps = DEFAULT_CRANK_TIP_BPS;
+        state.management_fee_bps = 0;
+        state.performance_fee_bps = 0;
+        state.fee_recipient = Pubkey::default();
+        state.last_fee_collection_ts = Clock::get()?.unix_timestamp;
+        state.high_water_mark = SHARE_PRICE_SCALE;
+        state.guardian = ctx.accounts.authority.key();
+        state.deposits_paused = false;
+        state.withdrawals_paused = false;
+        state.rebalances_paused = false;
+        state.deployed_amount = 0;
+        state.next_withdrawal_sequence = 0;
+        state.withdrawal_queue_head = 0;
+        state.instant_withdrawal_fee_bps = DEFAULT_INSTANT_WITHDRAWAL_FEE_BPS;
+        state.buffer_bps = DEFAULT_BUFFER_BPS;
+        state.swap_program = Pubkey::default();
+        state.max_total_deposits = 0;
+        state.max_deposit_per_user = 0;
+        state.allowlist_enabled = false;
+        state.max_reasonable_apy_bps = DEFAULT_MAX_REASONABLE_APY_BPS;
+        state.bump = ctx.bumps.state;
+        state.operation_in_progress = false;
+        state.insurance_bps = 0;
+        state.lifetime_deposits = 0;
+        state.lifetime_withdrawals = 0;
+        state.lifetime_fees_collected = 0;
+        state.lifetime_yield_earned = 0;
+        state.transferable_shares = true;
+        state.next_proposal_id = 0;
+        state.next_distributor_id = 0;
+        state.accrual_index = 0;
+        state.max_move_per_rebalance = 0;
+        state.registered_protocols_bitmap = [0; 4];
+        state.valuations_refreshed_bitmap = [0; 4];
+        state.max_withdrawal_bps_per_epoch = 0;
+        state.withdrawn_this_epoch = 0;
+        state.mint_decimals = ctx.accounts.mint.decimals;
+        state.decimals_offset = decimals_offset;
+        state.next_nft_receipt_id = 0;
+        state.allowed_adapter_programs = [Pubkey::default(); MAX_ALLOWED_ADAPTER_PROGRAMS];
+        state.allowed_adapter_program_count = 0;
+        state.wormhole_program = Pubkey::default();
+
+        let mut history = ctx.accounts.history.load_init()?;
+        history.version = CURRENT_STATE_VERSION;
+        history.bump = ctx.bumps.history;
+
+        emit_cpi!(Initialized {
+            state: ctx.accounts.state.key(),
+            authority: ctx.accounts.authority.key(),
+            mint: ctx.accounts.mint.key(),
+            vault_index,
+        });
+
+        Ok(())
+    }
+
+    /// Soul-bound counterpart to `create_vault`: identical vault setup, except `share_mint`
+    /// is a Token-2022 mint with the `NonTransferable` extension enabled instead of a freely
+    /// transferable SPL Token mint. For deployments where shares must stay tied to the
+    /// depositor that minted them (e.g. compliance requirements around KYC'd positions).
+    /// The choice is permanent — `transferable_shares` is never flipped after creation,
+    /// since doing so would require reinitializing extensions under shares already in
+    /// circulation. See `create_vault` for `decimals_offset`.
+    pub fn create_vault_soulbound(
+        ctx: Context<CreateVaultSoulbound>,
+        vault_index: u64,
+        decimals_offset: u8,
+    ) -> Result<()> {
+        require!(
+            decimals_offset <= MAX_DECIMALS_OFFSET,
+            YieldPilotError::DecimalsOffsetOutOfBounds
+        );
         let state = &mut ctx.accounts.state;
+        state.version = CURRENT_STATE_VERSION;
         state.authority = ctx.accounts.authority.key();
+        state.vault_index = vault_index;
         state.current_protocol = 0;
         state.current_apy_bps = 0;
+        state.min_improvement_bps = DEFAULT_MIN_IMPROVEMENT_BPS;
+        state.mint = ctx.accounts.mint.key();
+        state.share_mint = ctx.accounts.share_mint.key();
+        state.last_rebalance_ts = 0;
+        state.rebalance_cooldown_secs = DEFAULT_REBALANCE_COOLDOWN_SECS;
+        state.crank_tip_bps = DEFAULT_CRANK_TIP_BPS;
+        state.management_fee_bps = 0;
+        state.performance_fee_bps = 0;
+        state.fee_recipient = Pubkey::default();
+        state.last_fee_collection_ts = Clock::get()?.unix_timestamp;
+        state.high_water_mark = SHARE_PRICE_SCALE;
+        state.guardian = ctx.accounts.authority.key();
+        state.deposits_paused = false;
+        state.withdrawals_paused = false;
+        state.rebalances_paused = false;
+        state.deployed_amount = 0;
+        state.next_withdrawal_sequence = 0;
+        state.withdrawal_queue_head = 0;
+        state.instant_withdrawal_fee_bps = DEFAULT_INSTANT_WITHDRAWAL_FEE_BPS;
+        state.buffer_bps = DEFAULT_BUFFER_BPS;
+        state.swap_program = Pubkey::default();
+        state.max_total_deposits = 0;
+        state.max_deposit_per_user = 0;
+        state.allowlist_enabled = false;
+        state.max_reasonable_apy_bps = DEFAULT_MAX_REASONABLE_APY_BPS;
+        state.bump = ctx.bumps.state;
+        state.operation_in_progress = false;
+        state.insurance_bps = 0;
+        state.lifetime_deposits = 0;
+        state.lifetime_withdrawals = 0;
+        state.lifetime_fees_collected = 0;
+        state.lifetime_yield_earned = 0;
+        state.transferable_shares = false;
+        state.next_proposal_id = 0;
+        state.next_distributor_id = 0;
+        state.accrual_index = 0;
+        state.max_move_per_rebalance = 0;
+        state.registered_protocols_bitmap = [0; 4];
+        state.valuations_refreshed_bitmap = [0; 4];
+        state.max_withdrawal_bps_per_epoch = 0;
+        state.withdrawn_this_epoch = 0;
+        state.mint_decimals = ctx.accounts.mint.decimals;
+        state.decimals_offset = decimals_offset;
+        state.next_nft_receipt_id = 0;
+        state.allowed_adapter_programs = [Pubkey::default(); MAX_ALLOWED_ADAPTER_PROGRAMS];
+        state.allowed_adapter_program_count = 0;
+        state.wormhole_program = Pubkey::default();
+
+        let mut history = ctx.accounts.history.load_init()?;
+        history.version = CURRENT_STATE_VERSION;
+        history.bump = ctx.bumps.history;
+
+        emit_cpi!(Initialized {
+            state: ctx.accounts.state.key(),
+            authority: ctx.accounts.authority.key(),
+            mint: ctx.accounts.mint.key(),
+            vault_index,
+        });
+
         Ok(())
     }
 
@@ -19,46 +302,9949 @@ pub mod yield_pilot {
         new_protocol: u8,
         new_apy_bps: u16,
     ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let strategy_info = ctx
+            .accounts
+            .strategy_info
+            .as_mut()
+            .ok_or(YieldPilotError::UnknownStrategy)?;
+        require!(strategy_info.enabled, YieldPilotError::StrategyDisabled);
+        require!(
+            new_apy_bps <= strategy_info.max_apy_bps,
+            YieldPilotError::ApyOutOfBounds
+        );
+        require!(
+            new_apy_bps <= ctx.accounts.state.max_reasonable_apy_bps,
+            YieldPilotError::ApyOutOfBounds
+        );
+        validate_oracle(strategy_info, ctx.accounts.oracle.as_ref(), now)?;
+        strategy_info.last_apy_update_ts = now;
+
         let state = &mut ctx.accounts.state;
+        let signer = ctx.accounts.signer.key();
 
-        require_keys_eq!(
-            state.authority,
-            ctx.accounts.authority.key(),
+        require!(
+            signer == state.authority || state.is_updater(&signer),
             YieldPilotError::Unauthorized
         );
+        if let Some(limits) = ctx.accounts.operator_limits.as_mut() {
+            limits.charge_apy_update(now)?;
+        }
 
         state.current_protocol = new_protocol;
         state.current_apy_bps = new_apy_bps;
+        ctx.accounts
+            .history
+            .load_mut()?
+            .record_snapshot(new_protocol, new_apy_bps, now);
+        if let Some(audit_log) = ctx.accounts.audit_log.as_ref() {
+            let mut params = [0u8; 32];
+            params[0] = new_protocol;
+            params[1..3].copy_from_slice(&new_apy_bps.to_le_bytes());
+            audit_log.load_mut()?.record(
+                AUDIT_ACTION_UPDATE_YIELD,
+                signer,
+                Clock::get()?.slot,
+                params,
+            );
+        }
+
+        emit_cpi!(YieldUpdated {
+            state: ctx.accounts.state.key(),
+            protocol: new_protocol,
+            apy_bps: new_apy_bps,
+            actor: signer,
+        });
 
         Ok(())
     }
-}
 
-#[account]
-pub struct YieldState {
-    pub authority: Pubkey,
-    pub current_protocol: u8,
-    pub current_apy_bps: u16,
-}
+    /// Permissionless counterpart to `update_yield`, authorized by an Ed25519 signature
+    /// instead of an on-chain `Signer` matching `state.authority`/`state.updaters`. Lets a
+    /// keeper sign APY updates off-chain and have any relayer land them, rather than holding
+    /// a hot key that can sign Solana transactions directly. The signature must be produced
+    /// by `state.apy_oracle_signer` over exactly `(state, new_protocol, new_apy_bps,
+    /// signed_at)`, via a native Ed25519Program instruction placed immediately before this
+    /// one in the same transaction.
+    pub fn update_yield_signed(
+        ctx: Context<UpdateYieldSigned>,
+        new_protocol: u8,
+        new_apy_bps: u16,
+        signed_at: i64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.state.apy_oracle_signer != Pubkey::default(),
+            YieldPilotError::ApyOracleSignerNotConfigured
+        );
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(init, payer = authority, space = 8 + 32 + 1 + 2)]
-    pub state: Account<'info, YieldState>,
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            signed_at <= now && now - signed_at <= MAX_SIGNED_APY_AGE_SECS,
+            YieldPilotError::SignedApyUpdateExpired
+        );
 
-#[derive(Accounts)]
-pub struct UpdateYield<'info> {
-    #[account(mut)]
-    pub state: Account<'info, YieldState>,
-    pub authority: Signer<'info>,
-}
+        let strategy_info = ctx
+            .accounts
+            .strategy_info
+            .as_mut()
+            .ok_or(YieldPilotError::UnknownStrategy)?;
+        require!(
+            signed_at > strategy_info.last_apy_update_ts,
+            YieldPilotError::SignedApyUpdateExpired
+        );
 
-#[error_code]
-pub enum YieldPilotError {
-    #[msg("Unauthorized caller")]
-    Unauthorized,
+        let mut message = Vec::with_capacity(32 + 1 + 2 + 8);
+        message.extend_from_slice(ctx.accounts.state.key().as_ref());
+        message.push(new_protocol);
+        message.extend_from_slice(&new_apy_bps.to_le_bytes());
+        message.extend_from_slice(&signed_at.to_le_bytes());
+        verify_ed25519_signature(
+            &ctx.accounts.instructions_sysvar,
+            &ctx.accounts.state.apy_oracle_signer,
+            &message,
+        )?;
+
+        require!(strategy_info.enabled, YieldPilotError::StrategyDisabled);
+        require!(
+            new_apy_bps <= strategy_info.max_apy_bps,
+            YieldPilotError::ApyOutOfBounds
+        );
+        require!(
+            new_apy_bps <= ctx.accounts.state.max_reasonable_apy_bps,
+            YieldPilotError::ApyOutOfBounds
+        );
+        validate_oracle(strategy_info, ctx.accounts.oracle.as_ref(), now)?;
+        strategy_info.last_apy_update_ts = signed_at;
+
+        let actor = ctx.accounts.state.apy_oracle_signer;
+        let state = &mut ctx.accounts.state;
+        state.current_protocol = new_protocol;
+        state.current_apy_bps = new_apy_bps;
+        ctx.accounts
+            .history
+            .load_mut()?
+            .record_snapshot(new_protocol, new_apy_bps, now);
+
+        emit_cpi!(YieldUpdated {
+            state: ctx.accounts.state.key(),
+            protocol: new_protocol,
+            apy_bps: new_apy_bps,
+            actor,
+        });
+
+        Ok(())
+    }
+
+    /// EVM-keyed counterpart to `update_yield_signed`: authorized by a secp256k1 signature
+    /// from an Ethereum key instead of an Ed25519 Solana key, so a data pipeline that
+    /// already signs with `ecdsa`/`secp256k1` (e.g. alongside EVM-side attestations) never
+    /// has to generate or manage a separate Solana keypair. The signature must be produced
+    /// by `state.evm_apy_attester` over exactly `(state, new_protocol, new_apy_bps,
+    /// signed_at)`, via a native Secp256k1Program instruction placed immediately before
+    /// this one in the same transaction.
+    pub fn update_yield_attested_evm(
+        ctx: Context<UpdateYieldAttestedEvm>,
+        new_protocol: u8,
+        new_apy_bps: u16,
+        signed_at: i64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.state.evm_apy_attester != [0u8; 20],
+            YieldPilotError::EvmApyAttesterNotConfigured
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            signed_at <= now && now - signed_at <= MAX_SIGNED_APY_AGE_SECS,
+            YieldPilotError::SignedApyUpdateExpired
+        );
+
+        let strategy_info = ctx
+            .accounts
+            .strategy_info
+            .as_mut()
+            .ok_or(YieldPilotError::UnknownStrategy)?;
+        require!(
+            signed_at > strategy_info.last_apy_update_ts,
+            YieldPilotError::SignedApyUpdateExpired
+        );
+
+        let mut message = Vec::with_capacity(32 + 1 + 2 + 8);
+        message.extend_from_slice(ctx.accounts.state.key().as_ref());
+        message.push(new_protocol);
+        message.extend_from_slice(&new_apy_bps.to_le_bytes());
+        message.extend_from_slice(&signed_at.to_le_bytes());
+        verify_secp256k1_signature(
+            &ctx.accounts.instructions_sysvar,
+            &ctx.accounts.state.evm_apy_attester,
+            &message,
+        )?;
+
+        require!(strategy_info.enabled, YieldPilotError::StrategyDisabled);
+        require!(
+            new_apy_bps <= strategy_info.max_apy_bps,
+            YieldPilotError::ApyOutOfBounds
+        );
+        require!(
+            new_apy_bps <= ctx.accounts.state.max_reasonable_apy_bps,
+            YieldPilotError::ApyOutOfBounds
+        );
+        validate_oracle(strategy_info, ctx.accounts.oracle.as_ref(), now)?;
+        strategy_info.last_apy_update_ts = signed_at;
+
+        let attester = ctx.accounts.state.evm_apy_attester;
+        let state = &mut ctx.accounts.state;
+        state.current_protocol = new_protocol;
+        state.current_apy_bps = new_apy_bps;
+        ctx.accounts
+            .history
+            .load_mut()?
+            .record_snapshot(new_protocol, new_apy_bps, now);
+
+        emit_cpi!(YieldUpdatedByEvmAttester {
+            state: ctx.accounts.state.key(),
+            protocol: new_protocol,
+            apy_bps: new_apy_bps,
+            attester,
+        });
+
+        Ok(())
+    }
+
+    /// Records `signer`'s observed APY for `protocol` on the strategy's `YieldReportBoard`,
+    /// overwriting their previous sample if they already hold a slot. Doesn't move
+    /// `state.current_apy_bps` by itself — `aggregate_yield` reads the board and takes the
+    /// median across everyone's most recent sample, so no single reporter can move the
+    /// vault's APY unilaterally the way `update_yield` lets an updater do.
+    pub fn submit_yield_report(
+        ctx: Context<SubmitYieldReport>,
+        protocol: u8,
+        apy_bps: u16,
+    ) -> Result<()> {
+        let state = &ctx.accounts.state;
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == state.authority || state.is_updater(&signer),
+            YieldPilotError::Unauthorized
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let board = &mut ctx.accounts.board;
+        board.version = CURRENT_STATE_VERSION;
+        board.state = state.key();
+        board.protocol = protocol;
+        board.bump = ctx.bumps.board;
+        board.record_sample(signer, apy_bps, now);
+
+        Ok(())
+    }
+
+    /// Takes the median of every still-fresh, still-registered sample on the strategy's
+    /// `YieldReportBoard` and, if at least `state.min_report_quorum` of them agree to
+    /// within the protocol's own bounds, lands it as `state.current_apy_bps`. Permissionless
+    /// like `crank_rebalance`: anyone can pay to land the aggregate once enough reporters
+    /// have submitted, so the vault doesn't depend on a single trusted caller noticing fresh
+    /// data is available.
+    pub fn aggregate_yield(ctx: Context<AggregateYield>, new_protocol: u8) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let strategy_info = ctx
+            .accounts
+            .strategy_info
+            .as_mut()
+            .ok_or(YieldPilotError::UnknownStrategy)?;
+        require!(strategy_info.enabled, YieldPilotError::StrategyDisabled);
+
+        let state = &ctx.accounts.state;
+        let median_apy_bps = ctx.accounts.board.median_apy_bps(
+            &state.updaters[..state.updater_count as usize],
+            strategy_info.max_staleness_secs,
+            now,
+            state.min_report_quorum,
+        )?;
+
+        require!(
+            median_apy_bps <= strategy_info.max_apy_bps,
+            YieldPilotError::ApyOutOfBounds
+        );
+        require!(
+            median_apy_bps <= state.max_reasonable_apy_bps,
+            YieldPilotError::ApyOutOfBounds
+        );
+        validate_oracle(strategy_info, ctx.accounts.oracle.as_ref(), now)?;
+        strategy_info.last_apy_update_ts = now;
+
+        let state = &mut ctx.accounts.state;
+        state.current_protocol = new_protocol;
+        state.current_apy_bps = median_apy_bps;
+        ctx.accounts
+            .history
+            .load_mut()?
+            .record_snapshot(new_protocol, median_apy_bps, now);
+
+        emit_cpi!(YieldUpdated {
+            state: ctx.accounts.state.key(),
+            protocol: new_protocol,
+            apy_bps: median_apy_bps,
+            actor: ctx.accounts.payer.key(),
+        });
+
+        Ok(())
+    }
+
+    pub fn register_strategy(
+        ctx: Context<RegisterStrategy>,
+        id: u8,
+        name: [u8; 32],
+        adapter_program: Pubkey,
+        max_apy_bps: u16,
+    ) -> Result<()> {
+        // Id 0 is the sentinel `apply_rebalance` and friends treat as "no strategy
+        // deployed"; registering a real strategy there would make a fat-fingered
+        // `new_protocol = 0` silently skip the deposit/withdraw adapter calls.
+        require!(id != 0, YieldPilotError::InvalidProtocol);
+
+        bitmap_set(&mut ctx.accounts.state.registered_protocols_bitmap, id);
+
+        let strategy_info = &mut ctx.accounts.strategy_info;
+        strategy_info.version = CURRENT_STATE_VERSION;
+        strategy_info.id = id;
+        strategy_info.name = name;
+        strategy_info.adapter_program = adapter_program;
+        strategy_info.max_apy_bps = max_apy_bps;
+        strategy_info.tvl = 0;
+        strategy_info.enabled = true;
+        strategy_info.position_account = Pubkey::default();
+        strategy_info.reserve = Pubkey::default();
+        strategy_info.oracle = Pubkey::default();
+        strategy_info.oracle_kind = OracleKind::Pyth;
+        strategy_info.max_oracle_staleness_secs = 0;
+        strategy_info.min_price = 0;
+        strategy_info.max_price = 0;
+        strategy_info.last_apy_update_ts = 0;
+        strategy_info.max_staleness_secs = 0;
+        strategy_info.target_weight_bps = MAX_WEIGHT_BPS;
+        strategy_info.max_weight_bps = MAX_WEIGHT_BPS;
+        strategy_info.max_tvl_lamports = 0;
+        strategy_info.bump = ctx.bumps.strategy_info;
+        strategy_info.route_via_sanctum = false;
+        strategy_info.sanctum_max_slippage_bps = 0;
+        strategy_info.last_valued_at = 0;
+        strategy_info.max_valuation_staleness_secs = 0;
+
+        Ok(())
+    }
+
+    /// Wires up the Pyth oracle `update_yield`/`rebalance` must check before trusting a
+    /// reported APY for this strategy. Passing `Pubkey::default()` as `oracle` disables the
+    /// check for strategies with no reliable feed.
+    pub fn set_strategy_oracle(
+        ctx: Context<ManageStrategy>,
+        oracle: Pubkey,
+        oracle_kind: OracleKind,
+        max_oracle_staleness_secs: i64,
+        min_price: i64,
+        max_price: i64,
+    ) -> Result<()> {
+        let strategy_info = &mut ctx.accounts.strategy_info;
+        strategy_info.oracle = oracle;
+        strategy_info.oracle_kind = oracle_kind;
+        strategy_info.max_oracle_staleness_secs = max_oracle_staleness_secs;
+        strategy_info.min_price = min_price;
+        strategy_info.max_price = max_price;
+
+        Ok(())
+    }
+
+    /// Records the vault-owned token account a strategy's adapter deposits into (e.g. the
+    /// vault's mSOL account for the Marinade adapter, or a lending adapter's cToken
+    /// account), so the router and off-chain valuation tooling know where the deployed
+    /// position lives.
+    pub fn set_strategy_position(ctx: Context<ManageStrategy>, position_account: Pubkey) -> Result<()> {
+        ctx.accounts.strategy_info.position_account = position_account;
+
+        Ok(())
+    }
+
+    /// Records the protocol-specific market/reserve account a lending strategy's adapter
+    /// reads for valuation (e.g. Solend's reserve).
+    pub fn set_strategy_reserve(ctx: Context<ManageStrategy>, reserve: Pubkey) -> Result<()> {
+        ctx.accounts.strategy_info.reserve = reserve;
+
+        Ok(())
+    }
+
+    /// Sets how old this strategy's last reported APY is allowed to be before
+    /// `rebalance`/`crank_rebalance` refuse to route into it. Zero disables the check.
+    pub fn set_strategy_max_staleness_secs(
+        ctx: Context<ManageStrategy>,
+        max_staleness_secs: i64,
+    ) -> Result<()> {
+        ctx.accounts.strategy_info.max_staleness_secs = max_staleness_secs;
+
+        Ok(())
+    }
+
+    /// Sets how old this strategy's last `refresh_valuation` is allowed to be before
+    /// `deposit`/`withdraw` refuse to act while it's `current_protocol`. Zero disables the
+    /// check.
+    pub fn set_strategy_valuation_staleness(
+        ctx: Context<ManageStrategy>,
+        max_valuation_staleness_secs: i64,
+    ) -> Result<()> {
+        ctx.accounts.strategy_info.max_valuation_staleness_secs = max_valuation_staleness_secs;
+
+        Ok(())
+    }
+
+    /// Sets how much of `total_assets` should be deployed here, and the hard ceilings on
+    /// that share, once this strategy becomes `current_protocol`. Each strategy is set
+    /// independently since `StrategyInfo` accounts are separate PDAs; it's on the caller
+    /// to keep `target_weight_bps` sane across the registered set (`rebalance` only
+    /// enforces the per-strategy caps below, not a cross-strategy total).
+    ///
+    /// `max_tvl_lamports` is an absolute ceiling alongside `max_weight_bps`'s relative one,
+    /// for operators who want to cap exposure to a single protocol in underlying terms
+    /// regardless of how `total_assets` grows. Zero disables the absolute cap, matching the
+    /// `oracle == Pubkey::default()` "disabled" convention used elsewhere in this file.
+    pub fn set_allocation_targets(
+        ctx: Context<ManageStrategy>,
+        target_weight_bps: u16,
+        max_weight_bps: u16,
+        max_tvl_lamports: u64,
+    ) -> Result<()> {
+        require!(
+            max_weight_bps <= MAX_WEIGHT_BPS,
+            YieldPilotError::WeightOutOfBounds
+        );
+        require!(
+            target_weight_bps <= max_weight_bps,
+            YieldPilotError::WeightOutOfBounds
+        );
+
+        ctx.accounts.strategy_info.target_weight_bps = target_weight_bps;
+        ctx.accounts.strategy_info.max_weight_bps = max_weight_bps;
+        ctx.accounts.strategy_info.max_tvl_lamports = max_tvl_lamports;
+
+        Ok(())
+    }
+
+    /// Opts this strategy into the `route_via_sanctum` swap path: `apply_rebalance` will
+    /// CPI `amount_in` of the old strategy's position token straight into this one via
+    /// `state.sanctum_router_program` instead of unwinding to the base asset and calling
+    /// `invoke_adapter("deposit", ...)`. Still a no-op end to end while
+    /// `state.sanctum_router_program` is unset.
+    pub fn set_sanctum_route(
+        ctx: Context<ManageStrategy>,
+        route_via_sanctum: bool,
+        sanctum_max_slippage_bps: u16,
+    ) -> Result<()> {
+        require!(
+            sanctum_max_slippage_bps <= MAX_WEIGHT_BPS,
+            YieldPilotError::WeightOutOfBounds
+        );
+
+        ctx.accounts.strategy_info.route_via_sanctum = route_via_sanctum;
+        ctx.accounts.strategy_info.sanctum_max_slippage_bps = sanctum_max_slippage_bps;
+
+        Ok(())
+    }
+
+    /// Sets this strategy's interest-rate-curve parameters, so `rebalance`/`crank_rebalance`/
+    /// `simulate_rebalance` can weigh the APY our own deposit would leave behind rather than
+    /// the spot rate a lending venue quotes before we've added size to it. `pool_liquidity`
+    /// should track the venue's total supply the same way `refresh_valuation` tracks our own
+    /// position — there's no adapter hook for it yet, so it's admin-reported. Zero either
+    /// field to fall back to spot-APY behavior, same convention as `max_tvl_lamports`.
+    pub fn set_rate_curve(
+        ctx: Context<ManageStrategy>,
+        pool_liquidity: u64,
+        rate_slope_bps: u16,
+    ) -> Result<()> {
+        require!(
+            rate_slope_bps <= MAX_WEIGHT_BPS,
+            YieldPilotError::WeightOutOfBounds
+        );
+
+        ctx.accounts.strategy_info.pool_liquidity = pool_liquidity;
+        ctx.accounts.strategy_info.rate_slope_bps = rate_slope_bps;
+
+        Ok(())
+    }
+
+    pub fn update_strategy(
+        ctx: Context<ManageStrategy>,
+        adapter_program: Pubkey,
+        max_apy_bps: u16,
+    ) -> Result<()> {
+        let strategy_info = &mut ctx.accounts.strategy_info;
+        strategy_info.adapter_program = adapter_program;
+        strategy_info.max_apy_bps = max_apy_bps;
+
+        Ok(())
+    }
+
+    pub fn disable_strategy(ctx: Context<ManageStrategy>) -> Result<()> {
+        ctx.accounts.strategy_info.enabled = false;
+
+        Ok(())
+    }
+
+    /// CPIs into `strategy_info`'s adapter to re-read its current underlying-denominated
+    /// value and records it as `strategy_info.tvl`, stamping `last_valued_at` so
+    /// `deposit`/`withdraw` can refuse to act on a stale mark-to-market. Permissionless,
+    /// like `crank_rebalance`: the value comes straight off the protocol's own accounts, so
+    /// there's nothing for an untrusted caller to forge.
+    pub fn refresh_valuation(ctx: Context<RefreshValuation>, _protocol: u8) -> Result<()> {
+        let tvl = invoke_adapter_value(ctx.remaining_accounts, &ctx.accounts.state)?;
+
+        let old_tvl = ctx.accounts.strategy_info.tvl;
+        accrue_yield_index(&mut ctx.accounts.state, old_tvl, tvl)?;
+
+        let strategy_info = &mut ctx.accounts.strategy_info;
+        strategy_info.tvl = tvl;
+        strategy_info.last_valued_at = Clock::get()?.unix_timestamp;
+        bitmap_set(&mut ctx.accounts.state.valuations_refreshed_bitmap, strategy_info.id);
+
+        emit_cpi!(ValuationRefreshed {
+            state: ctx.accounts.state.key(),
+            protocol: strategy_info.id,
+            tvl,
+        });
+
+        Ok(())
+    }
+
+    /// Paginated sibling of `refresh_valuation` for vaults with enough registered strategies
+    /// that refreshing them all in one transaction would blow the 1.4M CU budget. Walks
+    /// protocol ids `[start_index, start_index + count)`, skipping any id
+    /// `registered_protocols_bitmap` doesn't have set, and for each registered id expects
+    /// `remaining_accounts` to contain that strategy's `StrategyInfo` PDA followed by the
+    /// adapter accounts `value_position` needs — `adapter_account_counts[i]` gives the size of
+    /// the latter group, in the same order as the registered ids this call touches. A keeper
+    /// calls this repeatedly across several transactions, covering every registered id by the
+    /// time it calls `roll_epoch`; `roll_epoch` itself checks `valuations_refreshed_bitmap`
+    /// against `registered_protocols_bitmap` and refuses to roll until they match, then clears
+    /// the refreshed bitmap for the next epoch's pass.
+    pub fn refresh_valuations(
+        ctx: Context<RefreshValuations>,
+        start_index: u8,
+        count: u8,
+        adapter_account_counts: Vec<u8>,
+    ) -> Result<()> {
+        require!(count > 0, YieldPilotError::ZeroAmount);
+
+        let state_key = ctx.accounts.state.key();
+        let now = Clock::get()?.unix_timestamp;
+        let mut remaining = ctx.remaining_accounts;
+        let mut counts = adapter_account_counts.into_iter();
+
+        for offset in 0..count {
+            let protocol = start_index.wrapping_add(offset);
+            if protocol == 0 || !bitmap_get(&ctx.accounts.state.registered_protocols_bitmap, protocol) {
+                continue;
+            }
+
+            let (strategy_info_ai, rest) = remaining
+                .split_first()
+                .ok_or(YieldPilotError::InvalidAdapterAccounts)?;
+            let mut strategy_info: Account<StrategyInfo> = Account::try_from(strategy_info_ai)?;
+            require!(strategy_info.id == protocol, YieldPilotError::InvalidProtocol);
+            let expected_key = Pubkey::create_program_address(
+                &[
+                    b"strategy",
+                    state_key.as_ref(),
+                    &[protocol],
+                    &[strategy_info.bump],
+                ],
+                ctx.program_id,
+            )
+            .map_err(|_| YieldPilotError::InvalidAdapterAccounts)?;
+            require_keys_eq!(
+                strategy_info_ai.key(),
+                expected_key,
+                YieldPilotError::InvalidAdapterAccounts
+            );
+
+            let adapter_account_count = counts
+                .next()
+                .ok_or(YieldPilotError::InvalidAdapterAccounts)? as usize;
+            require!(
+                adapter_account_count <= rest.len(),
+                YieldPilotError::InvalidAdapterAccounts
+            );
+            let (adapter_accounts, rest) = rest.split_at(adapter_account_count);
+            remaining = rest;
+
+            let tvl = invoke_adapter_value(adapter_accounts, &ctx.accounts.state)?;
+            let old_tvl = strategy_info.tvl;
+            accrue_yield_index(&mut ctx.accounts.state, old_tvl, tvl)?;
+            strategy_info.tvl = tvl;
+            strategy_info.last_valued_at = now;
+            strategy_info.exit(ctx.program_id)?;
+            bitmap_set(&mut ctx.accounts.state.valuations_refreshed_bitmap, protocol);
+
+            emit_cpi!(ValuationRefreshed {
+                state: state_key,
+                protocol,
+                tvl,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Moves the vault's deployed assets from the currently active strategy's adapter
+    /// into the new one via CPI, only flipping `current_protocol` once both CPIs land so
+    /// on-chain state can never point at a protocol the funds were never moved to.
+    ///
+    /// `remaining_accounts` carries the adapter-specific accounts for both legs back to
+    /// back: the first `old_adapter_account_count` accounts (adapter program first) are
+    /// forwarded to the withdraw CPI on the outgoing adapter, the rest to the deposit CPI
+    /// on the incoming one.
+    pub fn rebalance(
+        ctx: Context<Rebalance>,
+        new_protocol: u8,
+        new_apy_bps: u16,
+        old_adapter_account_count: u8,
+        min_amount_out: u64,
+    ) -> Result<RebalanceOutcome> {
+        require!(
+            !ctx.accounts.state.rebalances_paused,
+            YieldPilotError::RebalancesPaused
+        );
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+        ctx.accounts.state.operation_in_progress = true;
+        let strategy_info = ctx
+            .accounts
+            .strategy_info
+            .as_ref()
+            .ok_or(YieldPilotError::UnknownStrategy)?;
+        require!(strategy_info.enabled, YieldPilotError::StrategyDisabled);
+        require!(
+            ctx.accounts.protocol_blacklist.is_none(),
+            YieldPilotError::ProtocolBlacklisted
+        );
+        require!(
+            new_apy_bps <= strategy_info.max_apy_bps,
+            YieldPilotError::ApyOutOfBounds
+        );
+        require!(
+            new_apy_bps <= ctx.accounts.state.max_reasonable_apy_bps,
+            YieldPilotError::ApyOutOfBounds
+        );
+        let now = Clock::get()?.unix_timestamp;
+        validate_oracle(strategy_info, ctx.accounts.oracle.as_ref(), now)?;
+        require!(
+            strategy_info.max_staleness_secs == 0
+                || now.saturating_sub(strategy_info.last_apy_update_ts)
+                    <= strategy_info.max_staleness_secs,
+            YieldPilotError::StaleYieldData
+        );
+
+        let state_authority = ctx.accounts.state.authority;
+        let vault_index_bytes = ctx.accounts.state.vault_index.to_le_bytes();
+        let state = &mut ctx.accounts.state;
+        let signer = ctx.accounts.signer.key();
+
+        require!(
+            signer == state.authority || state.is_updater(&signer),
+            YieldPilotError::Unauthorized
+        );
+        let projected_deployed_amount = weight_capped_deployment(
+            state.total_assets,
+            strategy_info.target_weight_bps,
+            strategy_info.max_weight_bps,
+            strategy_info.max_tvl_lamports,
+        );
+        if let Some(limits) = ctx.accounts.operator_limits.as_mut() {
+            limits.charge_rebalance_volume(now, projected_deployed_amount)?;
+        }
+        if let Some(queued) = ctx.accounts.queued_rebalance.as_ref() {
+            if queued.target_protocol == new_protocol {
+                require!(!queued.vetoed, YieldPilotError::RebalanceVetoed);
+                require!(
+                    Clock::get()?.slot >= queued.execute_after_slot,
+                    YieldPilotError::RebalanceVetoWindowActive
+                );
+            }
+        }
+        let projected_apy_bps = projected_apy_after_deposit(
+            new_apy_bps,
+            projected_deployed_amount,
+            strategy_info.pool_liquidity,
+            strategy_info.rate_slope_bps,
+        );
+        require!(
+            projected_apy_bps >= ctx
+                .accounts
+                .history
+                .load()?
+                .twap_apy_bps(now)
+                .saturating_add(state.min_improvement_bps),
+            YieldPilotError::ApyImprovementTooLow
+        );
+
+        let old_protocol = state.current_protocol;
+        let sanctum_router_program = state.sanctum_router_program;
+        let vault_authority_bump = ctx.bumps.vault_authority;
+        let vault_authority_seeds: &[&[u8]] = &[
+            b"vault_authority",
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[vault_authority_bump],
+        ];
+
+        let vault_authority_info = ctx.accounts.vault_authority.to_account_info();
+        let outcome = apply_rebalance(
+            &mut ctx.accounts.state,
+            &mut *ctx.accounts.history.load_mut()?,
+            &mut ctx.accounts.vault,
+            &vault_authority_info,
+            vault_authority_seeds,
+            old_protocol,
+            new_protocol,
+            new_apy_bps,
+            strategy_info.target_weight_bps,
+            strategy_info.max_weight_bps,
+            strategy_info.max_tvl_lamports,
+            strategy_info.route_via_sanctum,
+            strategy_info.sanctum_max_slippage_bps,
+            sanctum_router_program,
+            ctx.remaining_accounts,
+            old_adapter_account_count,
+            min_amount_out,
+            now,
+        )?;
+
+        if let Some(audit_log) = ctx.accounts.audit_log.as_ref() {
+            let mut params = [0u8; 32];
+            params[0] = new_protocol;
+            params[1..3].copy_from_slice(&new_apy_bps.to_le_bytes());
+            params[3..11].copy_from_slice(&outcome.amount_deployed.to_le_bytes());
+            audit_log.load_mut()?.record(
+                AUDIT_ACTION_REBALANCE,
+                signer,
+                Clock::get()?.slot,
+                params,
+            );
+        }
+
+        emit_cpi!(Rebalanced {
+            state: ctx.accounts.state.key(),
+            old_protocol,
+            new_protocol,
+            new_apy_bps,
+            amount: outcome.amount_deployed,
+            actor: signer,
+        });
+
+        ctx.accounts.state.operation_in_progress = false;
+
+        Ok(outcome)
+    }
+
+    /// Dry-runs `rebalance`'s validations and valuation math against `new_protocol` without
+    /// touching any state or moving funds, meant to be called via RPC simulation so a keeper
+    /// can pre-flight before paying priority fees on a rebalance that would just fail or
+    /// isn't worth it yet. Mirrors `rebalance`'s checks, short of the ones that only make
+    /// sense for a signed, funds-moving call: no authority/updater gate (nothing here is
+    /// authorized to act), no reentrancy guard, and no CPI — see `SimulatedRebalanceOutcome`
+    /// for what's projected instead of actually measured.
+    pub fn simulate_rebalance(
+        ctx: Context<SimulateRebalance>,
+        new_protocol: u8,
+        new_apy_bps: u16,
+    ) -> Result<SimulatedRebalanceOutcome> {
+        let state = &ctx.accounts.state;
+        require!(!state.rebalances_paused, YieldPilotError::RebalancesPaused);
+
+        let strategy_info = ctx
+            .accounts
+            .strategy_info
+            .as_ref()
+            .ok_or(YieldPilotError::UnknownStrategy)?;
+        require!(strategy_info.enabled, YieldPilotError::StrategyDisabled);
+        require!(
+            ctx.accounts.protocol_blacklist.is_none(),
+            YieldPilotError::ProtocolBlacklisted
+        );
+        require!(
+            new_apy_bps <= strategy_info.max_apy_bps,
+            YieldPilotError::ApyOutOfBounds
+        );
+        require!(
+            new_apy_bps <= state.max_reasonable_apy_bps,
+            YieldPilotError::ApyOutOfBounds
+        );
+        let now = Clock::get()?.unix_timestamp;
+        validate_oracle(strategy_info, ctx.accounts.oracle.as_ref(), now)?;
+        require!(
+            strategy_info.max_staleness_secs == 0
+                || now.saturating_sub(strategy_info.last_apy_update_ts)
+                    <= strategy_info.max_staleness_secs,
+            YieldPilotError::StaleYieldData
+        );
+        let projected_deployed_amount = weight_capped_deployment(
+            state.total_assets,
+            strategy_info.target_weight_bps,
+            strategy_info.max_weight_bps,
+            strategy_info.max_tvl_lamports,
+        );
+        let projected_apy_bps = projected_apy_after_deposit(
+            new_apy_bps,
+            projected_deployed_amount,
+            strategy_info.pool_liquidity,
+            strategy_info.rate_slope_bps,
+        );
+        require!(
+            projected_apy_bps >= ctx
+                .accounts
+                .history
+                .load()?
+                .twap_apy_bps(now)
+                .saturating_add(state.min_improvement_bps),
+            YieldPilotError::ApyImprovementTooLow
+        );
+
+        let projected_fee_value = if state.fee_recipient == Pubkey::default() {
+            0
+        } else {
+            accrued_fee_value(
+                state.total_assets,
+                state.management_fee_bps,
+                now.saturating_sub(state.last_fee_collection_ts),
+                state.performance_fee_bps,
+                state.high_water_mark,
+                state.total_shares,
+            )
+        };
+
+        Ok(SimulatedRebalanceOutcome {
+            old_protocol: state.current_protocol,
+            new_protocol,
+            projected_deployed_amount,
+            projected_fee_value,
+        })
+    }
+
+    /// Opens a `REBALANCE_VETO_WINDOW_SLOTS` window during which the guardian can
+    /// `veto_rebalance` before `rebalance` will act on `target_protocol`. Purely advisory
+    /// until `rebalance` is actually called with a matching `new_protocol` — queuing one
+    /// doesn't commit the vault to anything by itself, and an operator who doesn't want the
+    /// extra round-trip can still call `rebalance` directly for a protocol nothing is
+    /// queued against.
+    pub fn queue_rebalance(
+        ctx: Context<QueueRebalance>,
+        target_protocol: u8,
+        amount: u64,
+    ) -> Result<()> {
+        let state = &ctx.accounts.state;
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == state.authority || state.is_updater(&signer),
+            YieldPilotError::Unauthorized
+        );
+
+        let queued = &mut ctx.accounts.queued_rebalance;
+        queued.version = CURRENT_STATE_VERSION;
+        queued.target_protocol = target_protocol;
+        queued.amount = amount;
+        queued.execute_after_slot = Clock::get()?.slot + REBALANCE_VETO_WINDOW_SLOTS;
+        queued.vetoed = false;
+        queued.bump = ctx.bumps.queued_rebalance;
+
+        Ok(())
+    }
+
+    /// Guardian-only veto of a queued rebalance. Sets `vetoed` rather than closing the
+    /// record, so the operator can't dodge the veto by finding an empty account for the
+    /// same protocol and calling `rebalance` straight through; queuing it again re-arms a
+    /// fresh window the guardian gets another look at.
+    pub fn veto_rebalance(ctx: Context<VetoRebalance>) -> Result<()> {
+        ctx.accounts.queued_rebalance.vetoed = true;
+
+        Ok(())
+    }
+
+    /// Opens a chunked rebalance for moves too large for `rebalance`/`crank_rebalance` to take
+    /// in one call under `max_move_per_rebalance`. Runs the same up-front checks `rebalance`
+    /// does, then records a `RebalanceInProgress` checkpoint for `continue_rebalance` to drain;
+    /// doesn't move any funds itself. Unlike `rebalance`, the destination's `target_weight_bps`/
+    /// `max_weight_bps` aren't consulted — a chunked move always unwinds the full
+    /// `deployed_amount` out of `old_protocol` and redeploys everything it gets back, so use
+    /// `rebalance` directly for a move that also changes the deployed weight.
+    pub fn start_rebalance(
+        ctx: Context<StartRebalance>,
+        new_protocol: u8,
+        new_apy_bps: u16,
+        old_adapter_account_count: u8,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.state.rebalances_paused,
+            YieldPilotError::RebalancesPaused
+        );
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+        let strategy_info = ctx
+            .accounts
+            .strategy_info
+            .as_ref()
+            .ok_or(YieldPilotError::UnknownStrategy)?;
+        require!(strategy_info.enabled, YieldPilotError::StrategyDisabled);
+        require!(
+            ctx.accounts.protocol_blacklist.is_none(),
+            YieldPilotError::ProtocolBlacklisted
+        );
+        require!(
+            new_apy_bps <= strategy_info.max_apy_bps,
+            YieldPilotError::ApyOutOfBounds
+        );
+        require!(
+            new_apy_bps <= ctx.accounts.state.max_reasonable_apy_bps,
+            YieldPilotError::ApyOutOfBounds
+        );
+        let now = Clock::get()?.unix_timestamp;
+        validate_oracle(strategy_info, ctx.accounts.oracle.as_ref(), now)?;
+        require!(
+            strategy_info.max_staleness_secs == 0
+                || now.saturating_sub(strategy_info.last_apy_update_ts)
+                    <= strategy_info.max_staleness_secs,
+            YieldPilotError::StaleYieldData
+        );
+
+        let state = &mut ctx.accounts.state;
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == state.authority || state.is_updater(&signer),
+            YieldPilotError::Unauthorized
+        );
+        if let Some(queued) = ctx.accounts.queued_rebalance.as_ref() {
+            if queued.target_protocol == new_protocol {
+                require!(!queued.vetoed, YieldPilotError::RebalanceVetoed);
+                require!(
+                    Clock::get()?.slot >= queued.execute_after_slot,
+                    YieldPilotError::RebalanceVetoWindowActive
+                );
+            }
+        }
+        let projected_deployed_amount = weight_capped_deployment(
+            state.total_assets,
+            strategy_info.target_weight_bps,
+            strategy_info.max_weight_bps,
+            strategy_info.max_tvl_lamports,
+        );
+        let projected_apy_bps = projected_apy_after_deposit(
+            new_apy_bps,
+            projected_deployed_amount,
+            strategy_info.pool_liquidity,
+            strategy_info.rate_slope_bps,
+        );
+        require!(
+            projected_apy_bps >= ctx
+                .accounts
+                .history
+                .load()?
+                .twap_apy_bps(now)
+                .saturating_add(state.min_improvement_bps),
+            YieldPilotError::ApyImprovementTooLow
+        );
+        require!(
+            now.saturating_sub(state.last_rebalance_ts) >= state.rebalance_cooldown_secs,
+            YieldPilotError::RebalanceCooldownActive
+        );
+
+        let old_protocol = state.current_protocol;
+        state.operation_in_progress = true;
+
+        let rip = &mut ctx.accounts.rebalance_in_progress;
+        rip.version = CURRENT_STATE_VERSION;
+        rip.old_protocol = old_protocol;
+        rip.new_protocol = new_protocol;
+        rip.new_apy_bps = new_apy_bps;
+        rip.phase = 0;
+        rip.remaining_amount = state.deployed_amount;
+        rip.withdraw_amount = state.deployed_amount;
+        rip.withdrawn_total = 0;
+        rip.old_adapter_account_count = old_adapter_account_count;
+        rip.min_amount_out = min_amount_out;
+        rip.bump = ctx.bumps.rebalance_in_progress;
+
+        emit_cpi!(RebalanceStarted {
+            state: ctx.accounts.state.key(),
+            old_protocol,
+            new_protocol,
+            new_apy_bps,
+            amount: ctx.accounts.rebalance_in_progress.remaining_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Drains one `max_move_per_rebalance`-sized chunk of a `RebalanceInProgress` opened by
+    /// `start_rebalance`: while `phase` is 0 it unwinds a chunk out of `old_protocol`, and once
+    /// fully unwound it flips to `phase` 1 and redeploys a chunk into `new_protocol` on
+    /// subsequent calls. Permissionless like `crank_rebalance` — `start_rebalance` already
+    /// locked in the destination and APY, so a cranker here can only push the move along, not
+    /// redirect it. Call `finish_rebalance` once this returns a `RebalanceOutcome` with
+    /// `amount_deployed` set to close out the checkpoint.
+    pub fn continue_rebalance(ctx: Context<ContinueRebalance>) -> Result<RebalanceOutcome> {
+        require!(
+            !ctx.accounts.state.rebalances_paused,
+            YieldPilotError::RebalancesPaused
+        );
+
+        let state_authority = ctx.accounts.state.authority;
+        let vault_index_bytes = ctx.accounts.state.vault_index.to_le_bytes();
+        let max_chunk = if ctx.accounts.state.max_move_per_rebalance == 0 {
+            u64::MAX
+        } else {
+            ctx.accounts.state.max_move_per_rebalance
+        };
+        let vault_authority_seeds: &[&[u8]] = &[
+            b"vault_authority",
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+        let vault_authority_info = ctx.accounts.vault_authority.to_account_info();
+
+        let rip = &mut ctx.accounts.rebalance_in_progress;
+        let split = rip.old_adapter_account_count as usize;
+        require!(
+            split <= ctx.remaining_accounts.len(),
+            YieldPilotError::InvalidAdapterAccounts
+        );
+        let (old_adapter_accounts, new_adapter_accounts) = ctx.remaining_accounts.split_at(split);
+
+        if rip.phase == 0 {
+            let chunk = rip.remaining_amount.min(max_chunk);
+            if chunk > 0 && rip.old_protocol != 0 {
+                let vault_balance_before = ctx.accounts.vault.amount;
+                invoke_adapter(
+                    "withdraw",
+                    chunk,
+                    old_adapter_accounts,
+                    &vault_authority_info,
+                    vault_authority_seeds,
+                    &ctx.accounts.state,
+                )?;
+                ctx.accounts.vault.reload()?;
+                let received = ctx
+                    .accounts
+                    .vault
+                    .amount
+                    .checked_sub(vault_balance_before)
+                    .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+                rip.withdrawn_total = rip
+                    .withdrawn_total
+                    .checked_add(received)
+                    .ok_or(YieldPilotError::ArithmeticOverflow)?;
+            }
+            rip.remaining_amount = rip
+                .remaining_amount
+                .checked_sub(chunk)
+                .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+
+            emit_cpi!(RebalanceChunkApplied {
+                state: ctx.accounts.state.key(),
+                phase: rip.phase,
+                amount: chunk,
+                remaining_amount: rip.remaining_amount,
+            });
+
+            if rip.remaining_amount == 0 {
+                require!(
+                    rip.withdrawn_total >= rip.min_amount_out,
+                    YieldPilotError::SlippageExceeded
+                );
+                rip.phase = 1;
+                rip.remaining_amount = rip.withdrawn_total;
+            }
+
+            return Ok(RebalanceOutcome {
+                old_protocol: rip.old_protocol,
+                new_protocol: rip.new_protocol,
+                amount_deployed: 0,
+                realized_slippage: 0,
+            });
+        }
+
+        let chunk = rip.remaining_amount.min(max_chunk);
+        if chunk > 0 && rip.new_protocol != 0 {
+            invoke_adapter(
+                "deposit",
+                chunk,
+                new_adapter_accounts,
+                &vault_authority_info,
+                vault_authority_seeds,
+                &ctx.accounts.state,
+            )?;
+        }
+        rip.remaining_amount = rip
+            .remaining_amount
+            .checked_sub(chunk)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+
+        emit_cpi!(RebalanceChunkApplied {
+            state: ctx.accounts.state.key(),
+            phase: rip.phase,
+            amount: chunk,
+            remaining_amount: rip.remaining_amount,
+        });
+
+        Ok(RebalanceOutcome {
+            old_protocol: rip.old_protocol,
+            new_protocol: rip.new_protocol,
+            amount_deployed: if rip.remaining_amount == 0 {
+                rip.withdrawn_total
+            } else {
+                0
+            },
+            realized_slippage: rip.withdraw_amount.saturating_sub(rip.withdrawn_total),
+        })
+    }
+
+    /// Finalizes a `RebalanceInProgress` once `continue_rebalance` has fully redeployed it
+    /// (`phase` 1, `remaining_amount` 0), flipping `state.current_protocol` over and closing
+    /// the checkpoint. Split out from `continue_rebalance` because Anchor's `close` constraint
+    /// always closes the account once the instruction succeeds, and a chunk still in flight
+    /// must leave the checkpoint open for the next `continue_rebalance` call.
+    pub fn finish_rebalance(ctx: Context<FinishRebalance>) -> Result<RebalanceOutcome> {
+        let rip = &ctx.accounts.rebalance_in_progress;
+        require!(
+            rip.phase == 1 && rip.remaining_amount == 0,
+            YieldPilotError::RebalanceStillInProgress
+        );
+
+        let old_protocol = rip.old_protocol;
+        let new_protocol = rip.new_protocol;
+        let new_apy_bps = rip.new_apy_bps;
+        let amount_deployed = rip.withdrawn_total;
+        let realized_slippage = rip.withdraw_amount.saturating_sub(rip.withdrawn_total);
+
+        let now = Clock::get()?.unix_timestamp;
+        let state = &mut ctx.accounts.state;
+        state.current_protocol = new_protocol;
+        state.current_apy_bps = new_apy_bps;
+        state.deployed_amount = amount_deployed;
+        state.last_rebalance_ts = now;
+        state.operation_in_progress = false;
+
+        ctx.accounts
+            .history
+            .load_mut()?
+            .record_snapshot(new_protocol, new_apy_bps, now);
+
+        emit_cpi!(Rebalanced {
+            state: ctx.accounts.state.key(),
+            old_protocol,
+            new_protocol,
+            new_apy_bps,
+            amount: amount_deployed,
+            actor: ctx.accounts.cranker.key(),
+        });
+
+        Ok(RebalanceOutcome {
+            old_protocol,
+            new_protocol,
+            amount_deployed,
+            realized_slippage,
+        })
+    }
+
+    /// Creates an Address Lookup Table owned by the vault's `vault_authority` PDA, sized
+    /// for the adapter accounts a multi-protocol `rebalance`/`crank_rebalance` needs.
+    /// Rebalances that move funds between two adapters pass both their account sets as
+    /// `remaining_accounts`, which can blow past the legacy transaction's ~35-account
+    /// limit; a versioned transaction referencing this ALT instead only spends 1 byte per
+    /// account. Call `extend_adapter_lookup_table` afterwards to populate it — creation and
+    /// population are separate native-program instructions.
+    pub fn create_adapter_lookup_table(
+        ctx: Context<CreateAdapterLookupTable>,
+        recent_slot: u64,
+    ) -> Result<()> {
+        let state_authority = ctx.accounts.state.authority;
+        let vault_index_bytes = ctx.accounts.state.vault_index.to_le_bytes();
+        let vault_authority_seeds: &[&[u8]] = &[
+            b"vault_authority",
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let (ix, lookup_table_address) = address_lookup_table::instruction::create_lookup_table_signed(
+            ctx.accounts.vault_authority.key(),
+            ctx.accounts.payer.key(),
+            recent_slot,
+        );
+        require_keys_eq!(
+            lookup_table_address,
+            ctx.accounts.lookup_table.key(),
+            YieldPilotError::InvalidLookupTableAddress
+        );
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.lookup_table.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &[vault_authority_seeds],
+        )?;
+
+        Ok(())
+    }
+
+    /// Appends `new_addresses` (adapter program ids and their per-protocol accounts) to a
+    /// lookup table created by `create_adapter_lookup_table`. Can be called repeatedly as
+    /// more protocols are registered; the Address Lookup Table program itself caps a table
+    /// at 256 entries.
+    pub fn extend_adapter_lookup_table(
+        ctx: Context<ExtendAdapterLookupTable>,
+        new_addresses: Vec<Pubkey>,
+    ) -> Result<()> {
+        require!(!new_addresses.is_empty(), YieldPilotError::ZeroAmount);
+
+        let state_authority = ctx.accounts.state.authority;
+        let vault_index_bytes = ctx.accounts.state.vault_index.to_le_bytes();
+        let vault_authority_seeds: &[&[u8]] = &[
+            b"vault_authority",
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+
+        let ix = address_lookup_table::instruction::extend_lookup_table(
+            ctx.accounts.lookup_table.key(),
+            ctx.accounts.vault_authority.key(),
+            Some(ctx.accounts.payer.key()),
+            new_addresses,
+        );
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.lookup_table.to_account_info(),
+                ctx.accounts.vault_authority.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &[vault_authority_seeds],
+        )?;
+
+        Ok(())
+    }
+
+    /// Creates or updates the share mint's Metaplex Token Metadata account (name, symbol,
+    /// URI), CPI-signed by the `vault_authority` PDA that already holds `share_mint`'s mint
+    /// authority. Wallets otherwise show the share token as "Unknown Token" with nothing to
+    /// render. Idempotent: creates the metadata account the first time it's called for a
+    /// vault, and updates it in place on every call after.
+    pub fn set_share_metadata(
+        ctx: Context<SetShareMetadata>,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        require!(
+            name.len() <= MAX_METADATA_NAME_LEN,
+            YieldPilotError::MetadataFieldTooLong
+        );
+        require!(
+            symbol.len() <= MAX_METADATA_SYMBOL_LEN,
+            YieldPilotError::MetadataFieldTooLong
+        );
+        require!(
+            uri.len() <= MAX_METADATA_URI_LEN,
+            YieldPilotError::MetadataFieldTooLong
+        );
+
+        let state_authority = ctx.accounts.state.authority;
+        let vault_index_bytes = ctx.accounts.state.vault_index.to_le_bytes();
+        let vault_authority_seeds: &[&[u8]] = &[
+            b"vault_authority",
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+        let vault_authority_info = ctx.accounts.vault_authority.to_account_info();
+
+        if ctx.accounts.metadata.data_is_empty() {
+            let ix = Instruction {
+                program_id: METAPLEX_TOKEN_METADATA_PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(ctx.accounts.metadata.key(), false),
+                    AccountMeta::new_readonly(ctx.accounts.share_mint.key(), false),
+                    AccountMeta::new_readonly(vault_authority_info.key(), true),
+                    AccountMeta::new(ctx.accounts.payer.key(), true),
+                    AccountMeta::new_readonly(vault_authority_info.key(), true),
+                    AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+                    AccountMeta::new_readonly(ctx.accounts.rent.key(), false),
+                ],
+                data: create_metadata_v3_instruction_data(&name, &symbol, &uri),
+            };
+
+            invoke_signed(
+                &ix,
+                &[
+                    ctx.accounts.metadata.to_account_info(),
+                    ctx.accounts.share_mint.to_account_info(),
+                    vault_authority_info.clone(),
+                    ctx.accounts.payer.to_account_info(),
+                    vault_authority_info.clone(),
+                    ctx.accounts.system_program.to_account_info(),
+                    ctx.accounts.rent.to_account_info(),
+                ],
+                &[vault_authority_seeds],
+            )?;
+        } else {
+            let ix = Instruction {
+                program_id: METAPLEX_TOKEN_METADATA_PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(ctx.accounts.metadata.key(), false),
+                    AccountMeta::new_readonly(vault_authority_info.key(), true),
+                ],
+                data: update_metadata_v2_instruction_data(&name, &symbol, &uri),
+            };
+
+            invoke_signed(
+                &ix,
+                &[ctx.accounts.metadata.to_account_info(), vault_authority_info.clone()],
+                &[vault_authority_seeds],
+            )?;
+        }
+
+        emit_cpi!(ShareMetadataUpdated {
+            state: ctx.accounts.state.key(),
+            share_mint: ctx.accounts.share_mint.key(),
+            name,
+            symbol,
+            uri,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless counterpart to `rebalance`: anyone can call this, but it only
+    /// succeeds when the on-chain conditions `rebalance` would otherwise have an
+    /// authority vouch for already hold — the new strategy clears `min_improvement_bps`,
+    /// its oracle is fresh, and `rebalance_cooldown_secs` has elapsed since the last move.
+    /// This lets a keeper crank allocation changes without being trusted with authority.
+    pub fn crank_rebalance(
+        ctx: Context<CrankRebalance>,
+        new_protocol: u8,
+        new_apy_bps: u16,
+        old_adapter_account_count: u8,
+        min_amount_out: u64,
+    ) -> Result<RebalanceOutcome> {
+        require!(
+            !ctx.accounts.state.rebalances_paused,
+            YieldPilotError::RebalancesPaused
+        );
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+        ctx.accounts.state.operation_in_progress = true;
+        let strategy_info = ctx
+            .accounts
+            .strategy_info
+            .as_ref()
+            .ok_or(YieldPilotError::UnknownStrategy)?;
+        require!(strategy_info.enabled, YieldPilotError::StrategyDisabled);
+        require!(
+            ctx.accounts.protocol_blacklist.is_none(),
+            YieldPilotError::ProtocolBlacklisted
+        );
+        require!(
+            new_apy_bps <= strategy_info.max_apy_bps,
+            YieldPilotError::ApyOutOfBounds
+        );
+        require!(
+            new_apy_bps <= ctx.accounts.state.max_reasonable_apy_bps,
+            YieldPilotError::ApyOutOfBounds
+        );
+        let now = Clock::get()?.unix_timestamp;
+        validate_oracle(strategy_info, ctx.accounts.oracle.as_ref(), now)?;
+        require!(
+            strategy_info.max_staleness_secs == 0
+                || now.saturating_sub(strategy_info.last_apy_update_ts)
+                    <= strategy_info.max_staleness_secs,
+            YieldPilotError::StaleYieldData
+        );
+
+        let state_authority = ctx.accounts.state.authority;
+        let vault_index_bytes = ctx.accounts.state.vault_index.to_le_bytes();
+        let state = &mut ctx.accounts.state;
+
+        let projected_deployed_amount = weight_capped_deployment(
+            state.total_assets,
+            strategy_info.target_weight_bps,
+            strategy_info.max_weight_bps,
+            strategy_info.max_tvl_lamports,
+        );
+        let projected_apy_bps = projected_apy_after_deposit(
+            new_apy_bps,
+            projected_deployed_amount,
+            strategy_info.pool_liquidity,
+            strategy_info.rate_slope_bps,
+        );
+        require!(
+            projected_apy_bps >= ctx
+                .accounts
+                .history
+                .load()?
+                .twap_apy_bps(now)
+                .saturating_add(state.min_improvement_bps),
+            YieldPilotError::ApyImprovementTooLow
+        );
+
+        let old_protocol = state.current_protocol;
+        let crank_tip_bps = state.crank_tip_bps;
+        let sanctum_router_program = state.sanctum_router_program;
+        let vault_authority_bump = ctx.bumps.vault_authority;
+        let vault_authority_seeds: &[&[u8]] = &[
+            b"vault_authority",
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[vault_authority_bump],
+        ];
+
+        let cranker = ctx.accounts.cranker.key();
+        let vault_authority_info = ctx.accounts.vault_authority.to_account_info();
+        let outcome = apply_rebalance(
+            &mut ctx.accounts.state,
+            &mut *ctx.accounts.history.load_mut()?,
+            &mut ctx.accounts.vault,
+            &vault_authority_info,
+            vault_authority_seeds,
+            old_protocol,
+            new_protocol,
+            new_apy_bps,
+            strategy_info.target_weight_bps,
+            strategy_info.max_weight_bps,
+            strategy_info.max_tvl_lamports,
+            strategy_info.route_via_sanctum,
+            strategy_info.sanctum_max_slippage_bps,
+            sanctum_router_program,
+            ctx.remaining_accounts,
+            old_adapter_account_count,
+            min_amount_out,
+            now,
+        )?;
+
+        emit_cpi!(Rebalanced {
+            state: ctx.accounts.state.key(),
+            old_protocol,
+            new_protocol,
+            new_apy_bps,
+            amount: outcome.amount_deployed,
+            actor: cranker,
+        });
+
+        // Tip is based on the amount actually deployed, not `total_assets`, so a partial
+        // allocation (see `StrategyInfo::target_weight_bps`) doesn't overpay the cranker
+        // relative to the work done.
+        let tip = ((outcome.amount_deployed as u128 * crank_tip_bps as u128) / 10_000) as u64;
+        if tip > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault.to_account_info(),
+                        mint: ctx.accounts.mint.to_account_info(),
+                        to: ctx.accounts.cranker_token_account.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    &[vault_authority_seeds],
+                ),
+                tip,
+                ctx.accounts.mint.decimals,
+            )?;
+
+            ctx.accounts.state.total_assets = ctx
+                .accounts
+                .state
+                .total_assets
+                .checked_sub(tip)
+                .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        }
+
+        ctx.accounts.state.operation_in_progress = false;
+
+        Ok(outcome)
+    }
+
+    /// Tops up `current_protocol` with whatever idle balance sits above `buffer_bps`.
+    /// Deposits are routed entirely into `vault` up front, so over time the idle balance
+    /// can drift above the target buffer as depositors come in; this pushes the excess out
+    /// without waiting for the next `rebalance`.
+    pub fn deploy_idle(ctx: Context<DeployIdle>) -> Result<()> {
+        require!(
+            !ctx.accounts.state.rebalances_paused,
+            YieldPilotError::RebalancesPaused
+        );
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+        let state = &mut ctx.accounts.state;
+        state.operation_in_progress = true;
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == state.authority || state.is_updater(&signer),
+            YieldPilotError::Unauthorized
+        );
+        require!(state.current_protocol != 0, YieldPilotError::UnknownStrategy);
+        require!(
+            ctx.accounts.protocol_blacklist.is_none(),
+            YieldPilotError::ProtocolBlacklisted
+        );
+
+        let state_authority = state.authority;
+        let vault_index_bytes = state.vault_index.to_le_bytes();
+        let vault_authority_seeds: &[&[u8]] = &[
+            b"vault_authority",
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+        let vault_authority_info = ctx.accounts.vault_authority.to_account_info();
+
+        let excess = deploy_excess_idle(
+            state,
+            &vault_authority_info,
+            vault_authority_seeds,
+            ctx.remaining_accounts,
+        )?;
+        require!(excess > 0, YieldPilotError::NoExcessLiquidity);
+
+        emit_cpi!(IdleDeployed {
+            state: ctx.accounts.state.key(),
+            protocol: ctx.accounts.state.current_protocol,
+            amount: excess,
+        });
+
+        ctx.accounts.state.operation_in_progress = false;
+
+        Ok(())
+    }
+
+    /// Claims pending protocol rewards for `current_protocol` via CPI and folds them back
+    /// into `total_assets` so they compound into the share price. If the reward mint
+    /// differs from the vault's underlying asset, the claimed amount is routed through
+    /// `swap_program` first; `claim_adapter_account_count` splits `remaining_accounts`
+    /// between the reward-claim CPI and the swap CPI, mirroring how `old_adapter_account_count`
+    /// splits `rebalance`'s accounts between the outgoing and incoming adapters.
+    pub fn harvest(
+        ctx: Context<Harvest>,
+        claim_adapter_account_count: u8,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+        let state = &mut ctx.accounts.state;
+        state.operation_in_progress = true;
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == state.authority || state.is_updater(&signer),
+            YieldPilotError::Unauthorized
+        );
+        require!(state.current_protocol != 0, YieldPilotError::UnknownStrategy);
+
+        let split = claim_adapter_account_count as usize;
+        require!(
+            split <= ctx.remaining_accounts.len(),
+            YieldPilotError::InvalidAdapterAccounts
+        );
+        let (claim_accounts, swap_accounts) = ctx.remaining_accounts.split_at(split);
+
+        let balance_before = ctx.accounts.reward_account.amount;
+
+        let state_authority = state.authority;
+        let vault_index_bytes = state.vault_index.to_le_bytes();
+        let vault_authority_seeds: &[&[u8]] = &[
+            b"vault_authority",
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+        let vault_authority_info = ctx.accounts.vault_authority.to_account_info();
+
+        invoke_adapter(
+            "claim_rewards",
+            0,
+            claim_accounts,
+            &vault_authority_info,
+            vault_authority_seeds,
+            state,
+        )?;
+
+        ctx.accounts.reward_account.reload()?;
+        let claimed = ctx
+            .accounts
+            .reward_account
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        require!(claimed > 0, YieldPilotError::NothingToHarvest);
+
+        let reward_mint = ctx.accounts.reward_account.mint;
+        let added = if reward_mint == state.mint {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.reward_account.to_account_info(),
+                        mint: ctx.accounts.mint.to_account_info(),
+                        to: ctx.accounts.vault.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    &[vault_authority_seeds],
+                ),
+                claimed,
+                ctx.accounts.mint.decimals,
+            )?;
+            claimed
+        } else {
+            require!(
+                state.swap_program != Pubkey::default(),
+                YieldPilotError::SwapProgramNotConfigured
+            );
+            let vault_balance_before = ctx.accounts.vault.amount;
+
+            invoke_swap(
+                claimed,
+                min_amount_out,
+                swap_accounts,
+                &vault_authority_info,
+                vault_authority_seeds,
+                state.swap_program,
+            )?;
+
+            ctx.accounts.vault.reload()?;
+            let received = ctx
+                .accounts
+                .vault
+                .amount
+                .checked_sub(vault_balance_before)
+                .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+            require!(
+                received >= min_amount_out,
+                YieldPilotError::SlippageExceeded
+            );
+            received
+        };
+
+        state.total_assets = state
+            .total_assets
+            .checked_add(added)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        state.lifetime_yield_earned = state
+            .lifetime_yield_earned
+            .checked_add(added)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        state.last_harvest_ts = Clock::get()?.unix_timestamp;
+        let protocol = state.current_protocol;
+
+        emit_cpi!(Harvested {
+            state: ctx.accounts.state.key(),
+            protocol,
+            reward_mint,
+            amount: added,
+        });
+
+        state.operation_in_progress = false;
+
+        Ok(())
+    }
+
+    pub fn set_min_improvement_bps(ctx: Context<ManageUpdaters>, min_improvement_bps: u16) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        state.min_improvement_bps = min_improvement_bps;
+
+        Ok(())
+    }
+
+    pub fn set_rebalance_cooldown_secs(ctx: Context<ManageUpdaters>, rebalance_cooldown_secs: i64) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        state.rebalance_cooldown_secs = rebalance_cooldown_secs;
+
+        Ok(())
+    }
+
+    pub fn set_max_move_per_rebalance(ctx: Context<ManageUpdaters>, max_move_per_rebalance: u64) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        state.max_move_per_rebalance = max_move_per_rebalance;
+
+        Ok(())
+    }
+
+    /// Zero disables the check; see `YieldState::max_withdrawal_bps_per_epoch`.
+    pub fn set_max_withdrawal_bps_per_epoch(
+        ctx: Context<ManageUpdaters>,
+        max_withdrawal_bps_per_epoch: u16,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        state.max_withdrawal_bps_per_epoch = max_withdrawal_bps_per_epoch;
+
+        Ok(())
+    }
+
+    pub fn set_epoch_length_secs(ctx: Context<ManageUpdaters>, epoch_length_secs: i64) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        state.epoch_length_secs = epoch_length_secs;
+
+        Ok(())
+    }
+
+    /// Sets (or clears, via `Pubkey::default()`) the off-chain keeper key that
+    /// `update_yield_signed` will accept Ed25519-signed APY updates from.
+    pub fn set_apy_oracle_signer(ctx: Context<ManageUpdaters>, apy_oracle_signer: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        state.apy_oracle_signer = apy_oracle_signer;
+
+        Ok(())
+    }
+
+    /// Sets (or clears, via `[0u8; 20]`) the Ethereum address `update_yield_attested_evm`
+    /// will accept secp256k1-signed APY updates from.
+    pub fn set_evm_apy_attester(ctx: Context<ManageUpdaters>, evm_apy_attester: [u8; 20]) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        state.evm_apy_attester = evm_apy_attester;
+
+        Ok(())
+    }
+
+    /// Sets how many fresh `YieldReportBoard` samples `aggregate_yield` requires before it
+    /// will land a median. Zero is treated the same as one everywhere it's read.
+    pub fn set_min_report_quorum(ctx: Context<ManageUpdaters>, min_report_quorum: u8) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        state.min_report_quorum = min_report_quorum;
+
+        Ok(())
+    }
+
+    pub fn set_crank_tip_bps(ctx: Context<ManageUpdaters>, crank_tip_bps: u16) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        state.crank_tip_bps = crank_tip_bps;
+
+        Ok(())
+    }
+
+    pub fn set_fee_config(
+        ctx: Context<ManageUpdaters>,
+        management_fee_bps: u16,
+        performance_fee_bps: u16,
+        insurance_bps: u16,
+        referral_bps: u16,
+    ) -> Result<()> {
+        require!(
+            insurance_bps.checked_add(referral_bps).ok_or(YieldPilotError::ArithmeticOverflow)? <= MAX_WEIGHT_BPS,
+            YieldPilotError::WeightOutOfBounds
+        );
+
+        let state = &mut ctx.accounts.state;
+
+        state.management_fee_bps = management_fee_bps;
+        state.performance_fee_bps = performance_fee_bps;
+        state.insurance_bps = insurance_bps;
+        state.referral_bps = referral_bps;
+
+        Ok(())
+    }
+
+    pub fn set_fee_recipient(ctx: Context<ManageUpdaters>, fee_recipient: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        state.fee_recipient = fee_recipient;
+
+        Ok(())
+    }
+
+    /// Sets `wallet`'s fee-tier discount, applied to their pro-rata share of `collect_fees`'s
+    /// mint when `wallet` supplies its position to that call. `10_000` exempts the position
+    /// entirely (e.g. treasury deposits); pass zero to return `wallet` to the standard rate.
+    pub fn set_fee_tier(ctx: Context<SetFeeTier>, _wallet: Pubkey, fee_discount_bps: u16) -> Result<()> {
+        require!(
+            fee_discount_bps <= MAX_WEIGHT_BPS,
+            YieldPilotError::WeightOutOfBounds
+        );
+
+        ctx.accounts.user_position.fee_discount_bps = fee_discount_bps;
+
+        Ok(())
+    }
+
+    /// Configures the Wormhole Core Bridge `publish_state` CPIs into. Zero (the default)
+    /// leaves `publish_state` disabled, since the right address is cluster-specific.
+    pub fn set_wormhole_program(ctx: Context<ManageUpdaters>, wormhole_program: Pubkey) -> Result<()> {
+        ctx.accounts.state.wormhole_program = wormhole_program;
+
+        Ok(())
+    }
+
+    /// Links `successor_vault` as this vault's migration target, enabling `migrate_position`
+    /// for depositors. Doesn't verify `successor_vault` is a real `YieldState` or shares this
+    /// vault's `mint` — `migrate_position` checks both at the point shares actually move, so
+    /// a bad address here just leaves migration broken rather than unsafe. Pass
+    /// `Pubkey::default()` to unlink.
+    pub fn migrate_to(ctx: Context<ManageUpdaters>, successor_vault: Pubkey) -> Result<()> {
+        ctx.accounts.state.successor_vault = successor_vault;
+
+        Ok(())
+    }
+
+    /// Caps how large `total_assets` can grow via `deposit`. Zero disables the cap. Meant
+    /// for a soft launch (e.g. a $100k-equivalent ceiling) that authority raises or removes
+    /// once the vault has proven itself.
+    pub fn set_max_total_deposits(ctx: Context<ManageUpdaters>, max_total_deposits: u64) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        state.max_total_deposits = max_total_deposits;
+
+        Ok(())
+    }
+
+    /// Caps how much a single depositor can put in over the lifetime of their position
+    /// (`UserPosition::cumulative_deposits`). Zero disables the cap.
+    pub fn set_max_deposit_per_user(ctx: Context<ManageUpdaters>, max_deposit_per_user: u64) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        state.max_deposit_per_user = max_deposit_per_user;
+
+        Ok(())
+    }
+
+    /// Toggles allowlist-gated deposits for a permissioned pilot. When enabled, `deposit`
+    /// requires the depositor to have an `AllowlistEntry` PDA from `add_to_allowlist`.
+    pub fn set_allowlist_enabled(ctx: Context<ManageUpdaters>, allowlist_enabled: bool) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        state.allowlist_enabled = allowlist_enabled;
+
+        Ok(())
+    }
+
+    /// Creates the marker PDA that lets `wallet` pass the `deposit` allowlist check.
+    pub fn add_to_allowlist(ctx: Context<AddToAllowlist>, wallet: Pubkey) -> Result<()> {
+        ctx.accounts.allowlist_entry.version = CURRENT_STATE_VERSION;
+        ctx.accounts.allowlist_entry.bump = ctx.bumps.allowlist_entry;
+
+        emit_cpi!(AllowlistUpdated {
+            state: ctx.accounts.state.key(),
+            wallet,
+            allowed: true,
+        });
+
+        Ok(())
+    }
+
+    /// Closes `wallet`'s marker PDA, revoking its ability to pass the `deposit` allowlist
+    /// check while `allowlist_enabled` is on.
+    pub fn remove_from_allowlist(ctx: Context<RemoveFromAllowlist>, wallet: Pubkey) -> Result<()> {
+        emit_cpi!(AllowlistUpdated {
+            state: ctx.accounts.state.key(),
+            wallet,
+            allowed: false,
+        });
+
+        Ok(())
+    }
+
+    /// Reassigns the guardian role. Authority-gated like the other admin setters, since
+    /// handing out pause rights is itself a parameter change.
+    pub fn set_guardian(ctx: Context<ManageUpdaters>, guardian: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        state.guardian = guardian;
+
+        Ok(())
+    }
+
+    /// Flips the vault's circuit breakers. Guardian-gated and independent of `authority`
+    /// so pausing in an emergency doesn't require the (possibly compromised or slow)
+    /// admin key.
+    pub fn set_pause_flags(
+        ctx: Context<ManageGuardian>,
+        deposits_paused: bool,
+        withdrawals_paused: bool,
+        rebalances_paused: bool,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        state.deposits_paused = deposits_paused;
+        state.withdrawals_paused = withdrawals_paused;
+        state.rebalances_paused = rebalances_paused;
+
+        Ok(())
+    }
+
+    /// Guardian-only panic button: unwinds the entire deployed position back into `vault`'s
+    /// idle balance in one call and pauses deposits, for when a venue shows signs of exploit
+    /// and waiting on `rebalance`'s normal cooldown/APY-improvement gating isn't safe. Unlike
+    /// `rebalance`, there's no new protocol to deploy into — funds just sit idle in `vault`
+    /// until the authority re-deploys via a normal `rebalance` once the venue is clear.
+    /// Still within the guardian's can't-move-funds boundary: this only ever returns custody
+    /// to the vault's own account, never sends anywhere else.
+    pub fn emergency_exit(ctx: Context<EmergencyExit>, min_amount_out: u64) -> Result<()> {
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+        let state = &mut ctx.accounts.state;
+        state.operation_in_progress = true;
+        let old_protocol = state.current_protocol;
+        require!(old_protocol != 0, YieldPilotError::UnknownStrategy);
+
+        let state_authority = state.authority;
+        let vault_index_bytes = state.vault_index.to_le_bytes();
+        let deployed_amount = state.deployed_amount;
+        let vault_authority_seeds: &[&[u8]] = &[
+            b"vault_authority",
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+        let vault_authority_info = ctx.accounts.vault_authority.to_account_info();
+
+        let balance_before = ctx.accounts.vault.amount;
+        invoke_adapter(
+            "withdraw",
+            deployed_amount,
+            ctx.remaining_accounts,
+            &vault_authority_info,
+            vault_authority_seeds,
+            state,
+        )?;
+        ctx.accounts.vault.reload()?;
+        let received = ctx
+            .accounts
+            .vault
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        require!(received >= min_amount_out, YieldPilotError::SlippageExceeded);
+
+        let state = &mut ctx.accounts.state;
+        state.current_protocol = 0;
+        state.deployed_amount = 0;
+        state.deposits_paused = true;
+        state.operation_in_progress = false;
+
+        emit_cpi!(EmergencyExited {
+            state: ctx.accounts.state.key(),
+            old_protocol,
+            amount_recovered: received,
+            actor: ctx.accounts.guardian.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Guardian-gated, immediate block on new allocations to `protocol` — checked by
+    /// `rebalance`/`crank_rebalance`/`deploy_idle` before they'd otherwise deploy into it.
+    /// Faster and lower-privilege than `disable_strategy` going through `ManageStrategy`'s
+    /// authority gate: a guardian who's spotted trouble at a venue doesn't need to wait on
+    /// the (possibly slower) admin key to lock it out.
+    pub fn blacklist_protocol(ctx: Context<BlacklistProtocol>, protocol: u8) -> Result<()> {
+        ctx.accounts.protocol_blacklist.version = CURRENT_STATE_VERSION;
+        ctx.accounts.protocol_blacklist.protocol = protocol;
+        ctx.accounts.protocol_blacklist.bump = ctx.bumps.protocol_blacklist;
+
+        emit_cpi!(ProtocolBlacklistUpdated {
+            state: ctx.accounts.state.key(),
+            protocol,
+            blacklisted: true,
+        });
+
+        Ok(())
+    }
+
+    /// Closes `protocol`'s blacklist marker, letting `rebalance`/`crank_rebalance`/
+    /// `deploy_idle` allocate to it again.
+    pub fn unblacklist_protocol(ctx: Context<UnblacklistProtocol>, protocol: u8) -> Result<()> {
+        emit_cpi!(ProtocolBlacklistUpdated {
+            state: ctx.accounts.state.key(),
+            protocol,
+            blacklisted: false,
+        });
+
+        Ok(())
+    }
+
+    /// Records a risky parameter change with a mandatory delay before it can land, so
+    /// depositors have a window to exit if they disagree with where it's headed.
+    pub fn queue_param_change(ctx: Context<QueueParamChange>, change: ParamChangeKind) -> Result<()> {
+        let pending_change = &mut ctx.accounts.pending_change;
+        pending_change.version = CURRENT_STATE_VERSION;
+        pending_change.change = change;
+        pending_change.earliest_execution_ts =
+            Clock::get()?.unix_timestamp + PARAM_CHANGE_TIMELOCK_SECS;
+        pending_change.bump = ctx.bumps.pending_change;
+
+        Ok(())
+    }
+
+    /// Applies a queued parameter change once its timelock has elapsed. Permissionless:
+    /// the delay itself is what protects depositors, not who calls this.
+    pub fn execute_param_change(ctx: Context<ExecuteParamChange>) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.pending_change.earliest_execution_ts,
+            YieldPilotError::ParamChangeNotReady
+        );
+
+        let state = &mut ctx.accounts.state;
+        match ctx.accounts.pending_change.change.clone() {
+            ParamChangeKind::FeeConfig {
+                management_fee_bps,
+                performance_fee_bps,
+            } => {
+                state.management_fee_bps = management_fee_bps;
+                state.performance_fee_bps = performance_fee_bps;
+            }
+            ParamChangeKind::FeeRecipient { fee_recipient } => {
+                state.fee_recipient = fee_recipient;
+            }
+            ParamChangeKind::MinImprovementBps { min_improvement_bps } => {
+                state.min_improvement_bps = min_improvement_bps;
+            }
+            ParamChangeKind::RebalanceCooldownSecs {
+                rebalance_cooldown_secs,
+            } => {
+                state.rebalance_cooldown_secs = rebalance_cooldown_secs;
+            }
+            ParamChangeKind::CrankTipBps { crank_tip_bps } => {
+                state.crank_tip_bps = crank_tip_bps;
+            }
+            ParamChangeKind::InstantWithdrawalFeeBps {
+                instant_withdrawal_fee_bps,
+            } => {
+                state.instant_withdrawal_fee_bps = instant_withdrawal_fee_bps;
+            }
+            ParamChangeKind::BufferBps { buffer_bps } => {
+                state.buffer_bps = buffer_bps;
+            }
+            ParamChangeKind::SwapProgram { swap_program } => {
+                state.swap_program = swap_program;
+            }
+            ParamChangeKind::MaxReasonableApyBps {
+                max_reasonable_apy_bps,
+            } => {
+                require!(max_reasonable_apy_bps > 0, YieldPilotError::ApyOutOfBounds);
+                state.max_reasonable_apy_bps = max_reasonable_apy_bps;
+            }
+            ParamChangeKind::SanctumRouterProgram {
+                sanctum_router_program,
+            } => {
+                state.sanctum_router_program = sanctum_router_program;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Drops a queued change before it executes. Guardian-gated so the same role that
+    /// can halt the vault in an emergency can also veto a bad config before it lands.
+    pub fn cancel_param_change(ctx: Context<CancelParamChange>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Opens a share-weighted governance proposal for one `ParamChangeKind` change.
+    /// `shares_to_lock` are transferred into `governance_escrow` and count as the creator's
+    /// implicit "for" vote; they're released by `reclaim_vote` once voting ends.
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        change: ParamChangeKind,
+        shares_to_lock: u64,
+    ) -> Result<()> {
+        require!(shares_to_lock > 0, YieldPilotError::ZeroAmount);
+
+        let state = &mut ctx.accounts.state;
+        let id = state.next_proposal_id;
+        state.next_proposal_id = state
+            .next_proposal_id
+            .checked_add(1)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.proposer_share_account.to_account_info(),
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.governance_escrow.to_account_info(),
+                    authority: ctx.accounts.proposer.to_account_info(),
+                },
+            ),
+            shares_to_lock,
+            ctx.accounts.share_mint.decimals,
+        )?;
+
+        let voting_ends_at = Clock::get()?.unix_timestamp + GOVERNANCE_VOTING_PERIOD_SECS;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.version = CURRENT_STATE_VERSION;
+        proposal.id = id;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.change = change;
+        proposal.votes_for = shares_to_lock;
+        proposal.votes_against = 0;
+        proposal.voting_ends_at = voting_ends_at;
+        proposal.queued = false;
+        proposal.bump = ctx.bumps.proposal;
+
+        let vote_record = &mut ctx.accounts.vote_record;
+        vote_record.version = CURRENT_STATE_VERSION;
+        vote_record.locked_shares = shares_to_lock;
+        vote_record.support = true;
+        vote_record.bump = ctx.bumps.vote_record;
+
+        emit_cpi!(ProposalCreated {
+            state: ctx.accounts.state.key(),
+            proposal_id: id,
+            proposer: ctx.accounts.proposer.key(),
+            voting_ends_at,
+        });
+
+        Ok(())
+    }
+
+    /// Locks `shares_to_lock` in support of or against an open proposal. One vote per
+    /// `(proposal, voter)`; `vote_record`'s `init` constraint rejects a second call for the
+    /// same voter.
+    pub fn vote_proposal(
+        ctx: Context<VoteProposal>,
+        _proposal_id: u64,
+        support: bool,
+        shares_to_lock: u64,
+    ) -> Result<()> {
+        require!(shares_to_lock > 0, YieldPilotError::ZeroAmount);
+        require!(
+            Clock::get()?.unix_timestamp < ctx.accounts.proposal.voting_ends_at,
+            YieldPilotError::VotingPeriodEnded
+        );
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.voter_share_account.to_account_info(),
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.governance_escrow.to_account_info(),
+                    authority: ctx.accounts.voter.to_account_info(),
+                },
+            ),
+            shares_to_lock,
+            ctx.accounts.share_mint.decimals,
+        )?;
+
+        let proposal = &mut ctx.accounts.proposal;
+        if support {
+            proposal.votes_for = proposal
+                .votes_for
+                .checked_add(shares_to_lock)
+                .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        } else {
+            proposal.votes_against = proposal
+                .votes_against
+                .checked_add(shares_to_lock)
+                .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        }
+
+        let vote_record = &mut ctx.accounts.vote_record;
+        vote_record.version = CURRENT_STATE_VERSION;
+        vote_record.locked_shares = shares_to_lock;
+        vote_record.support = support;
+        vote_record.bump = ctx.bumps.vote_record;
+
+        Ok(())
+    }
+
+    /// Returns a voter's locked shares and closes their `VoteRecord` once voting has ended,
+    /// regardless of which way the proposal went or whether it was queued.
+    pub fn reclaim_vote(ctx: Context<ReclaimVote>, _proposal_id: u64) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.proposal.voting_ends_at,
+            YieldPilotError::VotingStillOpen
+        );
+
+        let locked_shares = ctx.accounts.vote_record.locked_shares;
+        let state_authority = ctx.accounts.state.authority;
+        let vault_index_bytes = ctx.accounts.state.vault_index.to_le_bytes();
+        let seeds = &[
+            b"vault_authority".as_ref(),
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.governance_escrow.to_account_info(),
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.voter_share_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            locked_shares,
+            ctx.accounts.share_mint.decimals,
+        )?;
+
+        Ok(())
+    }
+
+    /// Hands a passed proposal to the same timelock `queue_param_change` uses, rather than
+    /// applying `change` directly, so depositors who disagree get the standard
+    /// `PARAM_CHANGE_TIMELOCK_SECS` window to exit before it lands. Permissionless: anyone
+    /// can queue a proposal that's cleared quorum and a majority once voting closes.
+    pub fn queue_proposal_execution(ctx: Context<QueueProposalExecution>, _proposal_id: u64) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.proposal.voting_ends_at,
+            YieldPilotError::VotingStillOpen
+        );
+        require!(
+            !ctx.accounts.proposal.queued,
+            YieldPilotError::ProposalAlreadyQueued
+        );
+
+        let proposal_id = ctx.accounts.proposal.id;
+        let votes_for = ctx.accounts.proposal.votes_for;
+        let votes_against = ctx.accounts.proposal.votes_against;
+        let change = ctx.accounts.proposal.change.clone();
+
+        let total_votes = votes_for
+            .checked_add(votes_against)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        let quorum_shares = (ctx.accounts.state.total_shares as u128)
+            .checked_mul(GOVERNANCE_QUORUM_BPS as u128)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?
+            / MAX_WEIGHT_BPS as u128;
+        require!(
+            total_votes as u128 >= quorum_shares,
+            YieldPilotError::QuorumNotMet
+        );
+        require!(votes_for > votes_against, YieldPilotError::ProposalRejected);
+
+        let pending_change = &mut ctx.accounts.pending_change;
+        pending_change.version = CURRENT_STATE_VERSION;
+        pending_change.change = change;
+        pending_change.earliest_execution_ts =
+            Clock::get()?.unix_timestamp + PARAM_CHANGE_TIMELOCK_SECS;
+        pending_change.bump = ctx.bumps.pending_change;
+
+        ctx.accounts.proposal.queued = true;
+
+        emit_cpi!(ProposalQueued {
+            state: ctx.accounts.state.key(),
+            proposal_id,
+            votes_for,
+            votes_against,
+        });
+
+        Ok(())
+    }
+
+    /// Records a pending write-down with a mandatory delay before it can land, mirroring
+    /// `queue_param_change`'s timelock: depositors see the evidence hash and the amount
+    /// on-chain and have a window to withdraw before the share price actually drops,
+    /// rather than being caught by a write-down with no warning.
+    pub fn queue_loss_report(
+        ctx: Context<QueueLossReport>,
+        amount: u64,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(amount > 0, YieldPilotError::ZeroAmount);
+
+        let pending_report = &mut ctx.accounts.pending_report;
+        pending_report.version = CURRENT_STATE_VERSION;
+        pending_report.amount = amount;
+        pending_report.evidence_hash = evidence_hash;
+        pending_report.earliest_execution_ts =
+            Clock::get()?.unix_timestamp + PARAM_CHANGE_TIMELOCK_SECS;
+        pending_report.bump = ctx.bumps.pending_report;
+
+        Ok(())
+    }
+
+    /// Applies a queued loss report once its timelock has elapsed: writes `total_assets`
+    /// down by `amount`, so every depositor's share is worth proportionally less rather
+    /// than whoever withdraws last absorbing the entire loss. Permissionless like
+    /// `execute_param_change` — the delay is what protects depositors, not who calls this.
+    pub fn execute_loss_report(ctx: Context<ExecuteLossReport>) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.pending_report.earliest_execution_ts,
+            YieldPilotError::ParamChangeNotReady
+        );
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+
+        let amount = ctx.accounts.pending_report.amount;
+        let evidence_hash = ctx.accounts.pending_report.evidence_hash;
+
+        let state = &mut ctx.accounts.state;
+        state.total_assets = state
+            .total_assets
+            .checked_sub(amount)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+
+        emit_cpi!(LossReported {
+            state: ctx.accounts.state.key(),
+            amount,
+            evidence_hash,
+            total_assets_after: ctx.accounts.state.total_assets,
+        });
+
+        Ok(())
+    }
+
+    /// Drops a queued loss report before it executes. Guardian-gated, same rationale as
+    /// `cancel_param_change`: the emergency-pause role can also veto a bad write-down.
+    pub fn cancel_loss_report(ctx: Context<CancelLossReport>) -> Result<()> {
+        Ok(())
+    }
+
+    /// One-time setup of the vault's insurance fund share account. Must run before
+    /// `insurance_bps` is set above zero, since `collect_fees` only routes a slice of the
+    /// fee there when the account already exists.
+    pub fn initialize_insurance_fund(ctx: Context<InitializeInsuranceFund>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Accrues the management fee (annualized, time-weighted since the last collection)
+    /// and mints the resulting shares to the configured `fee_recipient`. Deterministic in
+    /// `Clock::unix_timestamp`, so repeated calls never double-charge a given interval.
+    pub fn collect_fees(ctx: Context<CollectFees>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let state = &mut ctx.accounts.state;
+
+        require!(
+            state.fee_recipient != Pubkey::default(),
+            YieldPilotError::MissingFeeRecipient
+        );
+
+        let elapsed = now.saturating_sub(state.last_fee_collection_ts);
+        state.last_fee_collection_ts = now;
+
+        let fee_value = accrued_fee_value(
+            state.total_assets,
+            state.management_fee_bps,
+            elapsed,
+            state.performance_fee_bps,
+            state.high_water_mark,
+            state.total_shares,
+        );
+
+        let share_price = current_share_price(state.total_assets, state.total_shares);
+        if share_price > state.high_water_mark {
+            state.high_water_mark = share_price;
+        }
+
+        if fee_value == 0 {
+            return Ok(());
+        }
+
+        require!(
+            !state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+        state.operation_in_progress = true;
+
+        let total_shares_before_mint = state.total_shares;
+        let shares_minted = if state.total_shares == 0 {
+            fee_value
+        } else {
+            shares_for_amount(fee_value, state.total_shares, state.total_assets, state.decimals_offset)?
+        };
+
+        let insurance_shares = match ctx.accounts.insurance_fund_share_account.as_ref() {
+            Some(_) if state.insurance_bps > 0 => {
+                ((shares_minted as u128 * state.insurance_bps as u128) / 10_000u128) as u64
+            }
+            _ => 0,
+        };
+        let referral_shares = match ctx.accounts.referrer_share_account.as_ref() {
+            Some(_) if state.referral_bps > 0 && state.referrer != Pubkey::default() => {
+                ((shares_minted as u128 * state.referral_bps as u128) / 10_000u128) as u64
+            }
+            _ => 0,
+        };
+        // Rebates `fee_tier_position`'s pro-rata share of this mint back to itself instead
+        // of `fee_recipient`, scaled by its own `fee_discount_bps` rather than a vault-wide
+        // rate — `collect_fees` never enumerates positions, so only the one supplied here
+        // can be discounted on a given call.
+        let fee_tier_shares = match (
+            ctx.accounts.fee_tier_position.as_ref(),
+            ctx.accounts.fee_tier_share_account.as_ref(),
+        ) {
+            (Some(position), Some(_)) if position.fee_discount_bps > 0 && total_shares_before_mint > 0 => {
+                let pro_rata_shares =
+                    ((shares_minted as u128 * position.shares as u128) / total_shares_before_mint as u128) as u64;
+                ((pro_rata_shares as u128 * position.fee_discount_bps as u128) / 10_000u128) as u64
+            }
+            _ => 0,
+        };
+        let carved_out_shares = insurance_shares
+            .checked_add(referral_shares)
+            .and_then(|sum| sum.checked_add(fee_tier_shares))
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        require!(
+            carved_out_shares <= shares_minted,
+            YieldPilotError::FeeSharesExceedMinted
+        );
+        let recipient_shares = shares_minted
+            .checked_sub(carved_out_shares)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+
+        let state_authority = state.authority;
+        let vault_index_bytes = state.vault_index.to_le_bytes();
+        let seeds = &[
+            b"vault_authority".as_ref(),
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+
+        if recipient_shares > 0 {
+            token_interface::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    MintTo {
+                        mint: ctx.accounts.share_mint.to_account_info(),
+                        to: ctx.accounts.fee_recipient_share_account.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                recipient_shares,
+            )?;
+        }
+
+        if insurance_shares > 0 {
+            token_interface::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    MintTo {
+                        mint: ctx.accounts.share_mint.to_account_info(),
+                        to: ctx
+                            .accounts
+                            .insurance_fund_share_account
+                            .as_ref()
+                            .unwrap()
+                            .to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                insurance_shares,
+            )?;
+        }
+
+        if referral_shares > 0 {
+            token_interface::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    MintTo {
+                        mint: ctx.accounts.share_mint.to_account_info(),
+                        to: ctx
+                            .accounts
+                            .referrer_share_account
+                            .as_ref()
+                            .unwrap()
+                            .to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                referral_shares,
+            )?;
+        }
+
+        if fee_tier_shares > 0 {
+            token_interface::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    MintTo {
+                        mint: ctx.accounts.share_mint.to_account_info(),
+                        to: ctx
+                            .accounts
+                            .fee_tier_share_account
+                            .as_ref()
+                            .unwrap()
+                            .to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                fee_tier_shares,
+            )?;
+        }
+
+        ctx.accounts.state.total_shares = ctx
+            .accounts
+            .state
+            .total_shares
+            .checked_add(shares_minted)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        ctx.accounts.state.lifetime_fees_collected = ctx
+            .accounts
+            .state
+            .lifetime_fees_collected
+            .checked_add(fee_value)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        emit_cpi!(FeesCollected {
+            state: ctx.accounts.state.key(),
+            fee_value,
+            shares_minted,
+            high_water_mark: ctx.accounts.state.high_water_mark,
+            insurance_shares,
+            referral_shares,
+            fee_tier_shares,
+        });
+
+        ctx.accounts.state.operation_in_progress = false;
+
+        Ok(())
+    }
+
+    /// Governance-gated top-up after a strategy loss: burns `amount`'s worth of the
+    /// insurance fund's own shares (priced at the pre-top-up exchange rate) and credits
+    /// `amount` to `total_assets`. The burn keeps the insurance fund from double-dipping on
+    /// the top-up it just paid for; the net effect raises the share price for everyone else,
+    /// partially offsetting whatever loss `amount` is meant to cover.
+    pub fn cover_loss(ctx: Context<CoverLoss>, amount: u64) -> Result<()> {
+        require!(amount > 0, YieldPilotError::ZeroAmount);
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+
+        let state = &mut ctx.accounts.state;
+        state.operation_in_progress = true;
+
+        let shares_to_burn = if state.total_shares == 0 {
+            0
+        } else {
+            shares_for_amount(amount, state.total_shares, state.total_assets, state.decimals_offset)?
+        };
+        require!(
+            ctx.accounts.insurance_fund_share_account.amount >= shares_to_burn,
+            YieldPilotError::InsufficientInsuranceFund
+        );
+
+        let state_authority = state.authority;
+        let vault_index_bytes = state.vault_index.to_le_bytes();
+        let seeds = &[
+            b"vault_authority".as_ref(),
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+
+        if shares_to_burn > 0 {
+            token_interface::burn(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Burn {
+                        mint: ctx.accounts.share_mint.to_account_info(),
+                        from: ctx.accounts.insurance_fund_share_account.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                shares_to_burn,
+            )?;
+        }
+
+        ctx.accounts.state.total_shares = ctx
+            .accounts
+            .state
+            .total_shares
+            .checked_sub(shares_to_burn)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        ctx.accounts.state.total_assets = ctx
+            .accounts
+            .state
+            .total_assets
+            .checked_add(amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        emit_cpi!(LossCovered {
+            state: ctx.accounts.state.key(),
+            amount,
+            shares_burned: shares_to_burn,
+        });
+
+        ctx.accounts.state.operation_in_progress = false;
+
+        Ok(())
+    }
+
+    /// One-time setup for the liquidity-mining program: records the reward mint and
+    /// initial emission rate, and creates the `rewards_vault` PDA token account
+    /// `fund_rewards` pays into. Must run before `claim_rewards` has anything to pay out.
+    pub fn initialize_rewards_vault(
+        ctx: Context<InitializeRewardsVault>,
+        emission_per_second: u64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        state.reward_mint = ctx.accounts.reward_mint.key();
+        state.reward_emission_per_second = emission_per_second;
+        state.reward_per_share_index = 0;
+        state.last_reward_update_ts = Clock::get()?.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// Tops up `rewards_vault` with more of the reward token. Anyone holding a claim
+    /// against the vault can call `claim_rewards` regardless of who funded it, so this is
+    /// just a transfer, not tied to any particular depositor.
+    pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
+        require!(amount > 0, YieldPilotError::ZeroAmount);
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.authority_reward_account.to_account_info(),
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.rewards_vault.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.reward_mint.decimals,
+        )?;
+
+        Ok(())
+    }
+
+    /// Opens a new Merkle-distributed rewards campaign for this vault, committing to
+    /// `root` without yet funding it — `fund_distributor` tops up `distributor_vault`
+    /// separately, mirroring `initialize_rewards_vault`/`fund_rewards`'s init-then-fund
+    /// split. Lets a retroactive-incentives campaign for thousands of depositors land as
+    /// one small root instead of thousands of admin transactions.
+    pub fn create_distributor(ctx: Context<CreateDistributor>, root: [u8; 32]) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        let id = state.next_distributor_id;
+        state.next_distributor_id = state
+            .next_distributor_id
+            .checked_add(1)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        let distributor = &mut ctx.accounts.distributor;
+        distributor.version = CURRENT_STATE_VERSION;
+        distributor.id = id;
+        distributor.mint = ctx.accounts.mint.key();
+        distributor.root = root;
+        distributor.total_amount = 0;
+        distributor.claimed_amount = 0;
+        distributor.bump = ctx.bumps.distributor;
+
+        emit_cpi!(DistributorCreated {
+            state: ctx.accounts.state.key(),
+            distributor_id: id,
+            mint: ctx.accounts.mint.key(),
+            root,
+        });
+
+        Ok(())
+    }
+
+    /// Tops up a campaign's `distributor_vault` with more of its reward token. Anyone can
+    /// fund a campaign, mirroring `fund_rewards` — payouts only ever go to Merkle-proven
+    /// leaves, so extra funding can't be misdirected.
+    pub fn fund_distributor(
+        ctx: Context<FundDistributor>,
+        _distributor_id: u64,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, YieldPilotError::ZeroAmount);
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.funder_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.distributor_vault.to_account_info(),
+                    authority: ctx.accounts.funder.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        ctx.accounts.distributor.total_amount = ctx
+            .accounts
+            .distributor
+            .total_amount
+            .checked_add(amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        Ok(())
+    }
+
+    /// Claims leaf `(index, claimant, amount)` of a campaign once its Merkle proof
+    /// verifies against `distributor.root`, transferring `amount` of the campaign's reward
+    /// token to `claimant_token_account`. `claim_receipt`'s `init` constraint makes a
+    /// second claim of the same `index` fail outright.
+    pub fn claim(
+        ctx: Context<Claim>,
+        _distributor_id: u64,
+        index: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let leaf = keccak::hashv(&[
+            &index.to_le_bytes(),
+            ctx.accounts.claimant.key().as_ref(),
+            &amount.to_le_bytes(),
+        ])
+        .to_bytes();
+
+        require!(
+            verify_merkle_proof(&proof, ctx.accounts.distributor.root, leaf),
+            YieldPilotError::InvalidMerkleProof
+        );
+
+        let state_authority = ctx.accounts.state.authority;
+        let vault_index_bytes = ctx.accounts.state.vault_index.to_le_bytes();
+        let seeds = &[
+            b"vault_authority".as_ref(),
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.distributor_vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.claimant_token_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        ctx.accounts.claim_receipt.version = CURRENT_STATE_VERSION;
+        ctx.accounts.claim_receipt.bump = ctx.bumps.claim_receipt;
+
+        let distributor_id = ctx.accounts.distributor.id;
+        ctx.accounts.distributor.claimed_amount = ctx
+            .accounts
+            .distributor
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        emit_cpi!(RewardsDistributed {
+            state: ctx.accounts.state.key(),
+            distributor_id,
+            claimant: ctx.accounts.claimant.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Changes the emission rate going forward. Accrues the index under the old rate
+    /// first, so emission that already happened isn't retroactively repriced.
+    pub fn set_reward_emission_rate(
+        ctx: Context<ManageUpdaters>,
+        emission_per_second: u64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        accrue_reward_index(state)?;
+        state.reward_emission_per_second = emission_per_second;
+
+        Ok(())
+    }
+
+    /// Pays out a depositor's accrued liquidity-mining rewards. Settling and claiming are
+    /// the same step since there's nothing else to do with a settled balance.
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        accrue_reward_index(state)?;
+
+        let user_position = &mut ctx.accounts.user_position;
+        settle_pending_rewards(state, user_position)?;
+        settle_accrued_yield(state, user_position)?;
+
+        let amount = user_position.pending_rewards;
+        require!(amount > 0, YieldPilotError::NothingToClaim);
+        user_position.pending_rewards = 0;
+
+        let state_authority = state.authority;
+        let vault_index_bytes = state.vault_index.to_le_bytes();
+        let seeds = &[
+            b"vault_authority".as_ref(),
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.rewards_vault.to_account_info(),
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.depositor_reward_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            ctx.accounts.reward_mint.decimals,
+        )?;
+
+        emit_cpi!(RewardsClaimed {
+            state: ctx.accounts.state.key(),
+            depositor: ctx.accounts.depositor.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    pub fn deposit(
+        ctx: Context<Deposit>,
+        amount: u64,
+        referrer: Option<Pubkey>,
+        lock_duration_secs: i64,
+    ) -> Result<()> {
+        require!(amount > 0, YieldPilotError::ZeroAmount);
+        require!(!ctx.accounts.state.deposits_paused, YieldPilotError::DepositsPaused);
+        require!(
+            !ctx.accounts.state.allowlist_enabled || ctx.accounts.allowlist_entry.is_some(),
+            YieldPilotError::NotAllowlisted
+        );
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+        check_valuation_fresh(
+            ctx.accounts.strategy_info.as_deref(),
+            ctx.accounts.state.current_protocol,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let state = &mut ctx.accounts.state;
+        state.operation_in_progress = true;
+        if state.max_total_deposits > 0 {
+            let new_total_assets = state
+                .total_assets
+                .checked_add(amount)
+                .ok_or(YieldPilotError::ArithmeticOverflow)?;
+            require!(
+                new_total_assets <= state.max_total_deposits,
+                YieldPilotError::TotalDepositCapExceeded
+            );
+        }
+        if state.max_deposit_per_user > 0 {
+            let new_user_deposits = ctx
+                .accounts
+                .user_position
+                .cumulative_deposits
+                .checked_add(amount)
+                .ok_or(YieldPilotError::ArithmeticOverflow)?;
+            require!(
+                new_user_deposits <= state.max_deposit_per_user,
+                YieldPilotError::UserDepositCapExceeded
+            );
+        }
+
+        let balance_before = ctx.accounts.vault.amount;
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.depositor_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        // `amount` is what the depositor sent, but a transfer-fee-extension mint can skim
+        // some of it in transit, so shares are minted against what the vault actually
+        // received rather than the instruction argument.
+        ctx.accounts.vault.reload()?;
+        let credited = ctx
+            .accounts
+            .vault
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+
+        let state = &mut ctx.accounts.state;
+        accrue_reward_index(state)?;
+        let shares_minted = if state.total_shares == 0 {
+            credited
+        } else {
+            shares_for_amount(credited, state.total_shares, state.total_assets, state.decimals_offset)?
+        };
+
+        let state_authority = state.authority;
+        let vault_index_bytes = state.vault_index.to_le_bytes();
+        let seeds = &[
+            b"vault_authority".as_ref(),
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.depositor_share_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            shares_minted,
+        )?;
+
+        state.total_shares = state
+            .total_shares
+            .checked_add(shares_minted)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        state.total_assets = state
+            .total_assets
+            .checked_add(credited)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        state.lifetime_deposits = state
+            .lifetime_deposits
+            .checked_add(credited)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        let user_position = &mut ctx.accounts.user_position;
+        user_position.version = CURRENT_STATE_VERSION;
+        settle_pending_rewards(state, user_position)?;
+        settle_accrued_yield(state, user_position)?;
+        user_position.shares = user_position
+            .shares
+            .checked_add(shares_minted)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        user_position.cumulative_deposits = user_position
+            .cumulative_deposits
+            .checked_add(credited)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        user_position.last_action_slot = Clock::get()?.slot;
+        user_position.last_deposit_slot = user_position.last_action_slot;
+        user_position.bump = ctx.bumps.user_position;
+        record_referrer(state, user_position, referrer);
+        apply_lock(user_position, lock_duration_secs)?;
+
+        emit_cpi!(Deposited {
+            state: ctx.accounts.state.key(),
+            depositor: ctx.accounts.depositor.key(),
+            amount: credited,
+            shares_minted,
+        });
+
+        state.operation_in_progress = false;
+
+        Ok(())
+    }
+
+    /// `deposit`, but pulled via an SPL token delegate approval instead of the owner's own
+    /// signature — `owner_token_account.owner` must have `approve`d `vault_authority` for at
+    /// least `amount` beforehand. Lets an aggregator or integrator frontend submit the
+    /// deposit (and pay its rent/fees) in one click on the owner's behalf without ever
+    /// holding the owner's keypair; shares and the position land on the owner exactly as a
+    /// self-submitted `deposit` would.
+    pub fn deposit_with_delegate(
+        ctx: Context<DepositWithDelegate>,
+        amount: u64,
+        referrer: Option<Pubkey>,
+        lock_duration_secs: i64,
+    ) -> Result<()> {
+        require!(amount > 0, YieldPilotError::ZeroAmount);
+        require!(!ctx.accounts.state.deposits_paused, YieldPilotError::DepositsPaused);
+        require!(
+            !ctx.accounts.state.allowlist_enabled || ctx.accounts.allowlist_entry.is_some(),
+            YieldPilotError::NotAllowlisted
+        );
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+        require!(
+            amount <= ctx.accounts.owner_token_account.delegated_amount,
+            YieldPilotError::NotDelegatedToVault
+        );
+        check_valuation_fresh(
+            ctx.accounts.strategy_info.as_deref(),
+            ctx.accounts.state.current_protocol,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let owner = ctx.accounts.owner_token_account.owner;
+        let state = &mut ctx.accounts.state;
+        state.operation_in_progress = true;
+        if state.max_total_deposits > 0 {
+            let new_total_assets = state
+                .total_assets
+                .checked_add(amount)
+                .ok_or(YieldPilotError::ArithmeticOverflow)?;
+            require!(
+                new_total_assets <= state.max_total_deposits,
+                YieldPilotError::TotalDepositCapExceeded
+            );
+        }
+        if state.max_deposit_per_user > 0 {
+            let new_user_deposits = ctx
+                .accounts
+                .user_position
+                .cumulative_deposits
+                .checked_add(amount)
+                .ok_or(YieldPilotError::ArithmeticOverflow)?;
+            require!(
+                new_user_deposits <= state.max_deposit_per_user,
+                YieldPilotError::UserDepositCapExceeded
+            );
+        }
+
+        let balance_before = ctx.accounts.vault.amount;
+
+        let state_authority = state.authority;
+        let vault_index_bytes = state.vault_index.to_le_bytes();
+        let seeds = &[
+            b"vault_authority".as_ref(),
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.owner_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        ctx.accounts.vault.reload()?;
+        let credited = ctx
+            .accounts
+            .vault
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+
+        let state = &mut ctx.accounts.state;
+        accrue_reward_index(state)?;
+        let shares_minted = if state.total_shares == 0 {
+            credited
+        } else {
+            shares_for_amount(credited, state.total_shares, state.total_assets, state.decimals_offset)?
+        };
+
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.owner_share_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            shares_minted,
+        )?;
+
+        state.total_shares = state
+            .total_shares
+            .checked_add(shares_minted)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        state.total_assets = state
+            .total_assets
+            .checked_add(credited)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        state.lifetime_deposits = state
+            .lifetime_deposits
+            .checked_add(credited)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        let user_position = &mut ctx.accounts.user_position;
+        user_position.version = CURRENT_STATE_VERSION;
+        settle_pending_rewards(state, user_position)?;
+        settle_accrued_yield(state, user_position)?;
+        user_position.shares = user_position
+            .shares
+            .checked_add(shares_minted)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        user_position.cumulative_deposits = user_position
+            .cumulative_deposits
+            .checked_add(credited)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        user_position.last_action_slot = Clock::get()?.slot;
+        user_position.last_deposit_slot = user_position.last_action_slot;
+        user_position.bump = ctx.bumps.user_position;
+        record_referrer(state, user_position, referrer);
+        apply_lock(user_position, lock_duration_secs)?;
+
+        emit_cpi!(Deposited {
+            state: ctx.accounts.state.key(),
+            depositor: owner,
+            amount: credited,
+            shares_minted,
+        });
+
+        state.operation_in_progress = false;
+
+        Ok(())
+    }
+
+    /// `deposit` followed by a best-effort `deploy_idle`, so a keeper funding a vault doesn't
+    /// leave the deposited amount sitting idle for a whole extra transaction. The deploy leg
+    /// is skipped rather than erroring when `current_protocol` is unset, rebalances are
+    /// paused, or there's no excess above `buffer_bps` yet — unlike `deploy_idle` on its own,
+    /// none of those are failure conditions for a plain deposit.
+    pub fn deposit_and_deploy(
+        ctx: Context<DepositAndDeploy>,
+        amount: u64,
+        referrer: Option<Pubkey>,
+        lock_duration_secs: i64,
+    ) -> Result<()> {
+        require!(amount > 0, YieldPilotError::ZeroAmount);
+        require!(!ctx.accounts.state.deposits_paused, YieldPilotError::DepositsPaused);
+        require!(
+            !ctx.accounts.state.allowlist_enabled || ctx.accounts.allowlist_entry.is_some(),
+            YieldPilotError::NotAllowlisted
+        );
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+
+        let state = &mut ctx.accounts.state;
+        state.operation_in_progress = true;
+        if state.max_total_deposits > 0 {
+            let new_total_assets = state
+                .total_assets
+                .checked_add(amount)
+                .ok_or(YieldPilotError::ArithmeticOverflow)?;
+            require!(
+                new_total_assets <= state.max_total_deposits,
+                YieldPilotError::TotalDepositCapExceeded
+            );
+        }
+        if state.max_deposit_per_user > 0 {
+            let new_user_deposits = ctx
+                .accounts
+                .user_position
+                .cumulative_deposits
+                .checked_add(amount)
+                .ok_or(YieldPilotError::ArithmeticOverflow)?;
+            require!(
+                new_user_deposits <= state.max_deposit_per_user,
+                YieldPilotError::UserDepositCapExceeded
+            );
+        }
+
+        let balance_before = ctx.accounts.vault.amount;
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.depositor_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        ctx.accounts.vault.reload()?;
+        let credited = ctx
+            .accounts
+            .vault
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+
+        let state = &mut ctx.accounts.state;
+        accrue_reward_index(state)?;
+        let shares_minted = if state.total_shares == 0 {
+            credited
+        } else {
+            shares_for_amount(credited, state.total_shares, state.total_assets, state.decimals_offset)?
+        };
+
+        let state_authority = state.authority;
+        let vault_index_bytes = state.vault_index.to_le_bytes();
+        let vault_authority_seeds: &[&[u8]] = &[
+            b"vault_authority",
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.depositor_share_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[vault_authority_seeds],
+            ),
+            shares_minted,
+        )?;
+
+        state.total_shares = state
+            .total_shares
+            .checked_add(shares_minted)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        state.total_assets = state
+            .total_assets
+            .checked_add(credited)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        state.lifetime_deposits = state
+            .lifetime_deposits
+            .checked_add(credited)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        let user_position = &mut ctx.accounts.user_position;
+        user_position.version = CURRENT_STATE_VERSION;
+        settle_pending_rewards(state, user_position)?;
+        settle_accrued_yield(state, user_position)?;
+        user_position.shares = user_position
+            .shares
+            .checked_add(shares_minted)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        user_position.cumulative_deposits = user_position
+            .cumulative_deposits
+            .checked_add(credited)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        user_position.last_action_slot = Clock::get()?.slot;
+        user_position.last_deposit_slot = user_position.last_action_slot;
+        user_position.bump = ctx.bumps.user_position;
+        record_referrer(state, user_position, referrer);
+        apply_lock(user_position, lock_duration_secs)?;
+
+        emit_cpi!(Deposited {
+            state: ctx.accounts.state.key(),
+            depositor: ctx.accounts.depositor.key(),
+            amount: credited,
+            shares_minted,
+        });
+
+        let depositor = ctx.accounts.depositor.key();
+        let state = &mut ctx.accounts.state;
+        if !state.rebalances_paused
+            && state.current_protocol != 0
+            && (depositor == state.authority || state.is_updater(&depositor))
+        {
+            let vault_authority_info = ctx.accounts.vault_authority.to_account_info();
+            let excess = deploy_excess_idle(
+                state,
+                &vault_authority_info,
+                vault_authority_seeds,
+                ctx.remaining_accounts,
+            )?;
+            if excess > 0 {
+                emit_cpi!(IdleDeployed {
+                    state: ctx.accounts.state.key(),
+                    protocol: ctx.accounts.state.current_protocol,
+                    amount: excess,
+                });
+            }
+        }
+
+        ctx.accounts.state.operation_in_progress = false;
+
+        Ok(())
+    }
+
+    /// Institutional counterpart to `deposit`: the shares a normal deposit would mint
+    /// straight to `depositor` are escrowed instead, and a freshly minted single-supply
+    /// `receipt_mint` NFT stands in for the whole position. The NFT can then be transferred,
+    /// sold, or pledged as collateral as one unit rather than a fungible share balance;
+    /// `redeem_nft` unwinds it back into ordinary transferable shares for whoever holds it.
+    /// Skips `deposit`'s referral/lock-tier machinery — those attach to a `UserPosition`
+    /// this deposit path deliberately doesn't touch.
+    pub fn deposit_as_nft(ctx: Context<DepositAsNft>, amount: u64) -> Result<()> {
+        require!(amount > 0, YieldPilotError::ZeroAmount);
+        require!(!ctx.accounts.state.deposits_paused, YieldPilotError::DepositsPaused);
+        require!(
+            !ctx.accounts.state.allowlist_enabled || ctx.accounts.allowlist_entry.is_some(),
+            YieldPilotError::NotAllowlisted
+        );
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+        check_valuation_fresh(
+            ctx.accounts.strategy_info.as_deref(),
+            ctx.accounts.state.current_protocol,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let state = &mut ctx.accounts.state;
+        state.operation_in_progress = true;
+        if state.max_total_deposits > 0 {
+            let new_total_assets = state
+                .total_assets
+                .checked_add(amount)
+                .ok_or(YieldPilotError::ArithmeticOverflow)?;
+            require!(
+                new_total_assets <= state.max_total_deposits,
+                YieldPilotError::TotalDepositCapExceeded
+            );
+        }
+
+        let balance_before = ctx.accounts.vault.amount;
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.depositor_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        ctx.accounts.vault.reload()?;
+        let credited = ctx
+            .accounts
+            .vault
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+
+        let state = &mut ctx.accounts.state;
+        accrue_reward_index(state)?;
+        let shares_minted = if state.total_shares == 0 {
+            credited
+        } else {
+            shares_for_amount(credited, state.total_shares, state.total_assets, state.decimals_offset)?
+        };
+
+        let state_authority = state.authority;
+        let vault_index_bytes = state.vault_index.to_le_bytes();
+        let seeds = &[
+            b"vault_authority".as_ref(),
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.escrow_share_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            shares_minted,
+        )?;
+
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.receipt_mint.to_account_info(),
+                    to: ctx.accounts.depositor_nft_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            1,
+        )?;
+
+        state.total_shares = state
+            .total_shares
+            .checked_add(shares_minted)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        state.total_assets = state
+            .total_assets
+            .checked_add(credited)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        state.lifetime_deposits = state
+            .lifetime_deposits
+            .checked_add(credited)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        let id = state.next_nft_receipt_id;
+        state.next_nft_receipt_id = state
+            .next_nft_receipt_id
+            .checked_add(1)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        let receipt = &mut ctx.accounts.receipt;
+        receipt.version = CURRENT_STATE_VERSION;
+        receipt.state = ctx.accounts.state.key();
+        receipt.id = id;
+        receipt.owner = ctx.accounts.depositor.key();
+        receipt.receipt_mint = ctx.accounts.receipt_mint.key();
+        receipt.shares = shares_minted;
+        receipt.bump = ctx.bumps.receipt;
+
+        emit_cpi!(NftReceiptMinted {
+            state: ctx.accounts.state.key(),
+            depositor: ctx.accounts.depositor.key(),
+            receipt: receipt.key(),
+            receipt_mint: ctx.accounts.receipt_mint.key(),
+            amount: credited,
+            shares: shares_minted,
+        });
+
+        ctx.accounts.state.operation_in_progress = false;
+
+        Ok(())
+    }
+
+    /// Unwinds a `deposit_as_nft` position: burns the single `receipt_mint` token out of
+    /// `redeemer_nft_account`, releasing `receipt.shares` from escrow into `redeemer`'s own
+    /// share account as ordinary transferable (or soul-bound, per the vault's mint) shares.
+    /// Authorization is entirely via holding the NFT — `receipt.owner` is bookkeeping only,
+    /// so the position's current holder, not whoever originally deposited, redeems it.
+    pub fn redeem_nft(ctx: Context<RedeemNft>) -> Result<()> {
+        require!(
+            ctx.accounts.redeemer_nft_account.amount == 1,
+            YieldPilotError::NftReceiptNotHeld
+        );
+
+        let state = &ctx.accounts.state;
+        let state_authority = state.authority;
+        let vault_index_bytes = state.vault_index.to_le_bytes();
+        let seeds = &[
+            b"vault_authority".as_ref(),
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+
+        token_interface::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.receipt_mint.to_account_info(),
+                    from: ctx.accounts.redeemer_nft_account.to_account_info(),
+                    authority: ctx.accounts.redeemer.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+
+        let shares = ctx.accounts.receipt.shares;
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_share_account.to_account_info(),
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.redeemer_share_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            shares,
+            ctx.accounts.share_mint.decimals,
+        )?;
+
+        token_interface::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.escrow_share_account.to_account_info(),
+                destination: ctx.accounts.redeemer.to_account_info(),
+                authority: ctx.accounts.vault_authority.to_account_info(),
+            },
+            &[seeds],
+        ))?;
+
+        emit_cpi!(NftReceiptRedeemed {
+            state: ctx.accounts.state.key(),
+            redeemer: ctx.accounts.redeemer.key(),
+            receipt: ctx.accounts.receipt.key(),
+            receipt_mint: ctx.accounts.receipt_mint.key(),
+            shares,
+        });
+
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, shares: u64) -> Result<()> {
+        require!(shares > 0, YieldPilotError::ZeroAmount);
+        require!(
+            !ctx.accounts.state.withdrawals_paused,
+            YieldPilotError::WithdrawalsPaused
+        );
+        require!(
+            shares <= ctx.accounts.depositor_share_account.amount,
+            YieldPilotError::InsufficientShares
+        );
+        require!(
+            Clock::get()?.slot
+                >= ctx.accounts.user_position.last_deposit_slot
+                    + MIN_WITHDRAWAL_DELAY_SLOTS,
+            YieldPilotError::WithdrawalTooSoonAfterDeposit
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.user_position.locked_until_ts,
+            YieldPilotError::PositionLocked
+        );
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+        check_valuation_fresh(
+            ctx.accounts.strategy_info.as_deref(),
+            ctx.accounts.state.current_protocol,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let state = &mut ctx.accounts.state;
+        state.operation_in_progress = true;
+        accrue_reward_index(state)?;
+        let amount = amount_for_shares(shares, state.total_shares, state.total_assets, state.decimals_offset)?;
+
+        // Once this epoch's instant-payout budget is spent, further withdrawals queue via
+        // the same ticket `request_withdrawal` uses instead of failing outright — see
+        // `YieldState::max_withdrawal_bps_per_epoch`.
+        let queue_instead = state.max_withdrawal_bps_per_epoch > 0 && {
+            let epoch_cap = (state.total_assets as u128
+                * state.max_withdrawal_bps_per_epoch as u128
+                / MAX_WEIGHT_BPS as u128) as u64;
+            state.withdrawn_this_epoch.saturating_add(amount) > epoch_cap
+        };
+
+        token_interface::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    from: ctx.accounts.depositor_share_account.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            shares,
+        )?;
+
+        state.total_shares = state
+            .total_shares
+            .checked_sub(shares)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        state.total_assets = state
+            .total_assets
+            .checked_sub(amount)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+
+        let user_position = &mut ctx.accounts.user_position;
+        settle_pending_rewards(state, user_position)?;
+        settle_accrued_yield(state, user_position)?;
+        user_position.shares = user_position
+            .shares
+            .checked_sub(shares)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        user_position.last_action_slot = Clock::get()?.slot;
+
+        if queue_instead {
+            let sequence = state.next_withdrawal_sequence;
+            state.next_withdrawal_sequence = state
+                .next_withdrawal_sequence
+                .checked_add(1)
+                .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+            let ticket = ctx
+                .accounts
+                .ticket
+                .as_mut()
+                .ok_or(YieldPilotError::WithdrawalEpochCapExceeded)?;
+            ticket.version = CURRENT_STATE_VERSION;
+            ticket.owner = ctx.accounts.depositor.key();
+            ticket.shares = shares;
+            ticket.amount = amount;
+            ticket.sequence = sequence;
+            ticket.request_epoch = Clock::get()?.epoch;
+            ticket.ready = false;
+            ticket.claimed = false;
+            ticket.bump = ctx.bumps.ticket;
+
+            emit_cpi!(WithdrawalQueued {
+                state: ctx.accounts.state.key(),
+                depositor: ticket.owner,
+                sequence,
+                shares,
+                amount,
+            });
+        } else {
+            let state_authority = state.authority;
+            let vault_index_bytes = state.vault_index.to_le_bytes();
+            let seeds = &[
+                b"vault_authority".as_ref(),
+                state_authority.as_ref(),
+                &vault_index_bytes,
+                &[ctx.bumps.vault_authority],
+            ];
+
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.vault.to_account_info(),
+                        mint: ctx.accounts.mint.to_account_info(),
+                        to: ctx.accounts.depositor_token_account.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                amount,
+                ctx.accounts.mint.decimals,
+            )?;
+
+            state.lifetime_withdrawals = state
+                .lifetime_withdrawals
+                .checked_add(amount)
+                .ok_or(YieldPilotError::ArithmeticOverflow)?;
+            state.withdrawn_this_epoch = state
+                .withdrawn_this_epoch
+                .checked_add(amount)
+                .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+            let user_position = &mut ctx.accounts.user_position;
+            user_position.cumulative_withdrawals = user_position
+                .cumulative_withdrawals
+                .checked_add(amount)
+                .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+            emit_cpi!(Withdrawn {
+                state: ctx.accounts.state.key(),
+                depositor: ctx.accounts.depositor.key(),
+                amount,
+                shares_burned: shares,
+            });
+        }
+
+        ctx.accounts.state.operation_in_progress = false;
+
+        Ok(())
+    }
+
+    /// Converts `shares` of this vault's position into the successor vault `migrate_to`
+    /// linked, 1:1 in underlying value, without the depositor ever touching their own
+    /// wallet: shares are burned here, the matching underlying amount moves vault-to-vault
+    /// via a CPI signed by this vault's `vault_authority`, and the successor mints back
+    /// shares priced at its own exchange rate. Lets a depositor follow the vault into a new
+    /// version without the taxable-event/fee round-trip a withdraw-then-redeposit would
+    /// cost them.
+    ///
+    /// Requires `pending_rewards` to be claimed first — liquidity-mining rewards are
+    /// vault-specific, so there's nothing sensible to carry over, and silently forfeiting
+    /// them would be a quiet loss of value. `accrued_yield`/`referrer` do carry over: the
+    /// former is purely informational and the latter re-attributes the depositor's existing
+    /// referral in the new vault rather than losing it.
+    pub fn migrate_position(ctx: Context<MigratePosition>, shares: u64) -> Result<()> {
+        require!(shares > 0, YieldPilotError::ZeroAmount);
+        require!(
+            !ctx.accounts.state.withdrawals_paused,
+            YieldPilotError::WithdrawalsPaused
+        );
+        require!(
+            shares <= ctx.accounts.depositor_share_account.amount,
+            YieldPilotError::InsufficientShares
+        );
+        require!(
+            Clock::get()?.slot
+                >= ctx.accounts.user_position.last_deposit_slot
+                    + MIN_WITHDRAWAL_DELAY_SLOTS,
+            YieldPilotError::WithdrawalTooSoonAfterDeposit
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.user_position.locked_until_ts,
+            YieldPilotError::PositionLocked
+        );
+        require!(
+            ctx.accounts.user_position.pending_rewards == 0,
+            YieldPilotError::PendingRewardsMustBeClaimedFirst
+        );
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+        require!(
+            !ctx.accounts.successor_state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+        require!(
+            !ctx.accounts.successor_state.deposits_paused,
+            YieldPilotError::DepositsPaused
+        );
+
+        let state = &mut ctx.accounts.state;
+        state.operation_in_progress = true;
+        ctx.accounts.successor_state.operation_in_progress = true;
+        accrue_reward_index(state)?;
+        let amount = amount_for_shares(shares, state.total_shares, state.total_assets, state.decimals_offset)?;
+
+        token_interface::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    from: ctx.accounts.depositor_share_account.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            shares,
+        )?;
+
+        state.total_shares = state
+            .total_shares
+            .checked_sub(shares)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        state.total_assets = state
+            .total_assets
+            .checked_sub(amount)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        state.lifetime_withdrawals = state
+            .lifetime_withdrawals
+            .checked_add(amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        let user_position = &mut ctx.accounts.user_position;
+        settle_pending_rewards(state, user_position)?;
+        settle_accrued_yield(state, user_position)?;
+        user_position.shares = user_position
+            .shares
+            .checked_sub(shares)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        user_position.cumulative_withdrawals = user_position
+            .cumulative_withdrawals
+            .checked_add(amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        user_position.last_action_slot = Clock::get()?.slot;
+        let carried_referrer = user_position.referrer;
+        let carried_accrued_yield = user_position.accrued_yield;
+
+        let state_authority = state.authority;
+        let vault_index_bytes = state.vault_index.to_le_bytes();
+        let seeds = &[
+            b"vault_authority".as_ref(),
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.successor_vault.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+        state.operation_in_progress = false;
+
+        let successor_state = &mut ctx.accounts.successor_state;
+        accrue_reward_index(successor_state)?;
+        let shares_minted = if successor_state.total_shares == 0 {
+            amount
+        } else {
+            shares_for_amount(
+                amount,
+                successor_state.total_shares,
+                successor_state.total_assets,
+                successor_state.decimals_offset,
+            )?
+        };
+
+        let successor_authority = successor_state.authority;
+        let successor_vault_index_bytes = successor_state.vault_index.to_le_bytes();
+        let successor_seeds = &[
+            b"vault_authority".as_ref(),
+            successor_authority.as_ref(),
+            &successor_vault_index_bytes,
+            &[ctx.bumps.successor_vault_authority],
+        ];
+
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.successor_share_mint.to_account_info(),
+                    to: ctx.accounts.depositor_successor_share_account.to_account_info(),
+                    authority: ctx.accounts.successor_vault_authority.to_account_info(),
+                },
+                &[successor_seeds],
+            ),
+            shares_minted,
+        )?;
+
+        successor_state.total_shares = successor_state
+            .total_shares
+            .checked_add(shares_minted)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        successor_state.total_assets = successor_state
+            .total_assets
+            .checked_add(amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        successor_state.lifetime_deposits = successor_state
+            .lifetime_deposits
+            .checked_add(amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        let successor_position = &mut ctx.accounts.successor_user_position;
+        successor_position.version = CURRENT_STATE_VERSION;
+        settle_pending_rewards(successor_state, successor_position)?;
+        settle_accrued_yield(successor_state, successor_position)?;
+        successor_position.shares = successor_position
+            .shares
+            .checked_add(shares_minted)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        successor_position.cumulative_deposits = successor_position
+            .cumulative_deposits
+            .checked_add(amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        successor_position.last_action_slot = Clock::get()?.slot;
+        successor_position.last_deposit_slot = successor_position.last_action_slot;
+        successor_position.accrued_yield = successor_position
+            .accrued_yield
+            .checked_add(carried_accrued_yield)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        successor_position.bump = ctx.bumps.successor_user_position;
+        if successor_position.referrer == Pubkey::default() {
+            successor_position.referrer = carried_referrer;
+        }
+
+        successor_state.operation_in_progress = false;
+
+        emit_cpi!(PositionMigrated {
+            state: ctx.accounts.state.key(),
+            successor_state: successor_state.key(),
+            depositor: ctx.accounts.depositor.key(),
+            shares_burned: shares,
+            amount,
+            shares_minted,
+        });
+
+        Ok(())
+    }
+
+    /// Convenience wrapper around `deposit` for vaults whose asset is wrapped SOL: wraps the
+    /// caller's native lamports into the vault's wSOL token account with a direct lamport
+    /// transfer plus `sync_native`, then mints shares exactly as `deposit` does, so SOL
+    /// depositors don't need to run a separate wrap instruction first.
+    pub fn deposit_sol(
+        ctx: Context<DepositSol>,
+        amount: u64,
+        referrer: Option<Pubkey>,
+        lock_duration_secs: i64,
+    ) -> Result<()> {
+        require!(amount > 0, YieldPilotError::ZeroAmount);
+        require!(
+            ctx.accounts.state.mint == spl_token::native_mint::ID,
+            YieldPilotError::NotNativeMint
+        );
+        require!(!ctx.accounts.state.deposits_paused, YieldPilotError::DepositsPaused);
+        require!(
+            !ctx.accounts.state.allowlist_enabled || ctx.accounts.allowlist_entry.is_some(),
+            YieldPilotError::NotAllowlisted
+        );
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+
+        let state = &mut ctx.accounts.state;
+        state.operation_in_progress = true;
+        accrue_reward_index(state)?;
+        if state.max_total_deposits > 0 {
+            let new_total_assets = state
+                .total_assets
+                .checked_add(amount)
+                .ok_or(YieldPilotError::ArithmeticOverflow)?;
+            require!(
+                new_total_assets <= state.max_total_deposits,
+                YieldPilotError::TotalDepositCapExceeded
+            );
+        }
+        if state.max_deposit_per_user > 0 {
+            let new_user_deposits = ctx
+                .accounts
+                .user_position
+                .cumulative_deposits
+                .checked_add(amount)
+                .ok_or(YieldPilotError::ArithmeticOverflow)?;
+            require!(
+                new_user_deposits <= state.max_deposit_per_user,
+                YieldPilotError::UserDepositCapExceeded
+            );
+        }
+
+        let shares_minted = if state.total_shares == 0 {
+            amount
+        } else {
+            shares_for_amount(amount, state.total_shares, state.total_assets, state.decimals_offset)?
+        };
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.depositor.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        token_interface::sync_native(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            SyncNative {
+                account: ctx.accounts.vault.to_account_info(),
+            },
+        ))?;
+
+        let state_authority = state.authority;
+        let vault_index_bytes = state.vault_index.to_le_bytes();
+        let seeds = &[
+            b"vault_authority".as_ref(),
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.depositor_share_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            shares_minted,
+        )?;
+
+        state.total_shares = state
+            .total_shares
+            .checked_add(shares_minted)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        state.total_assets = state
+            .total_assets
+            .checked_add(amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        state.lifetime_deposits = state
+            .lifetime_deposits
+            .checked_add(amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        let user_position = &mut ctx.accounts.user_position;
+        user_position.version = CURRENT_STATE_VERSION;
+        settle_pending_rewards(state, user_position)?;
+        settle_accrued_yield(state, user_position)?;
+        user_position.shares = user_position
+            .shares
+            .checked_add(shares_minted)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        user_position.cumulative_deposits = user_position
+            .cumulative_deposits
+            .checked_add(amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        user_position.last_action_slot = Clock::get()?.slot;
+        user_position.last_deposit_slot = user_position.last_action_slot;
+        user_position.bump = ctx.bumps.user_position;
+        record_referrer(state, user_position, referrer);
+        apply_lock(user_position, lock_duration_secs)?;
+
+        emit_cpi!(Deposited {
+            state: ctx.accounts.state.key(),
+            depositor: ctx.accounts.depositor.key(),
+            amount,
+            shares_minted,
+        });
+
+        state.operation_in_progress = false;
+
+        Ok(())
+    }
+
+    /// Convenience wrapper around `withdraw` for vaults whose asset is wrapped SOL: redeems
+    /// shares into a temporary wSOL token account exactly as `withdraw` does, then unwraps it
+    /// by closing the account so the depositor receives native SOL instead of wSOL tokens.
+    pub fn withdraw_sol(ctx: Context<WithdrawSol>, shares: u64) -> Result<()> {
+        require!(shares > 0, YieldPilotError::ZeroAmount);
+        require!(
+            ctx.accounts.state.mint == spl_token::native_mint::ID,
+            YieldPilotError::NotNativeMint
+        );
+        require!(
+            !ctx.accounts.state.withdrawals_paused,
+            YieldPilotError::WithdrawalsPaused
+        );
+        require!(
+            shares <= ctx.accounts.depositor_share_account.amount,
+            YieldPilotError::InsufficientShares
+        );
+        require!(
+            Clock::get()?.slot
+                >= ctx.accounts.user_position.last_deposit_slot
+                    + MIN_WITHDRAWAL_DELAY_SLOTS,
+            YieldPilotError::WithdrawalTooSoonAfterDeposit
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.user_position.locked_until_ts,
+            YieldPilotError::PositionLocked
+        );
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+
+        let state = &mut ctx.accounts.state;
+        state.operation_in_progress = true;
+        accrue_reward_index(state)?;
+        let amount = amount_for_shares(shares, state.total_shares, state.total_assets, state.decimals_offset)?;
+
+        let state_authority = state.authority;
+        let vault_index_bytes = state.vault_index.to_le_bytes();
+        let seeds = &[
+            b"vault_authority".as_ref(),
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+
+        token_interface::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    from: ctx.accounts.depositor_share_account.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            shares,
+        )?;
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.temp_wsol_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        token_interface::close_account(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.temp_wsol_account.to_account_info(),
+                destination: ctx.accounts.depositor.to_account_info(),
+                authority: ctx.accounts.depositor.to_account_info(),
+            },
+        ))?;
+
+        state.total_shares = state
+            .total_shares
+            .checked_sub(shares)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        state.total_assets = state
+            .total_assets
+            .checked_sub(amount)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        state.lifetime_withdrawals = state
+            .lifetime_withdrawals
+            .checked_add(amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        let user_position = &mut ctx.accounts.user_position;
+        settle_pending_rewards(state, user_position)?;
+        settle_accrued_yield(state, user_position)?;
+        user_position.shares = user_position
+            .shares
+            .checked_sub(shares)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        user_position.cumulative_withdrawals = user_position
+            .cumulative_withdrawals
+            .checked_add(amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        user_position.last_action_slot = Clock::get()?.slot;
+
+        emit_cpi!(Withdrawn {
+            state: ctx.accounts.state.key(),
+            depositor: ctx.accounts.depositor.key(),
+            amount,
+            shares_burned: shares,
+        });
+
+        state.operation_in_progress = false;
+
+        Ok(())
+    }
+
+    /// Serves a withdrawal immediately from the vault's idle balance for an exit fee, as an
+    /// alternative to `request_withdrawal`/`claim_withdrawal` for depositors who don't want
+    /// to wait on an unwind cycle. The fee is left in the vault instead of paid out, so it
+    /// accrues to remaining depositors via a higher share price rather than a fee recipient.
+    pub fn withdraw_instant(ctx: Context<Withdraw>, shares: u64) -> Result<()> {
+        require!(shares > 0, YieldPilotError::ZeroAmount);
+        require!(
+            !ctx.accounts.state.withdrawals_paused,
+            YieldPilotError::WithdrawalsPaused
+        );
+        require!(
+            shares <= ctx.accounts.depositor_share_account.amount,
+            YieldPilotError::InsufficientShares
+        );
+        require!(
+            Clock::get()?.slot
+                >= ctx.accounts.user_position.last_deposit_slot
+                    + MIN_WITHDRAWAL_DELAY_SLOTS,
+            YieldPilotError::WithdrawalTooSoonAfterDeposit
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.user_position.locked_until_ts,
+            YieldPilotError::PositionLocked
+        );
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+
+        let state = &mut ctx.accounts.state;
+        state.operation_in_progress = true;
+        accrue_reward_index(state)?;
+        let gross_amount = amount_for_shares(shares, state.total_shares, state.total_assets, state.decimals_offset)?;
+        let fee = ((gross_amount as u128 * state.instant_withdrawal_fee_bps as u128) / 10_000) as u64;
+        let net_amount = gross_amount
+            .checked_sub(fee)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+
+        let state_authority = state.authority;
+        let vault_index_bytes = state.vault_index.to_le_bytes();
+        let seeds = &[
+            b"vault_authority".as_ref(),
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+
+        token_interface::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    from: ctx.accounts.depositor_share_account.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            shares,
+        )?;
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.depositor_token_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            net_amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        state.total_shares = state
+            .total_shares
+            .checked_sub(shares)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        state.total_assets = state
+            .total_assets
+            .checked_sub(net_amount)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        state.lifetime_withdrawals = state
+            .lifetime_withdrawals
+            .checked_add(net_amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        let user_position = &mut ctx.accounts.user_position;
+        settle_pending_rewards(state, user_position)?;
+        settle_accrued_yield(state, user_position)?;
+        user_position.shares = user_position
+            .shares
+            .checked_sub(shares)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        user_position.cumulative_withdrawals = user_position
+            .cumulative_withdrawals
+            .checked_add(net_amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        user_position.last_action_slot = Clock::get()?.slot;
+
+        emit_cpi!(InstantWithdrawn {
+            state: ctx.accounts.state.key(),
+            depositor: ctx.accounts.depositor.key(),
+            amount: net_amount,
+            fee,
+            shares_burned: shares,
+        });
+
+        state.operation_in_progress = false;
+
+        Ok(())
+    }
+
+    /// Queues a withdrawal for a depositor whose shares can't be redeemed instantly because
+    /// the corresponding funds are still deployed in a strategy with an unstaking delay.
+    /// Locks in the share price and burns the shares immediately, so remaining depositors
+    /// aren't diluted while the ticket waits to be unwound and processed.
+    pub fn request_withdrawal(ctx: Context<RequestWithdrawal>, shares: u64) -> Result<()> {
+        require!(shares > 0, YieldPilotError::ZeroAmount);
+        require!(
+            !ctx.accounts.state.withdrawals_paused,
+            YieldPilotError::WithdrawalsPaused
+        );
+        require!(
+            shares <= ctx.accounts.depositor_share_account.amount,
+            YieldPilotError::InsufficientShares
+        );
+        require!(
+            Clock::get()?.slot
+                >= ctx.accounts.user_position.last_deposit_slot
+                    + MIN_WITHDRAWAL_DELAY_SLOTS,
+            YieldPilotError::WithdrawalTooSoonAfterDeposit
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.user_position.locked_until_ts,
+            YieldPilotError::PositionLocked
+        );
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+
+        let state = &mut ctx.accounts.state;
+        state.operation_in_progress = true;
+        accrue_reward_index(state)?;
+        let amount = amount_for_shares(shares, state.total_shares, state.total_assets, state.decimals_offset)?;
+
+        token_interface::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    from: ctx.accounts.depositor_share_account.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            shares,
+        )?;
+
+        state.total_shares = state
+            .total_shares
+            .checked_sub(shares)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        state.total_assets = state
+            .total_assets
+            .checked_sub(amount)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+
+        let sequence = state.next_withdrawal_sequence;
+        state.next_withdrawal_sequence = state
+            .next_withdrawal_sequence
+            .checked_add(1)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        let ticket = &mut ctx.accounts.ticket;
+        ticket.version = CURRENT_STATE_VERSION;
+        ticket.owner = ctx.accounts.depositor.key();
+        ticket.shares = shares;
+        ticket.amount = amount;
+        ticket.sequence = sequence;
+        ticket.request_epoch = Clock::get()?.epoch;
+        ticket.ready = false;
+        ticket.claimed = false;
+        ticket.bump = ctx.bumps.ticket;
+
+        let user_position = &mut ctx.accounts.user_position;
+        settle_pending_rewards(state, user_position)?;
+        settle_accrued_yield(state, user_position)?;
+        user_position.shares = user_position
+            .shares
+            .checked_sub(shares)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        user_position.last_action_slot = Clock::get()?.slot;
+
+        emit_cpi!(WithdrawalQueued {
+            state: ctx.accounts.state.key(),
+            depositor: ticket.owner,
+            sequence,
+            shares,
+            amount,
+        });
+
+        state.operation_in_progress = false;
+
+        Ok(())
+    }
+
+    /// Operator-gated crank that advances the withdrawal queue: marks the ticket at the
+    /// front of the queue as payable once its underlying position has actually been
+    /// unwound. Must be processed strictly in order so no depositor's exit is skipped.
+    pub fn process_withdrawal_queue(
+        ctx: Context<ProcessWithdrawalQueue>,
+        _owner: Pubkey,
+        _sequence: u64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        let operator = ctx.accounts.operator.key();
+        require!(
+            operator == state.authority || state.is_updater(&operator),
+            YieldPilotError::Unauthorized
+        );
+
+        let ticket = &mut ctx.accounts.ticket;
+        require!(
+            ticket.sequence == state.withdrawal_queue_head,
+            YieldPilotError::OutOfOrderWithdrawal
+        );
+
+        ticket.ready = true;
+        state.withdrawal_queue_head = state
+            .withdrawal_queue_head
+            .checked_add(1)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        emit_cpi!(WithdrawalReady {
+            state: ctx.accounts.state.key(),
+            sequence: ticket.sequence,
+        });
+
+        Ok(())
+    }
+
+    /// Pays out a ticket once `process_withdrawal_queue` has marked it ready.
+    pub fn claim_withdrawal(
+        ctx: Context<ClaimWithdrawal>,
+        _owner: Pubkey,
+        _sequence: u64,
+    ) -> Result<()> {
+        let ticket = &mut ctx.accounts.ticket;
+        require!(ticket.ready, YieldPilotError::WithdrawalNotReady);
+        require!(!ticket.claimed, YieldPilotError::WithdrawalAlreadyClaimed);
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+        ctx.accounts.state.operation_in_progress = true;
+
+        let state_authority = ctx.accounts.state.authority;
+        let vault_index_bytes = ctx.accounts.state.vault_index.to_le_bytes();
+        let seeds = &[
+            b"vault_authority".as_ref(),
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.depositor_token_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            ticket.amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        ticket.claimed = true;
+
+        ctx.accounts.state.lifetime_withdrawals = ctx
+            .accounts
+            .state
+            .lifetime_withdrawals
+            .checked_add(ticket.amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        let user_position = &mut ctx.accounts.user_position;
+        user_position.cumulative_withdrawals = user_position
+            .cumulative_withdrawals
+            .checked_add(ticket.amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        user_position.last_action_slot = Clock::get()?.slot;
+
+        emit_cpi!(WithdrawalClaimed {
+            state: ctx.accounts.state.key(),
+            depositor: ticket.owner,
+            sequence: ticket.sequence,
+            amount: ticket.amount,
+        });
+
+        ctx.accounts.state.operation_in_progress = false;
+
+        Ok(())
+    }
+
+    /// `claim_withdrawal`, but first pulls any shortfall straight out of
+    /// `state.current_protocol` instead of requiring the vault to already be sitting on
+    /// enough idle balance. `process_withdrawal_queue` only orders the queue; nothing else
+    /// unwinds a strategy position to fund a specific ticket, so without this a ready ticket
+    /// could stall behind a `deploy_idle`/`rebalance` that never comes.
+    pub fn withdraw_from_strategy_and_claim(
+        ctx: Context<WithdrawFromStrategyAndClaim>,
+        _owner: Pubkey,
+        _sequence: u64,
+    ) -> Result<()> {
+        let ticket = &mut ctx.accounts.ticket;
+        require!(ticket.ready, YieldPilotError::WithdrawalNotReady);
+        require!(!ticket.claimed, YieldPilotError::WithdrawalAlreadyClaimed);
+        require!(
+            !ctx.accounts.state.operation_in_progress,
+            YieldPilotError::ReentrancyDetected
+        );
+        ctx.accounts.state.operation_in_progress = true;
+
+        let state_authority = ctx.accounts.state.authority;
+        let vault_index_bytes = ctx.accounts.state.vault_index.to_le_bytes();
+        let seeds = &[
+            b"vault_authority".as_ref(),
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+        let vault_authority_info = ctx.accounts.vault_authority.to_account_info();
+
+        let ticket_amount = ctx.accounts.ticket.amount;
+        let shortfall = ticket_amount.saturating_sub(ctx.accounts.vault.amount);
+        if shortfall > 0 {
+            let state = &mut ctx.accounts.state;
+            require!(state.current_protocol != 0, YieldPilotError::UnknownStrategy);
+            let vault_balance_before = ctx.accounts.vault.amount;
+
+            invoke_adapter(
+                "withdraw",
+                shortfall,
+                ctx.remaining_accounts,
+                &vault_authority_info,
+                seeds,
+                state,
+            )?;
+
+            ctx.accounts.vault.reload()?;
+            let received = ctx
+                .accounts
+                .vault
+                .amount
+                .checked_sub(vault_balance_before)
+                .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+            require!(received >= shortfall, YieldPilotError::SlippageExceeded);
+            ctx.accounts.state.deployed_amount = ctx
+                .accounts
+                .state
+                .deployed_amount
+                .checked_sub(shortfall)
+                .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        }
+
+        let ticket = &mut ctx.accounts.ticket;
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.depositor_token_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            ticket.amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        ticket.claimed = true;
+
+        ctx.accounts.state.lifetime_withdrawals = ctx
+            .accounts
+            .state
+            .lifetime_withdrawals
+            .checked_add(ticket.amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+        let user_position = &mut ctx.accounts.user_position;
+        user_position.cumulative_withdrawals = user_position
+            .cumulative_withdrawals
+            .checked_add(ticket.amount)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        user_position.last_action_slot = Clock::get()?.slot;
+
+        emit_cpi!(WithdrawalClaimed {
+            state: ctx.accounts.state.key(),
+            depositor: ticket.owner,
+            sequence: ticket.sequence,
+            amount: ticket.amount,
+        });
+
+        ctx.accounts.state.operation_in_progress = false;
+
+        Ok(())
+    }
+
+    /// Closes a fully-withdrawn `UserPosition` and returns its rent to the depositor, so
+    /// someone who has exited the vault entirely isn't left paying rent on an account
+    /// `deposit` would just reinitialize via `init_if_needed` on their next entry.
+    pub fn close_position(_ctx: Context<ClosePosition>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Closes a claimed `WithdrawalTicket` and returns its rent to the owner, once
+    /// `claim_withdrawal` has already paid it out and there's nothing left to track.
+    pub fn close_withdrawal_ticket(_ctx: Context<CloseWithdrawalTicket>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Closes out the current accounting epoch: snapshots the share price, advances the
+    /// epoch counter, and emits a summary event. Requires `collect_fees` to have already
+    /// crystallized this epoch's performance fee and the withdrawal queue to be fully
+    /// drained, so the crank is expected to run `collect_fees` and any outstanding
+    /// `process_withdrawal_queue` calls earlier in the same transaction before this one.
+    pub fn roll_epoch(ctx: Context<RollEpoch>) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == state.authority || state.is_updater(&signer),
+            YieldPilotError::Unauthorized
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            state.epoch_length_secs == 0 || now >= state.epoch_started_at + state.epoch_length_secs,
+            YieldPilotError::EpochNotElapsed
+        );
+        require!(
+            state.last_fee_collection_ts >= state.epoch_started_at,
+            YieldPilotError::FeesNotCrystallizedThisEpoch
+        );
+        require!(
+            state.withdrawal_queue_head == state.next_withdrawal_sequence,
+            YieldPilotError::WithdrawalQueueNotDrained
+        );
+        require!(
+            bitmap_is_subset(
+                &state.registered_protocols_bitmap,
+                &state.valuations_refreshed_bitmap
+            ),
+            YieldPilotError::ValuationsNotRefreshedThisEpoch
+        );
+
+        let share_price = current_share_price(state.total_assets, state.total_shares);
+
+        state.last_epoch_share_price = share_price;
+        state.epoch_started_at = now;
+        state.current_epoch = state
+            .current_epoch
+            .checked_add(1)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?;
+        state.valuations_refreshed_bitmap = [0; 4];
+        state.withdrawn_this_epoch = 0;
+
+        emit_cpi!(EpochRolled {
+            state: ctx.accounts.state.key(),
+            epoch: state.current_epoch,
+            share_price,
+            total_assets: state.total_assets,
+            total_shares: state.total_shares,
+        });
+
+        Ok(())
+    }
+
+    /// Posts a compact `(share_price, total_assets, apy_bps, timestamp)` message to
+    /// `state.wormhole_program`'s Core Bridge via CPI, so an EVM frontend can read the
+    /// vault's most recently rolled epoch trustlessly instead of taking an off-chain
+    /// relayer's word for it. Permissionless like `refresh_valuation`/`crank_rebalance` —
+    /// the payload comes straight off `state`'s own fields, so there's nothing for an
+    /// untrusted caller to forge, only to pay the Wormhole message fee and rent for.
+    pub fn publish_state(ctx: Context<PublishState>, nonce: u32) -> Result<()> {
+        let state = &ctx.accounts.state;
+        require!(
+            state.wormhole_program != Pubkey::default(),
+            YieldPilotError::WormholeProgramNotConfigured
+        );
+        require_keys_eq!(
+            ctx.accounts.wormhole_program.key(),
+            state.wormhole_program,
+            YieldPilotError::WormholeProgramNotConfigured
+        );
+
+        let mut payload = Vec::with_capacity(8 + 8 + 2 + 8);
+        payload.extend_from_slice(&state.last_epoch_share_price.to_be_bytes());
+        payload.extend_from_slice(&state.total_assets.to_be_bytes());
+        payload.extend_from_slice(&state.current_apy_bps.to_be_bytes());
+        payload.extend_from_slice(&(state.epoch_started_at as u64).to_be_bytes());
+
+        let state_authority = state.authority;
+        let vault_index_bytes = state.vault_index.to_le_bytes();
+        let vault_authority_seeds: &[&[u8]] = &[
+            b"vault_authority",
+            state_authority.as_ref(),
+            &vault_index_bytes,
+            &[ctx.bumps.vault_authority],
+        ];
+        let vault_authority_info = ctx.accounts.vault_authority.to_account_info();
+
+        let ix = Instruction {
+            program_id: ctx.accounts.wormhole_program.key(),
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.wormhole_bridge.key(), false),
+                AccountMeta::new(ctx.accounts.wormhole_message.key(), true),
+                AccountMeta::new_readonly(vault_authority_info.key(), true),
+                AccountMeta::new(ctx.accounts.wormhole_sequence.key(), false),
+                AccountMeta::new(ctx.accounts.payer.key(), true),
+                AccountMeta::new(ctx.accounts.wormhole_fee_collector.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.clock.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.rent.key(), false),
+                AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+            ],
+            data: post_message_instruction_data(nonce, &payload),
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.wormhole_bridge.to_account_info(),
+                ctx.accounts.wormhole_message.to_account_info(),
+                vault_authority_info.clone(),
+                ctx.accounts.wormhole_sequence.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.wormhole_fee_collector.to_account_info(),
+                ctx.accounts.clock.to_account_info(),
+                ctx.accounts.rent.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &[vault_authority_seeds],
+        )?;
+
+        emit_cpi!(StatePublished {
+            state: ctx.accounts.state.key(),
+            share_price: state.last_epoch_share_price,
+            total_assets: state.total_assets,
+            apy_bps: state.current_apy_bps,
+            timestamp: state.epoch_started_at,
+        });
+
+        Ok(())
+    }
+
+    /// Records `owner`'s current share balance into a `Snapshot` PDA keyed by
+    /// `(epoch, owner)`, so the balance can be proven on-chain later for an off-chain
+    /// airdrop or governance weighting pass. Only callable for the vault's
+    /// `current_epoch`, and only within `SNAPSHOT_WINDOW_SECS` of that epoch starting —
+    /// a snapshot can't be backdated once the window has closed, nor taken early for an
+    /// epoch that hasn't started counting yet. Permissionless: anyone (e.g. an airdrop
+    /// keeper) can snapshot any depositor's position, since the balance being recorded is
+    /// already public.
+    pub fn take_snapshot(ctx: Context<TakeSnapshot>, epoch: u64, owner: Pubkey) -> Result<()> {
+        require!(
+            epoch == ctx.accounts.state.current_epoch,
+            YieldPilotError::InvalidSnapshotEpoch
+        );
+        require!(
+            Clock::get()?.unix_timestamp - ctx.accounts.state.epoch_started_at <= SNAPSHOT_WINDOW_SECS,
+            YieldPilotError::SnapshotWindowClosed
+        );
+
+        let snapshot = &mut ctx.accounts.snapshot;
+        snapshot.version = CURRENT_STATE_VERSION;
+        snapshot.epoch = epoch;
+        snapshot.owner = owner;
+        snapshot.shares = ctx.accounts.user_position.shares;
+        snapshot.bump = ctx.bumps.snapshot;
+
+        Ok(())
+    }
+
+    /// Recomputes `VaultHealth` from `YieldState` and `current_protocol`'s `StrategyInfo`
+    /// (if registered). Permissionless and cheap enough to crank on a timer — monitoring can
+    /// then alert off this one account instead of fetching and cross-referencing several.
+    pub fn update_health(ctx: Context<UpdateHealth>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let state = &ctx.accounts.state;
+
+        let (valuation_stale, apy_stale, allocation_drift_bps) = match ctx.accounts.strategy_info.as_ref() {
+            Some(strategy_info) => {
+                let valuation_stale = strategy_info.max_valuation_staleness_secs > 0
+                    && now.saturating_sub(strategy_info.last_valued_at) > strategy_info.max_valuation_staleness_secs;
+                let apy_stale = strategy_info.max_staleness_secs > 0
+                    && now.saturating_sub(strategy_info.last_apy_update_ts) > strategy_info.max_staleness_secs;
+                let deployed_weight_bps = if state.total_assets > 0 {
+                    ((state.deployed_amount as u128 * MAX_WEIGHT_BPS as u128) / state.total_assets as u128) as i32
+                } else {
+                    0
+                };
+                let drift = deployed_weight_bps - strategy_info.target_weight_bps as i32;
+                (valuation_stale, apy_stale, drift)
+            }
+            None => (false, false, 0),
+        };
+
+        let buffer_ratio_bps = if state.total_assets > 0 {
+            let idle = state.total_assets.saturating_sub(state.deployed_amount);
+            ((idle as u128 * MAX_WEIGHT_BPS as u128) / state.total_assets as u128) as u16
+        } else {
+            0
+        };
+
+        let secs_since_harvest = if state.last_harvest_ts == 0 {
+            i64::MAX
+        } else {
+            now.saturating_sub(state.last_harvest_ts)
+        };
+
+        let state_key = ctx.accounts.state.key();
+        let slot = Clock::get()?.slot;
+        let health = &mut ctx.accounts.health;
+        health.version = CURRENT_STATE_VERSION;
+        health.state = state_key;
+        health.updated_at = now;
+        health.updated_slot = slot;
+        health.valuation_stale = valuation_stale;
+        health.apy_stale = apy_stale;
+        health.buffer_ratio_bps = buffer_ratio_bps;
+        health.allocation_drift_bps = allocation_drift_bps;
+        health.secs_since_harvest = secs_since_harvest;
+        health.bump = ctx.bumps.health;
+
+        Ok(())
+    }
+
+    /// Closes a disabled, zero-TVL `StrategyInfo` and returns its rent to the authority,
+    /// so the DAO can retire a protocol integration it's done with instead of paying rent
+    /// on it forever.
+    pub fn close_strategy(ctx: Context<CloseStrategy>) -> Result<()> {
+        let id = ctx.accounts.strategy_info.id;
+        bitmap_clear(&mut ctx.accounts.state.registered_protocols_bitmap, id);
+        bitmap_clear(&mut ctx.accounts.state.valuations_refreshed_bitmap, id);
+
+        Ok(())
+    }
+
+    pub fn add_updater(ctx: Context<ManageUpdaters>, updater: Pubkey) -> Result<()> {
+        ctx.accounts.state.add_updater(updater)?;
+        if let Some(audit_log) = ctx.accounts.audit_log.as_ref() {
+            let mut params = [0u8; 32];
+            params.copy_from_slice(updater.as_ref());
+            audit_log.load_mut()?.record(
+                AUDIT_ACTION_ADD_UPDATER,
+                ctx.accounts.authority.key(),
+                Clock::get()?.slot,
+                params,
+            );
+        }
+        Ok(())
+    }
+
+    pub fn remove_updater(ctx: Context<ManageUpdaters>, updater: Pubkey) -> Result<()> {
+        ctx.accounts.state.remove_updater(updater)?;
+        if let Some(audit_log) = ctx.accounts.audit_log.as_ref() {
+            let mut params = [0u8; 32];
+            params.copy_from_slice(updater.as_ref());
+            audit_log.load_mut()?.record(
+                AUDIT_ACTION_REMOVE_UPDATER,
+                ctx.accounts.authority.key(),
+                Clock::get()?.slot,
+                params,
+            );
+        }
+        Ok(())
+    }
+
+    /// Authorizes `adapter_program` as a CPI target for `invoke_adapter`/`invoke_adapter_value`.
+    /// A `StrategyInfo.adapter_program` entry doesn't grant this on its own — it's client data
+    /// reconstructed from `remaining_accounts[0]` on every call, so without this allowlist an
+    /// attacker could substitute a lookalike program for the real lending protocol.
+    pub fn add_allowed_adapter_program(ctx: Context<ManageUpdaters>, adapter_program: Pubkey) -> Result<()> {
+        ctx.accounts.state.add_allowed_adapter_program(adapter_program)
+    }
+
+    pub fn remove_allowed_adapter_program(ctx: Context<ManageUpdaters>, adapter_program: Pubkey) -> Result<()> {
+        ctx.accounts.state.remove_allowed_adapter_program(adapter_program)
+    }
+
+    /// Creates the vault's `AuditLog` ring buffer. One-time setup, same as `create_vault`
+    /// creating `history` — see `AuditLog` for why it's a separate opt-in account instead
+    /// of a `YieldState` field.
+    pub fn create_audit_log(ctx: Context<CreateAuditLog>) -> Result<()> {
+        let mut audit_log = ctx.accounts.audit_log.load_init()?;
+        audit_log.version = CURRENT_STATE_VERSION;
+        audit_log.bump = ctx.bumps.audit_log;
+
+        Ok(())
+    }
+
+    /// Registers `operator` with individual daily caps on `update_yield` frequency and
+    /// `rebalance` volume, tracked in its own `OperatorLimits` PDA. Separate from
+    /// `state.updaters`/`add_updater`, which only gates *whether* a key can act at all —
+    /// this bounds *how much* damage one compromised bot key can do per day without the
+    /// authority needing to revoke it outright.
+    pub fn register_operator(
+        ctx: Context<RegisterOperator>,
+        operator: Pubkey,
+        max_apy_updates_per_day: u16,
+        max_rebalance_volume_per_day: u64,
+    ) -> Result<()> {
+        let limits = &mut ctx.accounts.operator_limits;
+        limits.version = CURRENT_STATE_VERSION;
+        limits.operator = operator;
+        limits.max_apy_updates_per_day = max_apy_updates_per_day;
+        limits.max_rebalance_volume_per_day = max_rebalance_volume_per_day;
+        limits.window_started_at = Clock::get()?.unix_timestamp;
+        limits.apy_updates_in_window = 0;
+        limits.rebalance_volume_in_window = 0;
+        limits.bump = ctx.bumps.operator_limits;
+
+        if let Some(audit_log) = ctx.accounts.audit_log.as_ref() {
+            let mut params = [0u8; 32];
+            params.copy_from_slice(operator.as_ref());
+            audit_log.load_mut()?.record(
+                AUDIT_ACTION_REGISTER_OPERATOR,
+                ctx.accounts.authority.key(),
+                Clock::get()?.slot,
+                params,
+            );
+        }
+
+        emit_cpi!(OperatorLimitsUpdated {
+            state: ctx.accounts.state.key(),
+            operator,
+            max_apy_updates_per_day,
+            max_rebalance_volume_per_day,
+            active: true,
+        });
+
+        Ok(())
+    }
+
+    /// Updates an already-registered operator's caps in place, rather than closing and
+    /// re-creating its `OperatorLimits` PDA, which would otherwise reset the window
+    /// currently in progress.
+    pub fn set_operator_limits(
+        ctx: Context<SetOperatorLimits>,
+        max_apy_updates_per_day: u16,
+        max_rebalance_volume_per_day: u64,
+    ) -> Result<()> {
+        let limits = &mut ctx.accounts.operator_limits;
+        limits.max_apy_updates_per_day = max_apy_updates_per_day;
+        limits.max_rebalance_volume_per_day = max_rebalance_volume_per_day;
+        let operator = limits.operator;
+
+        if let Some(audit_log) = ctx.accounts.audit_log.as_ref() {
+            let mut params = [0u8; 32];
+            params.copy_from_slice(operator.as_ref());
+            audit_log.load_mut()?.record(
+                AUDIT_ACTION_SET_OPERATOR_LIMITS,
+                ctx.accounts.authority.key(),
+                Clock::get()?.slot,
+                params,
+            );
+        }
+
+        emit_cpi!(OperatorLimitsUpdated {
+            state: ctx.accounts.state.key(),
+            operator,
+            max_apy_updates_per_day,
+            max_rebalance_volume_per_day,
+            active: true,
+        });
+
+        Ok(())
+    }
+
+    /// Closes `operator`'s `OperatorLimits` PDA. `update_yield`/`rebalance` already treat a
+    /// missing `OperatorLimits` account as unrestricted, same as an operator that was never
+    /// registered to begin with.
+    pub fn deregister_operator(ctx: Context<DeregisterOperator>) -> Result<()> {
+        let operator = ctx.accounts.operator_limits.operator;
+
+        if let Some(audit_log) = ctx.accounts.audit_log.as_ref() {
+            let mut params = [0u8; 32];
+            params.copy_from_slice(operator.as_ref());
+            audit_log.load_mut()?.record(
+                AUDIT_ACTION_DEREGISTER_OPERATOR,
+                ctx.accounts.authority.key(),
+                Clock::get()?.slot,
+                params,
+            );
+        }
+
+        emit_cpi!(OperatorLimitsUpdated {
+            state: ctx.accounts.state.key(),
+            operator,
+            max_apy_updates_per_day: 0,
+            max_rebalance_volume_per_day: 0,
+            active: false,
+        });
+
+        Ok(())
+    }
+
+    pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        state.pending_authority = Some(new_authority);
+
+        Ok(())
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+
+        require_keys_eq!(
+            state.pending_authority.ok_or(YieldPilotError::PendingAuthorityMismatch)?,
+            ctx.accounts.pending_authority.key(),
+            YieldPilotError::PendingAuthorityMismatch
+        );
+
+        state.authority = ctx.accounts.pending_authority.key();
+        state.pending_authority = None;
+
+        Ok(())
+    }
+
+    /// Reallocs `YieldState` to make room for whatever `CURRENT_STATE_VERSION` expects and
+    /// bumps its stored version, so an existing vault can pick up a future schema change
+    /// without depositors ever needing to withdraw and re-enter a freshly deployed one.
+    pub fn migrate_state(ctx: Context<MigrateState>) -> Result<()> {
+        require!(
+            ctx.accounts.state.version < CURRENT_STATE_VERSION,
+            YieldPilotError::AlreadyOnLatestVersion
+        );
+
+        ctx.accounts.state.version = CURRENT_STATE_VERSION;
+
+        Ok(())
+    }
+
+    /// Returns the recorded APY history in chronological order (oldest first).
+    pub fn get_history(ctx: Context<ReadYieldState>) -> Result<Vec<YieldSnapshot>> {
+        Ok(ctx.accounts.history.load()?.history_chronological())
+    }
+
+    /// Quotes the shares `deposit`/`deposit_sol` would mint for `amount`, as of whatever
+    /// `collect_fees` would crystallize if it ran first. Pure view: takes no lock and
+    /// changes no state, so frontends can simulate an exact quote instead of
+    /// reimplementing `shares_for_amount` (and the fee dilution ahead of it) client-side.
+    pub fn preview_deposit(ctx: Context<ReadYieldState>, amount: u64) -> Result<u64> {
+        let state = &ctx.accounts.state;
+        let now = Clock::get()?.unix_timestamp;
+        let total_shares = projected_total_shares_after_fees(state, now)?;
+
+        if total_shares == 0 {
+            Ok(amount)
+        } else {
+            shares_for_amount(amount, total_shares, state.total_assets, state.decimals_offset)
+        }
+    }
+
+    /// Quotes the amount `withdraw` would pay out for `shares`, as of whatever
+    /// `collect_fees` would crystallize if it ran first. Doesn't account for
+    /// `withdraw_instant`'s separate `instant_withdrawal_fee_bps`; use that fee directly
+    /// for an instant-withdrawal quote. Pure view, like `preview_deposit`.
+    pub fn preview_withdraw(ctx: Context<ReadYieldState>, shares: u64) -> Result<u64> {
+        let state = &ctx.accounts.state;
+        let now = Clock::get()?.unix_timestamp;
+        let total_shares = projected_total_shares_after_fees(state, now)?;
+
+        amount_for_shares(shares, total_shares, state.total_assets, state.decimals_offset)
+    }
+
+    /// Quotes `owner`'s total yield since their position was opened, as a simple index delta
+    /// against `state.accrual_index` rather than replaying harvest/valuation history.
+    /// Doesn't include whatever's accrued since the last `refresh_valuation` call. Pure
+    /// view, like `preview_deposit`.
+    pub fn preview_accrued_yield(ctx: Context<PreviewAccruedYield>, _owner: Pubkey) -> Result<u64> {
+        let state = &ctx.accounts.state;
+        let user_position = &ctx.accounts.user_position;
+
+        let index_delta = state
+            .accrual_index
+            .checked_sub(user_position.accrual_index_snapshot)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        let pending = ((user_position.shares as u128)
+            .checked_mul(index_delta)
+            .ok_or(YieldPilotError::ArithmeticOverflow)?
+            / REWARD_INDEX_SCALE) as u64;
+
+        user_position
+            .accrued_yield
+            .checked_add(pending)
+            .ok_or(YieldPilotError::ArithmeticOverflow)
+    }
+}
+
+
+/// Read-only replay of `collect_fees`'s share-dilution math: how many shares `total_shares`
+/// would grow to if `collect_fees` ran right now, without mutating `state` or requiring
+/// `fee_recipient` to be configured. `total_assets` doesn't move — `collect_fees` pays fees
+/// by minting new shares against the existing asset pool, not by removing assets — so only
+/// the share count needs projecting forward.
+fn projected_total_shares_after_fees(state: &YieldState, now: i64) -> Result<u64> {
+    if state.fee_recipient == Pubkey::default() {
+        return Ok(state.total_shares);
+    }
+
+    let elapsed = now.saturating_sub(state.last_fee_collection_ts);
+    let fee_value = accrued_fee_value(
+        state.total_assets,
+        state.management_fee_bps,
+        elapsed,
+        state.performance_fee_bps,
+        state.high_water_mark,
+        state.total_shares,
+    );
+    if fee_value == 0 {
+        return Ok(state.total_shares);
+    }
+
+    let shares_minted = if state.total_shares == 0 {
+        fee_value
+    } else {
+        shares_for_amount(fee_value, state.total_shares, state.total_assets, state.decimals_offset)?
+    };
+
+    Ok(state
+        .total_shares
+        .checked_add(shares_minted)
+        .ok_or(YieldPilotError::ArithmeticOverflow)?)
+}
+
+/// Advances `state.reward_per_share_index` by the emission accrued since
+/// `last_reward_update_ts`. Must run before `total_shares` or `reward_emission_per_second`
+/// changes, so emission that already happened is locked in under the rate/supply that was
+/// actually active while it accrued. No-op while `total_shares` is zero — there's no one
+/// to attribute the emission to, so it simply isn't accrued rather than lost into the index.
+fn accrue_reward_index(state: &mut YieldState) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let elapsed = now.saturating_sub(state.last_reward_update_ts).max(0) as u128;
+    state.last_reward_update_ts = now;
+
+    if elapsed == 0 || state.total_shares == 0 || state.reward_emission_per_second == 0 {
+        return Ok(());
+    }
+
+    let emitted = elapsed
+        .checked_mul(state.reward_emission_per_second as u128)
+        .ok_or(YieldPilotError::ArithmeticOverflow)?;
+    let delta = emitted
+        .checked_mul(REWARD_INDEX_SCALE)
+        .ok_or(YieldPilotError::ArithmeticOverflow)?
+        .checked_div(state.total_shares as u128)
+        .ok_or(YieldPilotError::DivisionByZero)?;
+
+    state.reward_per_share_index = state
+        .reward_per_share_index
+        .checked_add(delta)
+        .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+    Ok(())
+}
+
+/// Settles `user_position`'s share of the emission accrued since its last snapshot into
+/// `pending_rewards`, then re-snapshots `reward_debt` against the current index. Must run
+/// after `accrue_reward_index` and before `user_position.shares` changes, so the
+/// settlement is charged against the balance that actually earned it. While the position
+/// is still inside its lockup (`locked_until_ts` in the future), the accrual is boosted by
+/// `lock_boost_bps`.
+fn settle_pending_rewards(state: &YieldState, user_position: &mut UserPosition) -> Result<()> {
+    let index_delta = state
+        .reward_per_share_index
+        .checked_sub(user_position.reward_debt)
+        .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+    let base_accrued = ((user_position.shares as u128)
+        .checked_mul(index_delta)
+        .ok_or(YieldPilotError::ArithmeticOverflow)?
+        / REWARD_INDEX_SCALE) as u64;
+
+    let accrued = if user_position.locked_until_ts > Clock::get()?.unix_timestamp {
+        base_accrued
+            .checked_add(
+                ((base_accrued as u128 * user_position.lock_boost_bps as u128) / 10_000) as u64,
+            )
+            .ok_or(YieldPilotError::ArithmeticOverflow)?
+    } else {
+        base_accrued
+    };
+
+    user_position.pending_rewards = user_position
+        .pending_rewards
+        .checked_add(accrued)
+        .ok_or(YieldPilotError::ArithmeticOverflow)?;
+    user_position.reward_debt = state.reward_per_share_index;
+
+    Ok(())
+}
+
+/// Advances `state.accrual_index` by the per-share yield implied by `refresh_valuation`
+/// observing `new_tvl` against the strategy's previously recorded `old_tvl`. Monotonically
+/// increasing: a valuation that comes back lower than last time's (a loss) leaves the index
+/// untouched rather than decreasing it. No-op while `total_shares` is zero — there's no one
+/// to attribute the gain to.
+fn accrue_yield_index(state: &mut YieldState, old_tvl: u64, new_tvl: u64) -> Result<()> {
+    if state.total_shares == 0 {
+        return Ok(());
+    }
+
+    let gain = new_tvl.saturating_sub(old_tvl);
+    if gain == 0 {
+        return Ok(());
+    }
+
+    let delta = (gain as u128)
+        .checked_mul(REWARD_INDEX_SCALE)
+        .ok_or(YieldPilotError::ArithmeticOverflow)?
+        .checked_div(state.total_shares as u128)
+        .ok_or(YieldPilotError::DivisionByZero)?;
+
+    state.accrual_index = state
+        .accrual_index
+        .checked_add(delta)
+        .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+    Ok(())
+}
+
+/// Settles `user_position`'s share of the yield accrued since its last snapshot into
+/// `accrued_yield`, then re-snapshots `accrual_index_snapshot` against the current index.
+/// Must run before `user_position.shares` changes, mirroring `settle_pending_rewards`, so a
+/// mid-epoch joiner's `accrued_yield` only reflects index growth that happened while they
+/// actually held their balance.
+fn settle_accrued_yield(state: &YieldState, user_position: &mut UserPosition) -> Result<()> {
+    let index_delta = state
+        .accrual_index
+        .checked_sub(user_position.accrual_index_snapshot)
+        .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+    let accrued = ((user_position.shares as u128)
+        .checked_mul(index_delta)
+        .ok_or(YieldPilotError::ArithmeticOverflow)?
+        / REWARD_INDEX_SCALE) as u64;
+
+    user_position.accrued_yield = user_position
+        .accrued_yield
+        .checked_add(accrued)
+        .ok_or(YieldPilotError::ArithmeticOverflow)?;
+    user_position.accrual_index_snapshot = state.accrual_index;
+
+    Ok(())
+}
+
+/// Records the wallet-supplied referrer on first deposit, both on the position (for
+/// attribution/analytics) and on the vault itself, since `collect_fees` only has a single
+/// aggregate performance fee to split and so can only pay out one referrer per vault.
+/// Later deposits — by this depositor or anyone else — cannot overwrite a referrer once
+/// one is recorded.
+fn record_referrer(state: &mut YieldState, user_position: &mut UserPosition, referrer: Option<Pubkey>) {
+    let Some(referrer) = referrer else {
+        return;
+    };
+
+    if user_position.referrer == Pubkey::default() {
+        user_position.referrer = referrer;
+    }
+    if state.referrer == Pubkey::default() {
+        state.referrer = referrer;
+    }
+}
+
+/// Resolves a requested lock tier to its reward boost. `lock_duration_secs` must already
+/// be known non-zero; the zero (no lock) case is handled by `apply_lock` before this runs.
+fn lock_tier_boost_bps(lock_duration_secs: i64) -> Result<u16> {
+    match lock_duration_secs {
+        LOCK_TIER_30D_SECS => Ok(LOCK_BOOST_BPS_30D),
+        LOCK_TIER_90D_SECS => Ok(LOCK_BOOST_BPS_90D),
+        _ => Err(YieldPilotError::InvalidLockDuration.into()),
+    }
+}
+
+/// Locks `user_position`'s shares until `lock_duration_secs` from now and records the
+/// matching reward boost. A no-op for `lock_duration_secs == 0` (plain unlocked deposit).
+/// Only ever extends an existing lock — a shorter or zero duration on a later deposit never
+/// shortens `locked_until_ts` or lowers `lock_boost_bps` below what's already recorded.
+fn apply_lock(user_position: &mut UserPosition, lock_duration_secs: i64) -> Result<()> {
+    if lock_duration_secs == 0 {
+        return Ok(());
+    }
+
+    let boost_bps = lock_tier_boost_bps(lock_duration_secs)?;
+    let locked_until_ts = Clock::get()?
+        .unix_timestamp
+        .checked_add(lock_duration_secs)
+        .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+    if locked_until_ts > user_position.locked_until_ts {
+        user_position.locked_until_ts = locked_until_ts;
+        user_position.lock_boost_bps = boost_bps;
+    }
+
+    Ok(())
+}
+
+/// Shared by `rebalance` and `crank_rebalance` once each has finished its own
+/// authorization/threshold checks: withdraws the vault's assets from the outgoing
+/// strategy, deposits into the incoming one, and records the resulting allocation.
+/// `min_amount_out` bounds the outgoing strategy's unstake/withdraw leg, which is the one
+/// capable of realizing less than expected (e.g. a stake pool's instant-unstake fee); the
+/// incoming deposit leg moves a caller-controlled amount and has nothing to slip.
+fn apply_rebalance<'info>(
+    state: &mut Account<'info, YieldState>,
+    history: &mut YieldHistory,
+    vault: &mut InterfaceAccount<'info, TokenAccount>,
+    vault_authority_info: &AccountInfo<'info>,
+    vault_authority_seeds: &[&[u8]],
+    old_protocol: u8,
+    new_protocol: u8,
+    new_apy_bps: u16,
+    new_target_weight_bps: u16,
+    new_max_weight_bps: u16,
+    new_max_tvl_lamports: u64,
+    route_via_sanctum: bool,
+    sanctum_max_slippage_bps: u16,
+    sanctum_router_program: Pubkey,
+    remaining_accounts: &[AccountInfo<'info>],
+    old_adapter_account_count: u8,
+    min_amount_out: u64,
+    now: i64,
+) -> Result<RebalanceOutcome> {
+    require!(
+        now.saturating_sub(state.last_rebalance_ts) >= state.rebalance_cooldown_secs,
+        YieldPilotError::RebalanceCooldownActive
+    );
+
+    let old_deployed_amount = state.deployed_amount;
+    let new_deployed_amount = weight_capped_deployment(
+        state.total_assets,
+        new_target_weight_bps,
+        new_max_weight_bps,
+        new_max_tvl_lamports,
+    );
+    require!(
+        state.max_move_per_rebalance == 0
+            || old_deployed_amount.max(new_deployed_amount) <= state.max_move_per_rebalance,
+        YieldPilotError::RebalanceExceedsMoveLimit
+    );
+
+    let split = old_adapter_account_count as usize;
+    require!(
+        split <= remaining_accounts.len(),
+        YieldPilotError::InvalidAdapterAccounts
+    );
+    let (old_adapter_accounts, new_adapter_accounts) = remaining_accounts.split_at(split);
+
+    let mut realized_slippage = 0u64;
+    if old_protocol != 0 && old_deployed_amount > 0 {
+        let vault_balance_before = vault.amount;
+        invoke_adapter(
+            "withdraw",
+            old_deployed_amount,
+            old_adapter_accounts,
+            vault_authority_info,
+            vault_authority_seeds,
+            state,
+        )?;
+        vault.reload()?;
+        let received = vault
+            .amount
+            .checked_sub(vault_balance_before)
+            .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+        require!(received >= min_amount_out, YieldPilotError::SlippageExceeded);
+        realized_slippage = old_deployed_amount.saturating_sub(received);
+    }
+    if new_protocol != 0 && new_deployed_amount > 0 {
+        if route_via_sanctum && sanctum_router_program != Pubkey::default() {
+            let min_route_amount_out = (new_deployed_amount as u128)
+                .saturating_mul((MAX_WEIGHT_BPS - sanctum_max_slippage_bps) as u128)
+                / MAX_WEIGHT_BPS as u128;
+            invoke_swap(
+                new_deployed_amount,
+                min_route_amount_out as u64,
+                new_adapter_accounts,
+                vault_authority_info,
+                vault_authority_seeds,
+                sanctum_router_program,
+            )?;
+        } else {
+            invoke_adapter(
+                "deposit",
+                new_deployed_amount,
+                new_adapter_accounts,
+                vault_authority_info,
+                vault_authority_seeds,
+                state,
+            )?;
+        }
+    }
+
+    state.current_protocol = new_protocol;
+    state.current_apy_bps = new_apy_bps;
+    state.deployed_amount = new_deployed_amount;
+    history.record_snapshot(new_protocol, new_apy_bps, now);
+    state.last_rebalance_ts = now;
+
+    Ok(RebalanceOutcome {
+        old_protocol,
+        new_protocol,
+        amount_deployed: new_deployed_amount,
+        realized_slippage,
+    })
+}
+
+/// Shared by `deploy_idle` and `deposit_and_deploy`: pushes whatever idle balance sits
+/// above `buffer_bps` into `state.current_protocol`. Returns `0` rather than erroring when
+/// there's no excess, since `deposit_and_deploy` treats the deploy leg as best-effort and
+/// only `deploy_idle` itself turns a zero return into `NoExcessLiquidity`.
+fn deploy_excess_idle<'info>(
+    state: &mut Account<'info, YieldState>,
+    vault_authority_info: &AccountInfo<'info>,
+    vault_authority_seeds: &[&[u8]],
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<u64> {
+    let idle = state
+        .total_assets
+        .checked_sub(state.deployed_amount)
+        .ok_or(YieldPilotError::ArithmeticUnderflow)?;
+    let target_buffer =
+        (state.total_assets as u128 * state.buffer_bps as u128 / MAX_WEIGHT_BPS as u128) as u64;
+    if idle <= target_buffer {
+        return Ok(0);
+    }
+    let excess = idle - target_buffer;
+
+    invoke_adapter(
+        "deposit",
+        excess,
+        remaining_accounts,
+        vault_authority_info,
+        vault_authority_seeds,
+        state,
+    )?;
+
+    state.deployed_amount = state
+        .deployed_amount
+        .checked_add(excess)
+        .ok_or(YieldPilotError::ArithmeticOverflow)?;
+
+    Ok(excess)
+}
+
+/// Standard sorted-pair Merkle proof verification for `claim`: hashes `leaf` up through
+/// `proof`, sorting each pair before hashing so the same tree verifies regardless of which
+/// side a node fell on when it was built.
+fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let mut computed = leaf;
+    for node in proof {
+        computed = if computed <= *node {
+            keccak::hashv(&[&computed, node]).to_bytes()
+        } else {
+            keccak::hashv(&[node, &computed]).to_bytes()
+        };
+    }
+    computed == root
+}
+
+/// Appends a Borsh-encoded `String` (u32 LE length prefix + UTF-8 bytes), matching how the
+/// Metaplex Token Metadata program expects its `name`/`symbol`/`uri` instruction arguments.
+fn push_borsh_string(buf: &mut Vec<u8>, s: &str) {
+    buf.extend_from_slice(&(s.len() as u32).to_le_bytes());
+    buf.extend_from_slice(s.as_bytes());
+}
+
+/// Appends a Borsh-encoded `DataV2` (name, symbol, uri, zero seller fee, no creators /
+/// collection / uses) — the shape both `CreateMetadataAccountV3` and
+/// `UpdateMetadataAccountV2` embed their token data as.
+fn push_metadata_data_v2(buf: &mut Vec<u8>, name: &str, symbol: &str, uri: &str) {
+    push_borsh_string(buf, name);
+    push_borsh_string(buf, symbol);
+    push_borsh_string(buf, uri);
+    buf.extend_from_slice(&0u16.to_le_bytes()); // seller_fee_basis_points
+    buf.push(0); // creators: None
+    buf.push(0); // collection: None
+    buf.push(0); // uses: None
+}
+
+fn create_metadata_v3_instruction_data(name: &str, symbol: &str, uri: &str) -> Vec<u8> {
+    let mut data = vec![33u8]; // CreateMetadataAccountV3 instruction discriminant
+    push_metadata_data_v2(&mut data, name, symbol, uri);
+    data.push(1); // is_mutable: true
+    data.push(0); // collection_details: None
+    data
+}
+
+fn update_metadata_v2_instruction_data(name: &str, symbol: &str, uri: &str) -> Vec<u8> {
+    let mut data = vec![15u8]; // UpdateMetadataAccountV2 instruction discriminant
+    data.push(1); // data: Some(DataV2)
+    push_metadata_data_v2(&mut data, name, symbol, uri);
+    data.push(0); // new_update_authority: None
+    data.push(0); // primary_sale_happened: None
+    data.push(0); // is_mutable: None
+    data
+}
+
+/// Wormhole Core Bridge's `PostMessage` instruction data: a one-byte discriminant, the
+/// caller-chosen `nonce` (lets a relayer dedupe resends of the same logical message), the
+/// payload length-prefixed as Borsh encodes a `Vec<u8>`, and a `consistency_level` of 1
+/// ("finalized") so downstream chains only observe the message after Solana finality.
+fn post_message_instruction_data(nonce: u32, payload: &[u8]) -> Vec<u8> {
+    let mut data = vec![1u8]; // PostMessage instruction discriminant
+    data.extend_from_slice(&nonce.to_le_bytes());
+    data.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    data.extend_from_slice(payload);
+    data.push(1); // consistency_level: Finalized
+    data
+}
+
+/// Invokes `global:<name>(amount: u64)` on a strategy adapter, signed by the vault
+/// authority PDA. Adapters implement a shared Anchor-style interface so the router
+/// doesn't need a generated CPI crate per protocol. `accounts[0]` is client-supplied via
+/// `remaining_accounts`, so it's checked against `state.allowed_adapter_programs` before
+/// the CPI — a `StrategyInfo.adapter_program` record alone isn't sufficient authorization,
+/// since nothing else ties it to what's actually passed in at call time.
+fn invoke_adapter<'info>(
+    name: &str,
+    amount: u64,
+    accounts: &[AccountInfo<'info>],
+    vault_authority: &AccountInfo<'info>,
+    vault_authority_seeds: &[&[u8]],
+    state: &YieldState,
+) -> Result<()> {
+    let (adapter_program, adapter_accounts) = accounts
+        .split_first()
+        .ok_or(YieldPilotError::InvalidAdapterAccounts)?;
+    require!(
+        state.is_allowed_adapter_program(&adapter_program.key()),
+        YieldPilotError::UnknownAdapterProgram
+    );
+
+    let mut data = anchor_lang::solana_program::hash::hash(format!("global:{name}").as_bytes())
+        .to_bytes()[..8]
+        .to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    let mut account_metas = vec![AccountMeta::new_readonly(vault_authority.key(), true)];
+    account_metas.extend(adapter_accounts.iter().map(|account| {
+        if account.is_writable {
+            AccountMeta::new(account.key(), account.is_signer)
+        } else {
+            AccountMeta::new_readonly(account.key(), account.is_signer)
+        }
+    }));
+
+    let mut cpi_accounts = vec![vault_authority.clone()];
+    cpi_accounts.extend(adapter_accounts.iter().cloned());
+
+    let ix = Instruction {
+        program_id: adapter_program.key(),
+        accounts: account_metas,
+        data,
+    };
+    invoke_signed(&ix, &cpi_accounts, &[vault_authority_seeds])?;
+
+    Ok(())
+}
+
+/// Invokes `global:value_position` on a strategy adapter and reads back its return-data
+/// u64, mirroring `invoke_adapter`'s shared interface but for a read-only valuation query
+/// rather than a state-mutating deposit/withdraw. No vault-authority signer is forwarded
+/// since valuation reads never move funds. `accounts[0]` is checked against
+/// `state.allowed_adapter_programs`, same as `invoke_adapter`.
+fn invoke_adapter_value<'info>(accounts: &[AccountInfo<'info>], state: &YieldState) -> Result<u64> {
+    let (adapter_program, adapter_accounts) = accounts
+        .split_first()
+        .ok_or(YieldPilotError::InvalidAdapterAccounts)?;
+    require!(
+        state.is_allowed_adapter_program(&adapter_program.key()),
+        YieldPilotError::UnknownAdapterProgram
+    );
+
+    let data = anchor_lang::solana_program::hash::hash(b"global:value_position").to_bytes()[..8].to_vec();
+
+    let account_metas = adapter_accounts
+        .iter()
+        .map(|account| {
+            if account.is_writable {
+                AccountMeta::new(account.key(), account.is_signer)
+            } else {
+                AccountMeta::new_readonly(account.key(), account.is_signer)
+            }
+        })
+        .collect();
+
+    let ix = Instruction {
+        program_id: adapter_program.key(),
+        accounts: account_metas,
+        data,
+    };
+    invoke(&ix, adapter_accounts)?;
+
+    let (returned_program_id, return_data) =
+        get_return_data().ok_or(YieldPilotError::MissingValuationReturnData)?;
+    require_keys_eq!(
+        returned_program_id,
+        adapter_program.key(),
+        YieldPilotError::InvalidAdapterAccounts
+    );
+    require!(
+        return_data.len() == 8,
+        YieldPilotError::MissingValuationReturnData
+    );
+    Ok(u64::from_le_bytes(return_data.try_into().unwrap()))
+}
+
+/// CPIs into the configured `swap_program` to route `amount_in` of whatever token the
+/// caller's accounts are denominated in into at least `min_amount_out` of the destination
+/// token. Unlike `invoke_adapter`, the program id is checked against `expected_program`
+/// rather than trusted from the account list, since it's meant to be a specific configured
+/// venue (e.g. Jupiter) rather than a per-strategy adapter the vault already knows about.
+fn invoke_swap<'info>(
+    amount_in: u64,
+    min_amount_out: u64,
+    accounts: &[AccountInfo<'info>],
+    vault_authority: &AccountInfo<'info>,
+    vault_authority_seeds: &[&[u8]],
+    expected_program: Pubkey,
+) -> Result<()> {
+    let (swap_program, swap_accounts) = accounts
+        .split_first()
+        .ok_or(YieldPilotError::InvalidAdapterAccounts)?;
+    require_keys_eq!(
+        swap_program.key(),
+        expected_program,
+        YieldPilotError::InvalidSwapProgram
+    );
+
+    let mut data = anchor_lang::solana_program::hash::hash(b"global:swap").to_bytes()[..8].to_vec();
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&min_amount_out.to_le_bytes());
+
+    let mut account_metas = vec![AccountMeta::new_readonly(vault_authority.key(), true)];
+    account_metas.extend(swap_accounts.iter().map(|account| {
+        if account.is_writable {
+            AccountMeta::new(account.key(), account.is_signer)
+        } else {
+            AccountMeta::new_readonly(account.key(), account.is_signer)
+        }
+    }));
+
+    let mut cpi_accounts = vec![vault_authority.clone()];
+    cpi_accounts.extend(swap_accounts.iter().cloned());
+
+    let ix = Instruction {
+        program_id: swap_program.key(),
+        accounts: account_metas,
+        data,
+    };
+    invoke_signed(&ix, &cpi_accounts, &[vault_authority_seeds])?;
+
+    Ok(())
+}
+
+/// Checks a strategy's Pyth price against its configured bounds and freshness window.
+/// A strategy with `oracle == Pubkey::default()` skips the check entirely; one that does
+/// require an oracle but wasn't passed an account fails closed.
+fn validate_oracle(
+    strategy_info: &StrategyInfo,
+    oracle: Option<&UncheckedAccount>,
+    now: i64,
+) -> Result<()> {
+    if strategy_info.oracle == Pubkey::default() {
+        return Ok(());
+    }
+
+    let oracle = oracle.ok_or(YieldPilotError::MissingOracle)?;
+    require_keys_eq!(
+        oracle.key(),
+        strategy_info.oracle,
+        YieldPilotError::MissingOracle
+    );
+
+    let data = oracle.try_borrow_data()?;
+    let (price, publish_time) = match strategy_info.oracle_kind {
+        OracleKind::Pyth => (read_pyth_price(&data)?, read_pyth_publish_time(&data)?),
+        OracleKind::Switchboard => (
+            read_switchboard_price(&data)?,
+            read_switchboard_publish_time(&data)?,
+        ),
+    };
+
+    require!(
+        now.saturating_sub(publish_time) <= strategy_info.max_oracle_staleness_secs,
+        YieldPilotError::OracleStale
+    );
+    require!(
+        price >= strategy_info.min_price && price <= strategy_info.max_price,
+        YieldPilotError::PriceOutOfBounds
+    );
+
+    Ok(())
+}
+
+/// Guards `deposit`/`withdraw` against acting on a stale mark-to-market: when the vault has
+/// funds deployed to a strategy with `max_valuation_staleness_secs` set, `refresh_valuation`
+/// must have run within that window, so a stale `total_assets` can't be used to mint or
+/// redeem shares at a gamed price.
+fn check_valuation_fresh(strategy_info: Option<&StrategyInfo>, current_protocol: u8, now: i64) -> Result<()> {
+    if current_protocol == 0 {
+        return Ok(());
+    }
+    let strategy_info = strategy_info.ok_or(YieldPilotError::UnknownStrategy)?;
+    require!(
+        strategy_info.max_valuation_staleness_secs == 0
+            || now.saturating_sub(strategy_info.last_valued_at) <= strategy_info.max_valuation_staleness_secs,
+        YieldPilotError::StaleValuation
+    );
+
+    Ok(())
+}
+
+/// Sets bit `id` in a 256-bit `[u64; 4]` protocol bitmap (`YieldState::registered_protocols_bitmap`
+/// / `valuations_refreshed_bitmap`), little-endian word order.
+fn bitmap_set(bitmap: &mut [u64; 4], id: u8) {
+    bitmap[(id / 64) as usize] |= 1u64 << (id % 64);
+}
+
+/// Clears bit `id`, mirroring `bitmap_set`.
+fn bitmap_clear(bitmap: &mut [u64; 4], id: u8) {
+    bitmap[(id / 64) as usize] &= !(1u64 << (id % 64));
+}
+
+fn bitmap_get(bitmap: &[u64; 4], id: u8) -> bool {
+    bitmap[(id / 64) as usize] & (1u64 << (id % 64)) != 0
+}
+
+/// True if every bit set in `required` is also set in `satisfied` — used by `roll_epoch` to
+/// confirm `valuations_refreshed_bitmap` covers everything in `registered_protocols_bitmap`.
+fn bitmap_is_subset(required: &[u64; 4], satisfied: &[u64; 4]) -> bool {
+    required
+        .iter()
+        .zip(satisfied.iter())
+        .all(|(req, sat)| req & sat == *req)
+}
+
+/// Byte layout of a native Ed25519Program instruction's data: a signature count, a padding
+/// byte, then per-signature offset/instruction-index pairs pointing at where the actual
+/// signature, pubkey, and message bytes live. `update_yield_signed` only ever expects a
+/// single signature.
+const ED25519_NUM_SIGNATURES_OFFSET: usize = 0;
+const ED25519_SIGNATURE_IX_INDEX_OFFSET: usize = 4;
+const ED25519_PUBKEY_OFFSET_OFFSET: usize = 6;
+const ED25519_PUBKEY_IX_INDEX_OFFSET: usize = 8;
+const ED25519_MESSAGE_DATA_OFFSET_OFFSET: usize = 10;
+const ED25519_MESSAGE_DATA_SIZE_OFFSET: usize = 12;
+const ED25519_MESSAGE_IX_INDEX_OFFSET: usize = 14;
+const ED25519_PUBKEY_LEN: usize = 32;
+/// Instruction-index value Solana's `Ed25519Program::new_ed25519_instruction` writes into
+/// every offset field to mean "this same instruction", rather than some other instruction in
+/// the transaction. Rejecting anything else stops the signature/pubkey/message bytes from
+/// being smuggled in from a different instruction.
+const ED25519_SELF_INSTRUCTION_INDEX: u16 = u16::MAX;
+
+/// Verifies that the instruction immediately preceding this one in the same transaction is a
+/// native Ed25519Program signature check by `expected_signer` over exactly
+/// `expected_message`. Used by `update_yield_signed` to accept off-chain-signed APY updates
+/// without requiring an on-chain `Signer`.
+fn verify_ed25519_signature(
+    instructions_sysvar: &UncheckedAccount,
+    expected_signer: &Pubkey,
+    expected_message: &[u8],
+) -> Result<()> {
+    let instructions_sysvar = instructions_sysvar.to_account_info();
+    let current_index = load_current_index_checked(&instructions_sysvar)?;
+    require!(current_index > 0, YieldPilotError::InvalidEd25519Instruction);
+
+    let ed25519_ix = load_instruction_at_checked((current_index - 1) as usize, &instructions_sysvar)?;
+    require!(
+        ed25519_ix.program_id == ed25519_program::ID,
+        YieldPilotError::InvalidEd25519Instruction
+    );
+
+    let data = &ed25519_ix.data;
+    require!(
+        data.len() >= ED25519_MESSAGE_IX_INDEX_OFFSET + 2,
+        YieldPilotError::InvalidEd25519Instruction
+    );
+    require!(
+        data[ED25519_NUM_SIGNATURES_OFFSET] == 1,
+        YieldPilotError::InvalidEd25519Instruction
+    );
+
+    let read_u16 = |offset: usize| u16::from_le_bytes(data[offset..offset + 2].try_into().unwrap());
+    require!(
+        read_u16(ED25519_SIGNATURE_IX_INDEX_OFFSET) == ED25519_SELF_INSTRUCTION_INDEX
+            && read_u16(ED25519_PUBKEY_IX_INDEX_OFFSET) == ED25519_SELF_INSTRUCTION_INDEX
+            && read_u16(ED25519_MESSAGE_IX_INDEX_OFFSET) == ED25519_SELF_INSTRUCTION_INDEX,
+        YieldPilotError::InvalidEd25519Instruction
+    );
+
+    let pubkey_offset = read_u16(ED25519_PUBKEY_OFFSET_OFFSET) as usize;
+    let message_offset = read_u16(ED25519_MESSAGE_DATA_OFFSET_OFFSET) as usize;
+    let message_size = read_u16(ED25519_MESSAGE_DATA_SIZE_OFFSET) as usize;
+    require!(
+        data.len() >= pubkey_offset + ED25519_PUBKEY_LEN && data.len() >= message_offset + message_size,
+        YieldPilotError::InvalidEd25519Instruction
+    );
+
+    require!(
+        data[pubkey_offset..pubkey_offset + ED25519_PUBKEY_LEN] == expected_signer.to_bytes(),
+        YieldPilotError::Ed25519SignerMismatch
+    );
+    require!(
+        data[message_offset..message_offset + message_size] == *expected_message,
+        YieldPilotError::Ed25519MessageMismatch
+    );
+
+    Ok(())
+}
+
+/// Byte layout of a native Secp256k1Program instruction's data: a signature count, then
+/// per-signature offset/instruction-index fields pointing at where the actual signature,
+/// Ethereum address, and message bytes live. `update_yield_attested_evm` only ever expects
+/// a single signature. Unlike Ed25519Program, there's no padding byte after the count and
+/// the instruction-index fields are `u8`, not `u16`.
+const SECP256K1_NUM_SIGNATURES_OFFSET: usize = 0;
+const SECP256K1_SIGNATURE_IX_INDEX_OFFSET: usize = 3;
+const SECP256K1_ETH_ADDRESS_OFFSET_OFFSET: usize = 4;
+const SECP256K1_ETH_ADDRESS_IX_INDEX_OFFSET: usize = 6;
+const SECP256K1_MESSAGE_DATA_OFFSET_OFFSET: usize = 7;
+const SECP256K1_MESSAGE_DATA_SIZE_OFFSET: usize = 9;
+const SECP256K1_MESSAGE_IX_INDEX_OFFSET: usize = 11;
+const SECP256K1_ETH_ADDRESS_LEN: usize = 20;
+
+/// Verifies that the instruction immediately preceding this one in the same transaction is
+/// a native Secp256k1Program signature check by `expected_signer` (a 20-byte Ethereum
+/// address) over exactly `expected_message`. Used by `update_yield_attested_evm` to accept
+/// EVM-key-signed APY updates without requiring an on-chain `Signer`.
+fn verify_secp256k1_signature(
+    instructions_sysvar: &UncheckedAccount,
+    expected_signer: &[u8; 20],
+    expected_message: &[u8],
+) -> Result<()> {
+    let instructions_sysvar = instructions_sysvar.to_account_info();
+    let current_index = load_current_index_checked(&instructions_sysvar)?;
+    require!(current_index > 0, YieldPilotError::InvalidSecp256k1Instruction);
+
+    let secp_ix_index = current_index - 1;
+    let secp_ix = load_instruction_at_checked(secp_ix_index as usize, &instructions_sysvar)?;
+    require!(
+        secp_ix.program_id == secp256k1_program::ID,
+        YieldPilotError::InvalidSecp256k1Instruction
+    );
+
+    let data = &secp_ix.data;
+    require!(
+        data.len() >= SECP256K1_MESSAGE_IX_INDEX_OFFSET + 1,
+        YieldPilotError::InvalidSecp256k1Instruction
+    );
+    require!(
+        data[SECP256K1_NUM_SIGNATURES_OFFSET] == 1,
+        YieldPilotError::InvalidSecp256k1Instruction
+    );
+
+    require!(
+        data[SECP256K1_SIGNATURE_IX_INDEX_OFFSET] as u16 == secp_ix_index
+            && data[SECP256K1_ETH_ADDRESS_IX_INDEX_OFFSET] as u16 == secp_ix_index
+            && data[SECP256K1_MESSAGE_IX_INDEX_OFFSET] as u16 == secp_ix_index,
+        YieldPilotError::InvalidSecp256k1Instruction
+    );
+
+    let read_u16 = |offset: usize| u16::from_le_bytes(data[offset..offset + 2].try_into().unwrap());
+    let eth_address_offset = read_u16(SECP256K1_ETH_ADDRESS_OFFSET_OFFSET) as usize;
+    let message_offset = read_u16(SECP256K1_MESSAGE_DATA_OFFSET_OFFSET) as usize;
+    let message_size = read_u16(SECP256K1_MESSAGE_DATA_SIZE_OFFSET) as usize;
+    require!(
+        data.len() >= eth_address_offset + SECP256K1_ETH_ADDRESS_LEN
+            && data.len() >= message_offset + message_size,
+        YieldPilotError::InvalidSecp256k1Instruction
+    );
+
+    require!(
+        data[eth_address_offset..eth_address_offset + SECP256K1_ETH_ADDRESS_LEN] == *expected_signer,
+        YieldPilotError::Secp256k1SignerMismatch
+    );
+    require!(
+        data[message_offset..message_offset + message_size] == *expected_message,
+        YieldPilotError::Secp256k1MessageMismatch
+    );
+
+    Ok(())
+}
+
+/// Pyth's mapped `Price` account stores the aggregate price as an i64 at a fixed byte
+/// offset, and the last publish unix timestamp a few fields later.
+const PYTH_PRICE_OFFSET: usize = 208;
+const PYTH_PUBLISH_TIME_OFFSET: usize = 224;
+
+fn read_pyth_price(data: &[u8]) -> Result<i64> {
+    let end = PYTH_PRICE_OFFSET + 8;
+    require!(data.len() >= end, YieldPilotError::MalformedOracleAccount);
+    Ok(i64::from_le_bytes(
+        data[PYTH_PRICE_OFFSET..end].try_into().unwrap(),
+    ))
+}
+
+fn read_pyth_publish_time(data: &[u8]) -> Result<i64> {
+    let end = PYTH_PUBLISH_TIME_OFFSET + 8;
+    require!(data.len() >= end, YieldPilotError::MalformedOracleAccount);
+    Ok(i64::from_le_bytes(
+        data[PYTH_PUBLISH_TIME_OFFSET..end].try_into().unwrap(),
+    ))
+}
+
+/// Switchboard V2's `AggregatorAccountData` stores the latest confirmed round's result as
+/// a `SwitchboardDecimal { mantissa: i128, scale: u32 }` and round-open timestamp at fixed
+/// byte offsets within the account.
+const SWITCHBOARD_RESULT_MANTISSA_OFFSET: usize = 120;
+const SWITCHBOARD_RESULT_SCALE_OFFSET: usize = 136;
+const SWITCHBOARD_ROUND_OPEN_TIMESTAMP_OFFSET: usize = 96;
+
+fn read_switchboard_price(data: &[u8]) -> Result<i64> {
+    let mantissa_end = SWITCHBOARD_RESULT_MANTISSA_OFFSET + 16;
+    let scale_end = SWITCHBOARD_RESULT_SCALE_OFFSET + 4;
+    require!(
+        data.len() >= scale_end,
+        YieldPilotError::MalformedOracleAccount
+    );
+    let mantissa = i128::from_le_bytes(
+        data[SWITCHBOARD_RESULT_MANTISSA_OFFSET..mantissa_end]
+            .try_into()
+            .unwrap(),
+    );
+    let scale = u32::from_le_bytes(
+        data[SWITCHBOARD_RESULT_SCALE_OFFSET..scale_end]
+            .try_into()
+            .unwrap(),
+    );
+    Ok((mantissa / 10i128.pow(scale)) as i64)
+}
+
+fn read_switchboard_publish_time(data: &[u8]) -> Result<i64> {
+    let end = SWITCHBOARD_ROUND_OPEN_TIMESTAMP_OFFSET + 8;
+    require!(data.len() >= end, YieldPilotError::MalformedOracleAccount);
+    Ok(i64::from_le_bytes(
+        data[SWITCHBOARD_ROUND_OPEN_TIMESTAMP_OFFSET..end]
+            .try_into()
+            .unwrap(),
+    ))
+}
+
+#[account]
+pub struct PendingParamChange {
+    pub version: u8,
+    pub change: ParamChangeKind,
+    pub earliest_execution_ts: i64,
+    pub bump: u8,
+}
+
+/// Queued via `queue_loss_report`, applied via `execute_loss_report` once
+/// `PARAM_CHANGE_TIMELOCK_SECS` has elapsed. One per vault; a fresh report can't be queued
+/// until the previous one has been executed or cancelled, so at most one write-down is ever
+/// in flight.
+#[account]
+#[derive(Default)]
+pub struct PendingLossReport {
+    pub version: u8,
+    pub amount: u64,
+    pub evidence_hash: [u8; 32],
+    pub earliest_execution_ts: i64,
+    pub bump: u8,
+}
+
+/// Optional pre-clearance for `rebalance` into `target_protocol`, gated behind
+/// `REBALANCE_VETO_WINDOW_SLOTS` so the guardian has a chance to block it. One per vault;
+/// re-queuing overwrites whatever protocol/amount/veto state was there before.
+#[account]
+#[derive(Default)]
+pub struct QueuedRebalance {
+    pub version: u8,
+    pub target_protocol: u8,
+    pub amount: u64,
+    pub execute_after_slot: u64,
+    pub vetoed: bool,
+    pub bump: u8,
+}
+
+/// Resumable-rebalance checkpoint opened by `start_rebalance` when a move is too large to
+/// take in one `rebalance`/`crank_rebalance` call under `max_move_per_rebalance`.
+/// `continue_rebalance` drains it one chunk at a time — first unwinding `withdraw_amount` out
+/// of `old_protocol` (`phase` 0), then redeploying whatever came back into `new_protocol`
+/// (`phase` 1) — and `finish_rebalance` closes it once `remaining_amount` reaches zero in
+/// `phase` 1. One per vault; `start_rebalance` can't open a second one while this exists.
+#[account]
+pub struct RebalanceInProgress {
+    pub version: u8,
+    pub old_protocol: u8,
+    pub new_protocol: u8,
+    pub new_apy_bps: u16,
+    /// 0 while unwinding `old_protocol`, 1 once fully unwound and redeploying into
+    /// `new_protocol`.
+    pub phase: u8,
+    /// Amount still to move in the current `phase`. Reset to `withdrawn_total` when `phase`
+    /// flips from 0 to 1.
+    pub remaining_amount: u64,
+    /// `state.deployed_amount` as of `start_rebalance`, fixed for the life of this
+    /// checkpoint. Used at `finish_rebalance` to compute `realized_slippage`.
+    pub withdraw_amount: u64,
+    /// Running total pulled out of `old_protocol` so far, net of realized slippage. Becomes
+    /// the `phase` 1 deposit amount once the unwind completes.
+    pub withdrawn_total: u64,
+    pub old_adapter_account_count: u8,
+    pub min_amount_out: u64,
+    pub bump: u8,
+}
+
+/// Parameter changes gated behind the `queue_param_change`/`execute_param_change`
+/// timelock. Limited to settings that can move funds or revenue around; things like
+/// `set_pause_flags` stay immediate since they only ever make the vault safer.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum ParamChangeKind {
+    FeeConfig {
+        management_fee_bps: u16,
+        performance_fee_bps: u16,
+    },
+    FeeRecipient {
+        fee_recipient: Pubkey,
+    },
+    MinImprovementBps {
+        min_improvement_bps: u16,
+    },
+    RebalanceCooldownSecs {
+        rebalance_cooldown_secs: i64,
+    },
+    CrankTipBps {
+        crank_tip_bps: u16,
+    },
+    InstantWithdrawalFeeBps {
+        instant_withdrawal_fee_bps: u16,
+    },
+    BufferBps {
+        buffer_bps: u16,
+    },
+    SwapProgram {
+        swap_program: Pubkey,
+    },
+    MaxReasonableApyBps {
+        max_reasonable_apy_bps: u16,
+    },
+    SanctumRouterProgram {
+        sanctum_router_program: Pubkey,
+    },
+}
+
+/// A share-weighted proposal to apply one `ParamChangeKind` change. Created by
+/// `create_proposal`, which locks the creator's shares as an implicit "for" vote, voted on
+/// by `vote_proposal` until `voting_ends_at`, and — if it clears `GOVERNANCE_QUORUM_BPS` and
+/// a simple majority — handed to `queue_proposal_execution`, which queues `change` into the
+/// same `PendingParamChange` timelock `queue_param_change` uses rather than applying it
+/// directly. Depositors get a window to exit either way: once during voting, once more
+/// during the timelock.
+#[account]
+pub struct GovernanceProposal {
+    pub version: u8,
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub change: ParamChangeKind,
+    pub votes_for: u64,
+    pub votes_against: u64,
+    pub voting_ends_at: i64,
+    pub queued: bool,
+    pub bump: u8,
+}
+
+/// Proof that `voter` locked `locked_shares` of the vault's shares into `governance_escrow`
+/// while voting on a `GovernanceProposal`. Closed by `reclaim_vote` once voting has ended,
+/// which returns the locked shares and this account's rent.
+#[account]
+#[derive(Default)]
+pub struct VoteRecord {
+    pub version: u8,
+    pub locked_shares: u64,
+    pub support: bool,
+    pub bump: u8,
+}
+
+/// One Merkle-distributed retroactive rewards campaign for this vault. `root` commits
+/// off-chain to the full list of `(index, claimant, amount)` leaves; `claim` verifies a
+/// Merkle proof against it before releasing `amount` of `mint` from `distributor_vault`.
+#[account]
+pub struct MerkleDistributor {
+    pub version: u8,
+    pub id: u64,
+    pub mint: Pubkey,
+    pub root: [u8; 32],
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub bump: u8,
+}
+
+/// Marker PDA proving leaf `index` of a `MerkleDistributor` has already been claimed;
+/// `claim`'s `init` constraint makes a second claim of the same leaf fail outright, it
+/// carries no other state.
+#[account]
+#[derive(Default)]
+pub struct ClaimReceipt {
+    pub version: u8,
+    pub bump: u8,
+}
+
+#[event]
+pub struct Initialized {
+    pub state: Pubkey,
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub vault_index: u64,
+}
+
+#[event]
+pub struct YieldUpdated {
+    pub state: Pubkey,
+    pub protocol: u8,
+    pub apy_bps: u16,
+    pub actor: Pubkey,
+}
+
+#[event]
+pub struct YieldUpdatedByEvmAttester {
+    pub state: Pubkey,
+    pub protocol: u8,
+    pub apy_bps: u16,
+    pub attester: [u8; 20],
+}
+
+#[event]
+pub struct ValuationRefreshed {
+    pub state: Pubkey,
+    pub protocol: u8,
+    pub tvl: u64,
+}
+
+#[event]
+pub struct Rebalanced {
+    pub state: Pubkey,
+    pub old_protocol: u8,
+    pub new_protocol: u8,
+    pub new_apy_bps: u16,
+    pub amount: u64,
+    pub actor: Pubkey,
+}
+
+/// Compact summary of what `rebalance`/`crank_rebalance` actually did, returned as the
+/// instruction's return value (alongside the `Rebalanced` event) so a keeper or integrator
+/// can read the outcome straight off the transaction's return data instead of parsing logs.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RebalanceOutcome {
+    pub old_protocol: u8,
+    pub new_protocol: u8,
+    /// Amount routed into `new_protocol`, mirroring `Rebalanced::amount`.
+    pub amount_deployed: u64,
+    /// How much less than `old_deployed_amount` came back out of `old_protocol` on unwind.
+    /// Zero when nothing was deployed there, or when the unwind returned at least as much
+    /// as was deployed.
+    pub realized_slippage: u64,
+}
+
+/// `simulate_rebalance`'s projection of what `rebalance`/`crank_rebalance` would do against
+/// `new_protocol`, returned as the instruction's return value for an RPC-simulating keeper
+/// to read. `projected_realized_slippage` isn't included — unlike `RebalanceOutcome`'s, it
+/// can only be known once the outgoing adapter's withdraw CPI actually executes, which this
+/// instruction never does.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct SimulatedRebalanceOutcome {
+    pub old_protocol: u8,
+    pub new_protocol: u8,
+    /// What `weight_capped_deployment` would route into `new_protocol`, mirroring
+    /// `RebalanceOutcome::amount_deployed`.
+    pub projected_deployed_amount: u64,
+    /// What `collect_fees` would currently claim if it ran first, same math as
+    /// `projected_total_shares_after_fees` uses for `preview_deposit`/`preview_withdraw`.
+    pub projected_fee_value: u64,
+}
+
+#[event]
+pub struct RebalanceStarted {
+    pub state: Pubkey,
+    pub old_protocol: u8,
+    pub new_protocol: u8,
+    pub new_apy_bps: u16,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RebalanceChunkApplied {
+    pub state: Pubkey,
+    pub phase: u8,
+    pub amount: u64,
+    pub remaining_amount: u64,
+}
+
+#[event]
+pub struct EmergencyExited {
+    pub state: Pubkey,
+    pub old_protocol: u8,
+    pub amount_recovered: u64,
+    pub actor: Pubkey,
+}
+
+#[event]
+pub struct Deposited {
+    pub state: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub shares_minted: u64,
+}
+
+#[event]
+pub struct Withdrawn {
+    pub state: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub shares_burned: u64,
+}
+
+#[event]
+pub struct PositionMigrated {
+    pub state: Pubkey,
+    pub successor_state: Pubkey,
+    pub depositor: Pubkey,
+    pub shares_burned: u64,
+    pub amount: u64,
+    pub shares_minted: u64,
+}
+
+#[event]
+pub struct NftReceiptMinted {
+    pub state: Pubkey,
+    pub depositor: Pubkey,
+    pub receipt: Pubkey,
+    pub receipt_mint: Pubkey,
+    pub amount: u64,
+    pub shares: u64,
+}
+
+#[event]
+pub struct NftReceiptRedeemed {
+    pub state: Pubkey,
+    pub redeemer: Pubkey,
+    pub receipt: Pubkey,
+    pub receipt_mint: Pubkey,
+    pub shares: u64,
+}
+
+#[event]
+pub struct IdleDeployed {
+    pub state: Pubkey,
+    pub protocol: u8,
+    pub amount: u64,
+}
+
+#[event]
+pub struct Harvested {
+    pub state: Pubkey,
+    pub protocol: u8,
+    pub reward_mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct AllowlistUpdated {
+    pub state: Pubkey,
+    pub wallet: Pubkey,
+    pub allowed: bool,
+}
+
+#[event]
+pub struct ProtocolBlacklistUpdated {
+    pub state: Pubkey,
+    pub protocol: u8,
+    pub blacklisted: bool,
+}
+
+#[event]
+pub struct OperatorLimitsUpdated {
+    pub state: Pubkey,
+    pub operator: Pubkey,
+    pub max_apy_updates_per_day: u16,
+    pub max_rebalance_volume_per_day: u64,
+    pub active: bool,
+}
+
+#[event]
+pub struct ShareMetadataUpdated {
+    pub state: Pubkey,
+    pub share_mint: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+#[event]
+pub struct InstantWithdrawn {
+    pub state: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub shares_burned: u64,
+}
+
+#[event]
+pub struct WithdrawalQueued {
+    pub state: Pubkey,
+    pub depositor: Pubkey,
+    pub sequence: u64,
+    pub shares: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct WithdrawalReady {
+    pub state: Pubkey,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct WithdrawalClaimed {
+    pub state: Pubkey,
+    pub depositor: Pubkey,
+    pub sequence: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FeesCollected {
+    pub state: Pubkey,
+    pub fee_value: u64,
+    pub shares_minted: u64,
+    pub high_water_mark: u64,
+    /// Portion of `shares_minted` routed to the insurance fund rather than `fee_recipient`.
+    pub insurance_shares: u64,
+    /// Portion of `shares_minted` routed to `state.referrer` rather than `fee_recipient`.
+    pub referral_shares: u64,
+    /// Portion of `shares_minted` rebated back to `fee_tier_position` rather than
+    /// `fee_recipient`, per its own `fee_discount_bps`.
+    pub fee_tier_shares: u64,
+}
+
+#[event]
+pub struct LossCovered {
+    pub state: Pubkey,
+    pub amount: u64,
+    pub shares_burned: u64,
+}
+
+#[event]
+pub struct LossReported {
+    pub state: Pubkey,
+    pub amount: u64,
+    /// Hash of the off-chain incident report (post-mortem, exploit tx, audit) backing this
+    /// write-down, so the reduction in `total_assets` isn't just an admin's bare assertion.
+    pub evidence_hash: [u8; 32],
+    pub total_assets_after: u64,
+}
+
+#[event]
+pub struct RewardsClaimed {
+    pub state: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct EpochRolled {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub share_price: u64,
+    pub total_assets: u64,
+    pub total_shares: u64,
+}
+
+#[event]
+pub struct StatePublished {
+    pub state: Pubkey,
+    pub share_price: u64,
+    pub total_assets: u64,
+    pub apy_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub state: Pubkey,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub voting_ends_at: i64,
+}
+
+#[event]
+pub struct ProposalQueued {
+    pub state: Pubkey,
+    pub proposal_id: u64,
+    pub votes_for: u64,
+    pub votes_against: u64,
+}
+
+#[event]
+pub struct DistributorCreated {
+    pub state: Pubkey,
+    pub distributor_id: u64,
+    pub mint: Pubkey,
+    pub root: [u8; 32],
+}
+
+#[event]
+pub struct RewardsDistributed {
+    pub state: Pubkey,
+    pub distributor_id: u64,
+    pub claimant: Pubkey,
+    pub amount: u64,
+}
+
+/// Per-depositor running total, kept alongside the share token balance so wallets can
+/// show realized/unrealized yield without replaying the vault's full deposit/withdraw
+/// history.
+#[account]
+#[derive(Default)]
+pub struct UserPosition {
+    pub version: u8,
+    pub shares: u64,
+    pub cumulative_deposits: u64,
+    pub cumulative_withdrawals: u64,
+    pub last_action_slot: u64,
+    /// Slot of this depositor's most recent `deposit`/`deposit_sol`. Withdrawals check this
+    /// against `MIN_WITHDRAWAL_DELAY_SLOTS` to prevent depositing right before a favorable
+    /// `update_yield`/harvest and withdrawing the inflated share price in the same slot.
+    pub last_deposit_slot: u64,
+    pub bump: u8,
+    /// Snapshot of `YieldState.reward_per_share_index` as of this position's last accrual.
+    /// `settle_pending_rewards` charges only the index movement since this snapshot.
+    pub reward_debt: u128,
+    /// Rewards settled by `settle_pending_rewards` but not yet paid out by `claim_rewards`.
+    pub pending_rewards: u64,
+    /// Referrer supplied on this position's first `deposit`/`deposit_sol` call, recorded by
+    /// `record_referrer`. Default (unset) means this depositor came in without a referrer.
+    pub referrer: Pubkey,
+    /// Unix timestamp before which no withdrawal path will release this position's shares.
+    /// Zero (the default) means unlocked. Set and extended (never shortened) by `apply_lock`.
+    pub locked_until_ts: i64,
+    /// Liquidity-mining reward boost, in bps, applied by `settle_pending_rewards` while
+    /// `locked_until_ts` is still in the future. Set alongside `locked_until_ts`.
+    pub lock_boost_bps: u16,
+    /// Snapshot of `YieldState.accrual_index` as of this position's last `deposit`/
+    /// `withdraw`. `settle_accrued_yield` charges only the index movement since this
+    /// snapshot, so a mid-epoch joiner's `accrued_yield` only reflects index growth that
+    /// happened while they actually held their balance.
+    pub accrual_index_snapshot: u128,
+    /// Yield settled by `settle_accrued_yield` since this position was opened, in the
+    /// vault's underlying asset units. Purely informational/for fee-split attribution — it
+    /// isn't a separate payable balance, since the yield is already reflected in the share
+    /// price `withdraw` redeems against.
+    pub accrued_yield: u64,
+    /// Discount, in bps, applied to this position's pro-rata share of `collect_fees`'s
+    /// mint, set by `set_fee_tier` for strategic depositors (e.g. treasury, launch
+    /// partners). `10_000` exempts the position entirely; zero (the default) means no
+    /// discount, same blended rate as everyone else. Appended after `accrued_yield` for the
+    /// same realloc-slack reason as the other additions above.
+    pub fee_discount_bps: u16,
+}
+
+/// Proof of `owner`'s `shares` balance as of `epoch`, taken via `take_snapshot` within
+/// `SNAPSHOT_WINDOW_SECS` of that epoch starting. One per `(epoch, owner)` — `UserPosition`'s
+/// live `shares` field keeps moving, so this is the only way to prove what a wallet held at
+/// a specific past epoch for an off-chain airdrop or governance weighting pass.
+#[account]
+#[derive(Default)]
+pub struct Snapshot {
+    pub version: u8,
+    pub epoch: u64,
+    pub owner: Pubkey,
+    pub shares: u64,
+    pub bump: u8,
+}
+
+/// Compact, refreshable health summary for one vault, written by the permissionless
+/// `update_health` so monitoring can alert off a single account fetch instead of reading
+/// `YieldState` plus `StrategyInfo` plus re-deriving staleness/drift itself.
+#[account]
+#[derive(Default)]
+pub struct VaultHealth {
+    pub version: u8,
+    pub state: Pubkey,
+    /// When `update_health` last wrote this snapshot.
+    pub updated_at: i64,
+    pub updated_slot: u64,
+    /// `current_protocol`'s `StrategyInfo.last_valued_at` is older than its own
+    /// `max_valuation_staleness_secs` (and the check is enabled). Always `false` when
+    /// `current_protocol` has no registered `StrategyInfo`.
+    pub valuation_stale: bool,
+    /// `current_protocol`'s `StrategyInfo.last_apy_update_ts` is older than its own
+    /// `max_staleness_secs` (and the check is enabled). Always `false` when
+    /// `current_protocol` has no registered `StrategyInfo`.
+    pub apy_stale: bool,
+    /// Idle (undeployed) share of `total_assets`, in bps. Compare against `state.buffer_bps`
+    /// to see whether the vault is sitting on more or less idle cash than its target.
+    pub buffer_ratio_bps: u16,
+    /// `current_protocol`'s actually-deployed share of `total_assets` minus its own
+    /// `target_weight_bps`, in bps. Positive means overallocated, negative underallocated,
+    /// zero when `total_assets` is zero or there's no registered `StrategyInfo`.
+    pub allocation_drift_bps: i32,
+    /// Seconds since `state.last_harvest_ts`. `i64::MAX` if harvest has never run.
+    pub secs_since_harvest: i64,
+    pub bump: u8,
+}
+
+/// Marker PDA proving `add_to_allowlist` has approved a wallet; `deposit` only checks for
+/// its existence, it carries no other state.
+#[account]
+#[derive(Default)]
+pub struct AllowlistEntry {
+    pub version: u8,
+    pub bump: u8,
+}
+
+/// Marker PDA proving `blacklist_protocol` has blocked a protocol id; `rebalance`,
+/// `crank_rebalance`, and `deploy_idle` only check for its existence before deploying into
+/// that protocol, it carries no other state. Separate from `StrategyInfo::enabled` so a
+/// guardian can block a protocol without needing the authority key that `update_strategy`
+/// requires.
+#[account]
+#[derive(Default)]
+pub struct ProtocolBlacklist {
+    pub version: u8,
+    pub protocol: u8,
+    pub bump: u8,
+}
+
+/// Per-operator daily caps on `update_yield` frequency and `rebalance` volume, registered
+/// by `register_operator` so a single compromised bot key granted `state.updaters` status
+/// has bounded blast radius instead of the same unlimited trust as every other updater.
+/// One PDA per `(state, operator)`; an operator with no `OperatorLimits` PDA is simply
+/// unrestricted, same as before this registry existed.
+#[account]
+#[derive(Default)]
+pub struct OperatorLimits {
+    pub version: u8,
+    pub operator: Pubkey,
+    /// Zero means unlimited, matching the "zero disables" convention used by
+    /// `max_total_deposits`/`max_tvl_lamports` elsewhere.
+    pub max_apy_updates_per_day: u16,
+    pub max_rebalance_volume_per_day: u64,
+    pub window_started_at: i64,
+    pub apy_updates_in_window: u16,
+    pub rebalance_volume_in_window: u64,
+    pub bump: u8,
+}
+
+impl OperatorLimits {
+    /// Rolls the window forward and zeroes both counters once `OPERATOR_LIMITS_WINDOW_SECS`
+    /// has elapsed since it opened, so idle capacity never accumulates across windows.
+    fn roll_window_if_elapsed(&mut self, now: i64) {
+        if now.saturating_sub(self.window_started_at) >= OPERATOR_LIMITS_WINDOW_SECS {
+            self.window_started_at = now;
+            self.apy_updates_in_window = 0;
+            self.rebalance_volume_in_window = 0;
+        }
+    }
+
+    /// Charges one `update_yield` call against the window, rejecting once
+    /// `max_apy_updates_per_day` would be exceeded.
+    fn charge_apy_update(&mut self, now: i64) -> Result<()> {
+        self.roll_window_if_elapsed(now);
+        if self.max_apy_updates_per_day > 0 {
+            require!(
+                self.apy_updates_in_window < self.max_apy_updates_per_day,
+                YieldPilotError::OperatorApyUpdateCapExceeded
+            );
+        }
+        self.apy_updates_in_window = self.apy_updates_in_window.saturating_add(1);
+        Ok(())
+    }
+
+    /// Charges `amount` of rebalance volume against the window, rejecting once
+    /// `max_rebalance_volume_per_day` would be exceeded.
+    fn charge_rebalance_volume(&mut self, now: i64, amount: u64) -> Result<()> {
+        self.roll_window_if_elapsed(now);
+        let projected = self.rebalance_volume_in_window.saturating_add(amount);
+        if self.max_rebalance_volume_per_day > 0 {
+            require!(
+                projected <= self.max_rebalance_volume_per_day,
+                YieldPilotError::OperatorRebalanceVolumeCapExceeded
+            );
+        }
+        self.rebalance_volume_in_window = projected;
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(Default)]
+pub struct YieldState {
+    /// Schema version, checked by `migrate_state` before it reallocs the account to a
+    /// newer layout. Kept as the very first field so its offset never moves across
+    /// migrations, regardless of what gets added or reordered after it.
+    pub version: u8,
+    /// Admin key, checked via `has_one` on every privileged instruction. Anchor's `Signer`
+    /// only requires `is_signer` to be set on the account, so this works unmodified when
+    /// `authority` is a multisig's PDA vault (e.g. a Squads vault) rather than a raw
+    /// keypair: the multisig program CPIs in and signs for its own PDA with
+    /// `invoke_signed`, which sets `is_signer` exactly as a top-level signature would.
+    pub authority: Pubkey,
+    /// Disambiguates multiple vaults owned by the same `authority` (e.g. one per asset);
+    /// folded into every PDA derived directly from `authority` (`yield_state`,
+    /// `vault_authority`, `share_mint`) so their seeds don't collide across vaults.
+    pub vault_index: u64,
+    pub current_protocol: u8,
+    pub current_apy_bps: u16,
+    pub pending_authority: Option<Pubkey>,
+    pub updaters: [Pubkey; MAX_UPDATERS],
+    pub updater_count: u8,
+    pub min_improvement_bps: u16,
+    pub total_shares: u64,
+    pub total_assets: u64,
+    pub mint: Pubkey,
+    pub share_mint: Pubkey,
+    /// Unix timestamp of the last successful `rebalance`/`crank_rebalance`. Used to
+    /// enforce `rebalance_cooldown_secs` against permissionless cranking.
+    pub last_rebalance_ts: i64,
+    pub rebalance_cooldown_secs: i64,
+    /// Tip paid to whoever calls `crank_rebalance` successfully, in bps of the rebalanced
+    /// amount. Gives keepers a reason to crank so the vault doesn't depend on a trusted
+    /// off-chain authority actually showing up.
+    pub crank_tip_bps: u16,
+    /// Annualized management fee in bps of `total_assets`, accrued continuously and
+    /// collected via `collect_fees`.
+    pub management_fee_bps: u16,
+    /// Performance fee in bps of profit above the high-water mark, collected alongside
+    /// the management fee.
+    pub performance_fee_bps: u16,
+    /// Share token account fees are minted into.
+    pub fee_recipient: Pubkey,
+    /// Unix timestamp of the last `collect_fees` call; management fee accrues from here.
+    pub last_fee_collection_ts: i64,
+    /// Highest share price (scaled by `SHARE_PRICE_SCALE`) ever observed in `collect_fees`.
+    /// The performance fee only taxes profit above this mark, so a drawdown followed by a
+    /// recovery back to the old price isn't charged again.
+    pub high_water_mark: u64,
+    /// Guardian role: authorized to pause/unpause the vault but nothing else. Kept
+    /// separate from `authority` (admin: parameter changes) and `updaters` (operator:
+    /// APY updates and rebalances) so a compromised guardian key can't move funds.
+    pub guardian: Pubkey,
+    /// Independent circuit breakers the guardian can flip without touching the others —
+    /// withdrawals stay open under `deposits_paused` so depositors are never trapped.
+    pub deposits_paused: bool,
+    pub withdrawals_paused: bool,
+    pub rebalances_paused: bool,
+    /// How much of `total_assets` is actually deployed to `current_protocol`, as opposed
+    /// to sitting idle in `vault`. Equal to `total_assets` unless `current_protocol`'s
+    /// `target_weight_bps`/`max_weight_bps` cap it below full concentration.
+    pub deployed_amount: u64,
+    /// Sequence number assigned to the next `request_withdrawal` ticket. Monotonically
+    /// increasing, so tickets can be processed in the order they were requested.
+    pub next_withdrawal_sequence: u64,
+    /// Sequence number of the oldest unprocessed ticket; `process_withdrawal_queue` only
+    /// accepts the ticket at this position, enforcing FIFO order.
+    pub withdrawal_queue_head: u64,
+    /// Fee charged on `withdraw_instant`, in bps of the withdrawn amount. Left in the vault
+    /// rather than transferred out, so it accrues to remaining depositors via a higher share
+    /// price instead of going to a fee recipient.
+    pub instant_withdrawal_fee_bps: u16,
+    /// Target share of `total_assets` to keep undeployed in `vault` for instant withdrawals.
+    /// `deploy_idle` tops up `current_protocol` when the idle balance exceeds this. Zero
+    /// disables the buffer (the pre-existing behavior of deploying everything on rebalance).
+    pub buffer_bps: u16,
+    /// Program id CPI'd into by `harvest` to route reward tokens into the vault's
+    /// underlying asset when they arrive in a different mint. Pluggable so the vault isn't
+    /// hard-coded to one venue (e.g. Jupiter's aggregator). Default (zero) disables
+    /// cross-mint harvesting; same-mint rewards still compound without it.
+    pub swap_program: Pubkey,
+    /// Ceiling on `total_assets` enforced by `deposit`. Zero disables the cap.
+    pub max_total_deposits: u64,
+    /// Ceiling on a single depositor's `UserPosition::cumulative_deposits` enforced by
+    /// `deposit`. Zero disables the cap.
+    pub max_deposit_per_user: u64,
+    /// When true, `deposit` requires the depositor to hold an `AllowlistEntry` PDA.
+    pub allowlist_enabled: bool,
+    /// Absolute sanity ceiling on `update_yield`/`rebalance`/`crank_rebalance`'s
+    /// `new_apy_bps`, independent of any single strategy's `max_apy_bps`. Bounds the
+    /// blast radius of a corrupted or compromised updater bot posting an outlandish APY,
+    /// even if a misconfigured strategy's own cap would otherwise let it through.
+    pub max_reasonable_apy_bps: u16,
+    pub bump: u8,
+    /// Reentrancy lock held for the duration of any instruction that CPIs into an
+    /// adapter or the token program (deposits, withdrawals, rebalances, fee collection).
+    /// Appended after `bump` rather than inserted alongside the other flags so existing
+    /// vaults pick it up from `migrate_state`'s realloc slack without shifting any
+    /// already-deployed field's offset.
+    pub operation_in_progress: bool,
+    /// Slice of each `collect_fees` mint diverted into the `insurance_fund` share account
+    /// (seeds `[b"insurance_fund", state.key()]`) instead of `fee_recipient`, in bps of the
+    /// total fee. Zero keeps the pre-existing behavior of sending every fee share to
+    /// `fee_recipient`. Appended after `operation_in_progress` for the same realloc-slack
+    /// reason.
+    pub insurance_bps: u16,
+    /// Running totals for off-chain dashboards, so they don't need to replay every
+    /// `deposit`/`withdraw`/`collect_fees`/`harvest` event to show vault performance.
+    /// Never read on-chain; purely analytics. Appended after `insurance_bps` for the same
+    /// realloc-slack reason as the other additions above.
+    pub lifetime_deposits: u64,
+    /// Incremented when a withdrawal actually pays out (`withdraw`, `withdraw_sol`,
+    /// `withdraw_instant`, `claim_withdrawal`), not when `request_withdrawal` queues one —
+    /// mirrors `UserPosition::cumulative_withdrawals`'s timing.
+    pub lifetime_withdrawals: u64,
+    pub lifetime_fees_collected: u64,
+    /// Cumulative `amount` harvested into `total_assets` across every `harvest` call.
+    pub lifetime_yield_earned: u64,
+    /// Reward token paid out by `claim_rewards` for the liquidity-mining program. Zero (the
+    /// default) means `initialize_rewards_vault` hasn't been called for this vault yet.
+    /// Appended after `lifetime_yield_earned` for the same realloc-slack reason as the
+    /// other additions above.
+    pub reward_mint: Pubkey,
+    /// Reward units emitted per second, split across `total_shares` via
+    /// `reward_per_share_index`. Set by `initialize_rewards_vault`/`set_reward_emission_rate`.
+    pub reward_emission_per_second: u64,
+    /// Cumulative reward units owed per share, scaled by `REWARD_INDEX_SCALE`. Advanced by
+    /// `accrue_reward_index` immediately before `total_shares` or
+    /// `reward_emission_per_second` changes, so every share earns exactly the emission that
+    /// occurred while it was outstanding.
+    pub reward_per_share_index: u128,
+    /// Unix timestamp `reward_per_share_index` was last advanced to.
+    pub last_reward_update_ts: i64,
+    /// Referrer attributed by the first `deposit`/`deposit_sol` call that supplied one.
+    /// Default (unset) means no referral has been recorded for this vault yet. Locked in
+    /// on first write; see `record_referrer`.
+    pub referrer: Pubkey,
+    /// Share of the performance/management fee routed to `referrer_share_account` on
+    /// `collect_fees`, in bps. Has no effect while `referrer` is unset.
+    pub referral_bps: u16,
+    /// Minimum seconds between `roll_epoch` calls. Zero means epochs are unbounded — the
+    /// crank may roll one as often as it likes, the same way `rebalance_cooldown_secs == 0`
+    /// leaves rebalancing uncooled.
+    pub epoch_length_secs: i64,
+    /// Unix timestamp the current epoch started at. Advanced to `now` by every `roll_epoch`.
+    pub epoch_started_at: i64,
+    /// Number of epochs rolled so far. Starts at zero; `roll_epoch` increments it.
+    pub current_epoch: u64,
+    /// Share price (scaled by `SHARE_PRICE_SCALE`) captured by the most recent `roll_epoch`.
+    pub last_epoch_share_price: u64,
+    /// Off-chain keeper key `update_yield_signed` accepts Ed25519-signed APY updates from.
+    /// Default (unset) disables the instruction entirely. Appended after
+    /// `last_epoch_share_price` for the same realloc-slack reason as the other additions
+    /// above.
+    pub apy_oracle_signer: Pubkey,
+    /// Minimum number of fresh, still-registered `YieldReportBoard` samples
+    /// `aggregate_yield` requires before it will land a median. Appended after
+    /// `apy_oracle_signer` for the same realloc-slack reason as the other additions above.
+    pub min_report_quorum: u8,
+    /// Ethereum address `update_yield_attested_evm` accepts secp256k1-signed APY updates
+    /// from. Default (`[0u8; 20]`) disables the instruction entirely. Appended after
+    /// `min_report_quorum` for the same realloc-slack reason as the other additions above.
+    pub evm_apy_attester: [u8; 20],
+    /// Program id `apply_rebalance` CPIs into (via `invoke_swap`) to hop directly between
+    /// two LST strategies' position tokens instead of unwinding all the way to the vault's
+    /// base asset and redepositing, e.g. Sanctum's router for SOL-denominated vaults. Default
+    /// (zero) disables the path entirely, regardless of any per-strategy
+    /// `StrategyInfo::route_via_sanctum` flag. Appended after `evm_apy_attester` for the same
+    /// realloc-slack reason as the other additions above.
+    pub sanctum_router_program: Pubkey,
+    /// Whether `share_mint` allows transfers between depositor wallets. Set once at
+    /// `create_vault`/`create_vault_soulbound` time and never changed after, since flipping
+    /// it would require reinitializing the mint's Token-2022 extensions underneath shares
+    /// already in circulation. Appended after `sanctum_router_program` for the same
+    /// realloc-slack reason as the other additions above.
+    pub transferable_shares: bool,
+    /// Next id handed out by `create_proposal`, then incremented. Appended after
+    /// `transferable_shares` for the same realloc-slack reason as the other additions above.
+    pub next_proposal_id: u64,
+    /// Next id handed out by `create_distributor`, then incremented. Appended after
+    /// `next_proposal_id` for the same realloc-slack reason as the other additions above.
+    pub next_distributor_id: u64,
+    /// Cumulative per-share yield observed across every `refresh_valuation` call, scaled by
+    /// `REWARD_INDEX_SCALE` like `reward_per_share_index`. Monotonically increasing — a
+    /// valuation that comes back lower than last time leaves it untouched rather than
+    /// decreasing it. Appended after `next_distributor_id` for the same realloc-slack reason
+    /// as the other additions above.
+    pub accrual_index: u128,
+    /// Largest single-call move `apply_rebalance` will make between `old_protocol` and
+    /// `new_protocol`, in underlying asset units. Above this, `rebalance`/`crank_rebalance`
+    /// refuse with `RebalanceExceedsMoveLimit` and the operator must use
+    /// `start_rebalance`/`continue_rebalance` instead, which chunk the same move across
+    /// multiple crank calls. Zero disables the check, same convention as
+    /// `max_staleness_secs`. Appended after `accrual_index` for the same realloc-slack
+    /// reason as the other additions above.
+    pub max_move_per_rebalance: u64,
+    /// One bit per protocol id (0-255) set by `register_strategy` and cleared by
+    /// `close_strategy`. `roll_epoch` checks this against `valuations_refreshed_bitmap` to
+    /// confirm every live strategy was refreshed this epoch before rolling. Appended after
+    /// `max_move_per_rebalance` for the same realloc-slack reason as the other additions
+    /// above.
+    pub registered_protocols_bitmap: [u64; 4],
+    /// One bit per protocol id, set by `refresh_valuation`/`refresh_valuations` for the
+    /// protocols it touched. Cleared back to all-zero by `roll_epoch` once it rolls, so the
+    /// next epoch's keeper pass starts from scratch. Appended after
+    /// `registered_protocols_bitmap` for the same realloc-slack reason as the other
+    /// additions above.
+    pub valuations_refreshed_bitmap: [u64; 4],
+    /// Ceiling on aggregate `withdraw` payouts within a single epoch, in bps of
+    /// `total_assets`. Zero disables the check, same convention as `max_move_per_rebalance`.
+    /// Meant for vaults deployed into illiquid strategies, where an uncapped bank run would
+    /// force a liquidation `rebalance`/`crank_rebalance` can't unwind cleanly. Appended after
+    /// `valuations_refreshed_bitmap` for the same realloc-slack reason as the other additions
+    /// above.
+    pub max_withdrawal_bps_per_epoch: u16,
+    /// Running total of `withdraw` payouts (not `request_withdrawal` queueing, which doesn't
+    /// count against the cap until actually claimed) since `epoch_started_at`. Reset to zero
+    /// by `roll_epoch`. Appended after `max_withdrawal_bps_per_epoch` for the same
+    /// realloc-slack reason as the other additions above.
+    pub withdrawn_this_epoch: u64,
+    /// `mint.decimals` captured at `create_vault`/`create_vault_soulbound` time, tracked
+    /// explicitly alongside `decimals_offset` so a client can recover the underlying asset's
+    /// precision without fetching the mint account. Appended after `withdrawn_this_epoch` for
+    /// the same realloc-slack reason as the other additions above.
+    pub mint_decimals: u8,
+    /// Extra decimal places added onto `mint_decimals` for `share_mint`, and the power of ten
+    /// of virtual shares folded into `shares_for_amount`/`amount_for_shares`'s pricing (see
+    /// `math.rs`) to defeat the first-depositor donation-inflation attack. Zero is a valid
+    /// choice for a high-decimals asset like 9-decimal wrapped SOL; a low-decimals asset like
+    /// 6-decimal USDC wants a few bits of headroom instead. Fixed at vault creation, since
+    /// changing it after shares are in circulation would shift the exchange rate out from
+    /// under existing holders. Appended after `mint_decimals` for the same realloc-slack
+    /// reason as the other additions above.
+    pub decimals_offset: u8,
+    /// Next id handed out by `deposit_as_nft`, then incremented. Appended after
+    /// `decimals_offset` for the same realloc-slack reason as the other additions above.
+    pub next_nft_receipt_id: u64,
+    /// Program ids `invoke_adapter`/`invoke_adapter_value` are allowed to CPI into, maintained
+    /// by `add_allowed_adapter_program`/`remove_allowed_adapter_program`. A `StrategyInfo`
+    /// recording an `adapter_program` is not by itself sufficient authorization to CPI into
+    /// it — `remaining_accounts[0]` is client-supplied on every call, so without this allowlist
+    /// a depositor-facing instruction could be made to CPI into an attacker-substituted
+    /// lookalike program instead of the real lending protocol. Appended after
+    /// `next_nft_receipt_id` for the same realloc-slack reason as the other additions above.
+    pub allowed_adapter_programs: [Pubkey; MAX_ALLOWED_ADAPTER_PROGRAMS],
+    /// Number of populated entries in `allowed_adapter_programs`. Appended after
+    /// `allowed_adapter_programs` for the same realloc-slack reason as the other additions
+    /// above.
+    pub allowed_adapter_program_count: u8,
+    /// Wormhole Core Bridge program `publish_state` CPIs into. Unset (`Pubkey::default()`)
+    /// disables `publish_state` entirely, since unlike Metaplex's metadata program Wormhole's
+    /// Core Bridge address differs per cluster and has no sane hardcoded default. Appended
+    /// after `allowed_adapter_program_count` for the same realloc-slack reason as the other
+    /// additions above.
+    pub wormhole_program: Pubkey,
+    /// Successor vault's `YieldState` address, linked by `migrate_to` so depositors can
+    /// `migrate_position` into it in-kind instead of withdrawing and re-depositing (and
+    /// paying the fees and taxable events that implies). Unset (`Pubkey::default()`)
+    /// disables `migrate_position` entirely. Appended after `wormhole_program` for the
+    /// same realloc-slack reason as the other additions above.
+    pub successor_vault: Pubkey,
+    /// Timestamp of the last successful `harvest` call. Zero means harvest has never run for
+    /// this vault. Feeds `update_health`'s `secs_since_harvest`. Appended after
+    /// `successor_vault` for the same realloc-slack reason as the other additions above.
+    pub last_harvest_ts: i64,
+}
+
+impl YieldState {
+    /// Exact on-chain size of this account, discriminator included. Maintained by hand
+    /// alongside the struct rather than derived, since Anchor's `space =` needs a
+    /// compile-time constant and `std::mem::size_of` doesn't account for Rust's field
+    /// reordering/padding the way Borsh's wire layout does. Whichever commit appends a
+    /// new field to `YieldState` must add its size here in the same commit — `init`ing
+    /// `state` for less than `LEN` fails serialization on every `create_vault`/
+    /// `create_vault_soulbound` call, not just once the account happens to fill up.
+    pub const LEN: usize = 8 // discriminator
+        + 1 // version
+        + 32 // authority
+        + 8 // vault_index
+        + 1 // current_protocol
+        + 2 // current_apy_bps
+        + (1 + 32) // pending_authority
+        + 32 * MAX_UPDATERS // updaters
+        + 1 // updater_count
+        + 2 // min_improvement_bps
+        + 8 // total_shares
+        + 8 // total_assets
+        + 32 // mint
+        + 32 // share_mint
+        + 8 // last_rebalance_ts
+        + 8 // rebalance_cooldown_secs
+        + 2 // crank_tip_bps
+        + 2 // management_fee_bps
+        + 2 // performance_fee_bps
+        + 32 // fee_recipient
+        + 8 // last_fee_collection_ts
+        + 8 // high_water_mark
+        + 32 // guardian
+        + 1 // deposits_paused
+        + 1 // withdrawals_paused
+        + 1 // rebalances_paused
+        + 8 // deployed_amount
+        + 8 // next_withdrawal_sequence
+        + 8 // withdrawal_queue_head
+        + 2 // instant_withdrawal_fee_bps
+        + 2 // buffer_bps
+        + 32 // swap_program
+        + 8 // max_total_deposits
+        + 8 // max_deposit_per_user
+        + 1 // allowlist_enabled
+        + 2 // max_reasonable_apy_bps
+        + 1 // bump
+        + 1 // operation_in_progress
+        + 2 // insurance_bps
+        + 8 // lifetime_deposits
+        + 8 // lifetime_withdrawals
+        + 8 // lifetime_fees_collected
+        + 8 // lifetime_yield_earned
+        + 32 // reward_mint
+        + 8 // reward_emission_per_second
+        + 16 // reward_per_share_index
+        + 8 // last_reward_update_ts
+        + 32 // referrer
+        + 2 // referral_bps
+        + 8 // epoch_length_secs
+        + 8 // epoch_started_at
+        + 8 // current_epoch
+        + 8 // last_epoch_share_price
+        + 32 // apy_oracle_signer
+        + 1 // min_report_quorum
+        + 20 // evm_apy_attester
+        + 32 // sanctum_router_program
+        + 1 // transferable_shares
+        + 8 // next_proposal_id
+        + 8 // next_distributor_id
+        + 16 // accrual_index
+        + 8 // max_move_per_rebalance
+        + 32 // registered_protocols_bitmap
+        + 32 // valuations_refreshed_bitmap
+        + 2 // max_withdrawal_bps_per_epoch
+        + 8 // withdrawn_this_epoch
+        + 1 // mint_decimals
+        + 1 // decimals_offset
+        + 8 // next_nft_receipt_id
+        + 32 * MAX_ALLOWED_ADAPTER_PROGRAMS // allowed_adapter_programs
+        + 1 // allowed_adapter_program_count
+        + 32 // wormhole_program
+        + 32 // successor_vault
+        + 8; // last_harvest_ts
+
+    pub fn is_updater(&self, key: &Pubkey) -> bool {
+        self.updaters[..self.updater_count as usize].contains(key)
+    }
+
+    pub fn add_updater(&mut self, updater: Pubkey) -> Result<()> {
+        require!(!self.is_updater(&updater), YieldPilotError::UpdaterAlreadyRegistered);
+        require!(
+            (self.updater_count as usize) < MAX_UPDATERS,
+            YieldPilotError::UpdaterCapacityExceeded
+        );
+
+        self.updaters[self.updater_count as usize] = updater;
+        self.updater_count += 1;
+
+        Ok(())
+    }
+
+    pub fn remove_updater(&mut self, updater: Pubkey) -> Result<()> {
+        let count = self.updater_count as usize;
+        let pos = self.updaters[..count]
+            .iter()
+            .position(|key| *key == updater)
+            .ok_or(YieldPilotError::UpdaterNotFound)?;
+
+        self.updaters[pos] = self.updaters[count - 1];
+        self.updaters[count - 1] = Pubkey::default();
+        self.updater_count -= 1;
+
+        Ok(())
+    }
+
+    pub fn is_allowed_adapter_program(&self, key: &Pubkey) -> bool {
+        self.allowed_adapter_programs[..self.allowed_adapter_program_count as usize].contains(key)
+    }
+
+    pub fn add_allowed_adapter_program(&mut self, adapter_program: Pubkey) -> Result<()> {
+        require!(
+            !self.is_allowed_adapter_program(&adapter_program),
+            YieldPilotError::AdapterProgramAlreadyAllowed
+        );
+        require!(
+            (self.allowed_adapter_program_count as usize) < MAX_ALLOWED_ADAPTER_PROGRAMS,
+            YieldPilotError::AdapterProgramCapacityExceeded
+        );
+
+        self.allowed_adapter_programs[self.allowed_adapter_program_count as usize] = adapter_program;
+        self.allowed_adapter_program_count += 1;
+
+        Ok(())
+    }
+
+    pub fn remove_allowed_adapter_program(&mut self, adapter_program: Pubkey) -> Result<()> {
+        let count = self.allowed_adapter_program_count as usize;
+        let pos = self.allowed_adapter_programs[..count]
+            .iter()
+            .position(|key| *key == adapter_program)
+            .ok_or(YieldPilotError::AdapterProgramNotFound)?;
+
+        self.allowed_adapter_programs[pos] = self.allowed_adapter_programs[count - 1];
+        self.allowed_adapter_programs[count - 1] = Pubkey::default();
+        self.allowed_adapter_program_count -= 1;
+
+        Ok(())
+    }
+}
+
+/// Holds the APY history ring buffer in its own account, separate from the hot
+/// `YieldState` row that every deposit/withdraw touches. `zero_copy` lets
+/// `update_yield`/`rebalance` append a snapshot and `get_history` read the whole buffer
+/// by reinterpreting the account's bytes directly instead of Borsh-(de)serializing the
+/// full `HISTORY_LEN`-entry array on every access.
+#[account(zero_copy)]
+#[derive(Default)]
+pub struct YieldHistory {
+    pub version: u8,
+    pub bump: u8,
+    pub head: u8,
+    pub len: u8,
+    pub history: [YieldSnapshot; HISTORY_LEN],
+}
+
+impl YieldHistory {
+    /// Returns the stored snapshots oldest-first, unwrapping the circular buffer.
+    pub fn history_chronological(&self) -> Vec<YieldSnapshot> {
+        let len = self.len as usize;
+        let head = self.head as usize;
+        let start = (head + HISTORY_LEN - len) % HISTORY_LEN;
+        (0..len).map(|i| self.history[(start + i) % HISTORY_LEN]).collect()
+    }
+
+    pub fn record_snapshot(&mut self, protocol: u8, apy_bps: u16, ts: i64) {
+        let head = self.head as usize;
+        self.history[head] = YieldSnapshot {
+            protocol,
+            apy_bps,
+            ts,
+        };
+        self.head = ((head + 1) % HISTORY_LEN) as u8;
+        self.len = self.len.saturating_add(1).min(HISTORY_LEN as u8);
+    }
+
+    /// Time-weighted average APY across the stored history, so a single instantaneous
+    /// spike can't by itself clear `min_improvement_bps`. Each snapshot's `apy_bps` is
+    /// weighted by how long it held before being superseded, with the most recent
+    /// snapshot weighted up to `now`. Falls back to the latest (or a default of 0 when
+    /// there's no history at all) when there isn't enough history to weight.
+    pub fn twap_apy_bps(&self, now: i64) -> u16 {
+        let snapshots = self.history_chronological();
+        let last = match snapshots.last() {
+            Some(snapshot) => snapshot.apy_bps,
+            None => return 0,
+        };
+        if snapshots.len() == 1 {
+            return last;
+        }
+
+        let mut weighted_sum: u128 = 0;
+        let mut total_duration: u128 = 0;
+        for i in 0..snapshots.len() {
+            let end = if i + 1 < snapshots.len() {
+                snapshots[i + 1].ts
+            } else {
+                now
+            };
+            let duration = end.saturating_sub(snapshots[i].ts).max(0) as u128;
+            weighted_sum += snapshots[i].apy_bps as u128 * duration;
+            total_duration += duration;
+        }
+
+        if total_duration == 0 {
+            last
+        } else {
+            (weighted_sum / total_duration) as u16
+        }
+    }
+}
+
+#[account]
+pub struct StrategyInfo {
+    pub version: u8,
+    pub id: u8,
+    pub name: [u8; 32],
+    pub adapter_program: Pubkey,
+    pub max_apy_bps: u16,
+    pub tvl: u64,
+    pub enabled: bool,
+    /// Vault-owned token account the adapter deposits into (e.g. the vault's mSOL account
+    /// for the Marinade adapter, or its collateral/cToken account for a lending adapter).
+    /// Unset until the strategy has been deployed to at least once.
+    pub position_account: Pubkey,
+    /// Protocol-specific market/reserve account the adapter reads for valuation (e.g.
+    /// Solend's reserve). Unused by adapters that don't need one.
+    pub reserve: Pubkey,
+    /// Price account for this strategy's underlying asset, in the format `oracle_kind`
+    /// says. `Pubkey::default()` means no oracle check is required before routing into
+    /// this strategy.
+    pub oracle: Pubkey,
+    pub oracle_kind: OracleKind,
+    /// Oldest an oracle update is allowed to be, in seconds, before `update_yield` and
+    /// `rebalance` reject it as stale.
+    pub max_oracle_staleness_secs: i64,
+    pub min_price: i64,
+    pub max_price: i64,
+    /// Timestamp of the last `update_yield` call that reported an APY for this strategy.
+    pub last_apy_update_ts: i64,
+    /// Oldest `last_apy_update_ts` is allowed to be before `rebalance`/`crank_rebalance`
+    /// refuse to route into this strategy. Zero disables the check.
+    pub max_staleness_secs: i64,
+    /// Target share of `total_assets` to deploy here when this strategy becomes
+    /// `current_protocol`, in bps. Defaults to 10_000 (full concentration), matching the
+    /// vault's original all-in-one-protocol behavior until an admin opts into partial
+    /// allocation via `set_allocation_targets`.
+    pub target_weight_bps: u16,
+    /// Hard ceiling on `target_weight_bps` for this strategy, independent of what's
+    /// requested, so a single compromised/misconfigured protocol can't take more than its
+    /// risk budget even if `target_weight_bps` is set higher.
+    pub max_weight_bps: u16,
+    /// Absolute ceiling, in the vault's underlying token units, on how much can be deployed
+    /// here regardless of `max_weight_bps`. Zero disables the check. Exists alongside the
+    /// relative `max_weight_bps` cap for operators who want a fixed exposure limit that
+    /// doesn't grow with `total_assets`.
+    pub max_tvl_lamports: u64,
+    pub bump: u8,
+    /// When routing *into* this strategy, skip `invoke_adapter`'s plain deposit and instead
+    /// swap directly from the old strategy's position token via `state.sanctum_router_program`.
+    /// Has no effect when `state.sanctum_router_program` is unset. Appended after `bump` for
+    /// the same realloc-slack reason documented on `YieldState`'s trailing fields.
+    pub route_via_sanctum: bool,
+    /// Worst acceptable slippage for the `route_via_sanctum` swap into this strategy, in bps
+    /// of the amount being moved. Unused when `route_via_sanctum` is false.
+    pub sanctum_max_slippage_bps: u16,
+    /// Timestamp of the last `refresh_valuation` call for this strategy. Appended after
+    /// `sanctum_max_slippage_bps` for the same realloc-slack reason as the other additions
+    /// above.
+    pub last_valued_at: i64,
+    /// Oldest `last_valued_at` is allowed to be before `deposit`/`withdraw` refuse to act
+    /// while this strategy is `current_protocol`. Zero disables the check, same convention
+    /// as `max_staleness_secs`.
+    pub max_valuation_staleness_secs: i64,
+    /// Total liquidity currently supplied to this venue, protocol-wide and not just the
+    /// vault's own position, in underlying-asset units. Kept in sync with the venue by
+    /// whoever calls `set_rate_curve`; zero disables deposit-impact projection entirely,
+    /// same convention as `max_tvl_lamports`. Appended after `max_valuation_staleness_secs`
+    /// for the same realloc-slack reason as the other additions above.
+    pub pool_liquidity: u64,
+    /// Local slope of this venue's supply-rate curve around its current utilization, in bps
+    /// of APY decay per 10_000 bps (100%) growth in `pool_liquidity` our deposit would
+    /// represent. Zero disables projection, so a strategy with no curve configured behaves
+    /// exactly as before `rate_slope_bps` existed.
+    pub rate_slope_bps: u16,
+}
+
+/// Which feed format `StrategyInfo::oracle` should be parsed as.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OracleKind {
+    #[default]
+    Pyth,
+    Switchboard,
+}
+
+/// One reporter's most recent `submit_yield_report` sample for a strategy.
+/// `reporter == Pubkey::default()` marks an empty slot.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct ReportSample {
+    pub reporter: Pubkey,
+    pub apy_bps: u16,
+    pub submitted_at: i64,
+}
+
+/// Per-strategy scratchpad `submit_yield_report` writes into and `aggregate_yield` reads
+/// the median out of. One slot per registered updater (indexed by first match, not by
+/// `state.updaters` position, since updaters can be added/removed independently of when
+/// they last reported).
+#[account]
+pub struct YieldReportBoard {
+    pub version: u8,
+    pub state: Pubkey,
+    pub protocol: u8,
+    pub samples: [ReportSample; MAX_UPDATERS],
+    pub bump: u8,
+}
+
+impl YieldReportBoard {
+    /// Overwrites `reporter`'s existing slot if they have one, otherwise claims an empty
+    /// slot, otherwise evicts whichever slot is oldest. The eviction case only bites if
+    /// more distinct reporters have ever submitted than `MAX_UPDATERS` allows, which
+    /// shouldn't happen since only current updaters/the authority can call
+    /// `submit_yield_report` and the updater set is itself capped at `MAX_UPDATERS`.
+    pub fn record_sample(&mut self, reporter: Pubkey, apy_bps: u16, now: i64) {
+        let slot = self
+            .samples
+            .iter()
+            .position(|s| s.reporter == reporter)
+            .or_else(|| self.samples.iter().position(|s| s.reporter == Pubkey::default()))
+            .unwrap_or_else(|| {
+                self.samples
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, s)| s.submitted_at)
+                    .map(|(i, _)| i)
+                    .unwrap_or(0)
+            });
+
+        self.samples[slot] = ReportSample {
+            reporter,
+            apy_bps,
+            submitted_at: now,
+        };
+    }
+
+    /// Median `apy_bps` across every sample whose reporter is still in `updaters` and
+    /// whose age is within `max_staleness_secs` (zero disables the staleness check, same
+    /// convention as `StrategyInfo::max_staleness_secs` elsewhere). Requires at least
+    /// `min_report_quorum` qualifying samples, so a single stale or lone reporter can't
+    /// move the vault's APY on its own.
+    pub fn median_apy_bps(
+        &self,
+        updaters: &[Pubkey],
+        max_staleness_secs: i64,
+        now: i64,
+        min_report_quorum: u8,
+    ) -> Result<u16> {
+        let mut fresh: Vec<u16> = self
+            .samples
+            .iter()
+            .filter(|s| s.reporter != Pubkey::default() && updaters.contains(&s.reporter))
+            .filter(|s| {
+                max_staleness_secs == 0 || now.saturating_sub(s.submitted_at) <= max_staleness_secs
+            })
+            .map(|s| s.apy_bps)
+            .collect();
+
+        require!(
+            fresh.len() >= min_report_quorum.max(1) as usize,
+            YieldPilotError::InsufficientReports
+        );
+
+        fresh.sort_unstable();
+        let mid = fresh.len() / 2;
+        if fresh.len() % 2 == 1 {
+            Ok(fresh[mid])
+        } else {
+            Ok(((fresh[mid - 1] as u32 + fresh[mid] as u32) / 2) as u16)
+        }
+    }
+}
+
+#[zero_copy]
+#[derive(AnchorSerialize, AnchorDeserialize, Default)]
+pub struct YieldSnapshot {
+    pub protocol: u8,
+    pub apy_bps: u16,
+    pub ts: i64,
+}
+
+/// One entry in `AuditLog`'s ring buffer. `params` holds the action's key numeric
+/// parameters packed as raw little-endian bytes (interpretation depends on `action`), so
+/// one fixed-size record shape covers every instrumented instruction without a separate
+/// account layout per action.
+#[zero_copy]
+#[derive(AnchorSerialize, AnchorDeserialize, Default)]
+pub struct AuditEntry {
+    pub sequence: u64,
+    pub slot: u64,
+    pub actor: Pubkey,
+    pub action: u8,
+    pub params: [u8; 32],
+}
+
+/// Append-only ring buffer of the last `AUDIT_LOG_LEN` admin/operator actions, held in its
+/// own zero-copy account per vault like `YieldHistory`, so integrators can detect missed
+/// events off `next_sequence` and compliance can reconstruct recent operations purely
+/// on-chain. Optional and created separately from `create_vault` via `create_audit_log`,
+/// so an existing vault can opt in without a `YieldState` migration; every instrumented
+/// instruction skips logging when it's absent, same as any other `Option` account here.
+#[account(zero_copy)]
+#[derive(Default)]
+pub struct AuditLog {
+    pub version: u8,
+    pub bump: u8,
+    pub head: u8,
+    pub len: u8,
+    pub next_sequence: u64,
+    pub entries: [AuditEntry; AUDIT_LOG_LEN],
+}
+
+impl AuditLog {
+    /// Returns the stored entries oldest-first, unwrapping the circular buffer.
+    pub fn entries_chronological(&self) -> Vec<AuditEntry> {
+        let len = self.len as usize;
+        let head = self.head as usize;
+        let start = (head + AUDIT_LOG_LEN - len) % AUDIT_LOG_LEN;
+        (0..len).map(|i| self.entries[(start + i) % AUDIT_LOG_LEN]).collect()
+    }
+
+    pub fn record(&mut self, action: u8, actor: Pubkey, slot: u64, params: [u8; 32]) {
+        let head = self.head as usize;
+        self.entries[head] = AuditEntry {
+            sequence: self.next_sequence,
+            slot,
+            actor,
+            action,
+            params,
+        };
+        self.head = ((head + 1) % AUDIT_LOG_LEN) as u8;
+        self.len = self.len.saturating_add(1).min(AUDIT_LOG_LEN as u8);
+        self.next_sequence = self.next_sequence.saturating_add(1);
+    }
+}
+
+/// Queued exit for funds deployed in a strategy with an unstaking delay: `request_withdrawal`
+/// locks in the share price and burns shares up front, `process_withdrawal_queue` marks the
+/// ticket payable once the underlying position has been unwound, and `claim_withdrawal` hands
+/// the depositor their tokens.
+#[account]
+#[derive(Default)]
+pub struct WithdrawalTicket {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub shares: u64,
+    pub amount: u64,
+    pub sequence: u64,
+    pub request_epoch: u64,
+    pub ready: bool,
+    pub claimed: bool,
+    pub bump: u8,
+}
+
+/// Backs `deposit_as_nft`'s escrow: `shares` sit in `escrow_share_account` (authority
+/// `vault_authority`, never the depositor) until whoever holds `receipt_mint`'s one
+/// outstanding token calls `redeem_nft`. Ownership of the position travels with the NFT
+/// rather than `owner` below, which is recorded only for off-chain bookkeeping of who
+/// originally deposited.
+#[account]
+#[derive(Default)]
+pub struct NftReceipt {
+    pub version: u8,
+    pub state: Pubkey,
+    pub id: u64,
+    pub owner: Pubkey,
+    pub receipt_mint: Pubkey,
+    pub shares: u64,
+    pub bump: u8,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_index: u64, decimals_offset: u8)]
+pub struct CreateVault<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = YieldState::LEN,
+        seeds = [b"yield_state", authority.key().as_ref(), &vault_index.to_le_bytes()],
+        bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 1 + 1 + 1 + 1 + (1 + 2 + 8) * HISTORY_LEN,
+        seeds = [b"yield_history", state.key().as_ref()],
+        bump,
+    )]
+    pub history: AccountLoader<'info, YieldHistory>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", authority.key().as_ref(), &vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = mint.decimals + decimals_offset,
+        mint::authority = vault_authority,
+        mint::token_program = token_program,
+        seeds = [b"share_mint", authority.key().as_ref(), &vault_index.to_le_bytes()],
+        bump,
+    )]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_index: u64, decimals_offset: u8)]
+pub struct CreateVaultSoulbound<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = YieldState::LEN,
+        seeds = [b"yield_state", authority.key().as_ref(), &vault_index.to_le_bytes()],
+        bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 1 + 1 + 1 + 1 + (1 + 2 + 8) * HISTORY_LEN,
+        seeds = [b"yield_history", state.key().as_ref()],
+        bump,
+    )]
+    pub history: AccountLoader<'info, YieldHistory>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", authority.key().as_ref(), &vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// Token-2022 mint with the `NonTransferable` extension enabled, so shares can be
+    /// minted and burned (deposit/withdraw) but never moved between wallets — the
+    /// soul-bound mode compliance-sensitive deployments need.
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = mint.decimals + decimals_offset,
+        mint::authority = vault_authority,
+        mint::token_program = token_program,
+        extensions::non_transferable,
+        seeds = [b"share_mint", authority.key().as_ref(), &vault_index.to_le_bytes()],
+        bump,
+    )]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(address = anchor_spl::token_2022::ID @ YieldPilotError::NonTransferableRequiresToken2022)]
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(new_protocol: u8, new_apy_bps: u16)]
+pub struct UpdateYield<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(mut, seeds = [b"yield_history", state.key().as_ref()], bump = history.load()?.bump)]
+    pub history: AccountLoader<'info, YieldHistory>,
+    #[account(mut, seeds = [b"strategy", state.key().as_ref(), &[new_protocol]], bump)]
+    pub strategy_info: Option<Account<'info, StrategyInfo>>,
+    /// CHECK: Pyth price account for the strategy's underlying asset; parsed manually and
+    /// required only when the strategy has an oracle configured.
+    pub oracle: Option<UncheckedAccount<'info>>,
+    pub signer: Signer<'info>,
+    /// Present only if `register_operator` has registered `signer`; when absent `signer`
+    /// is unrestricted, same as before this registry existed.
+    #[account(mut, seeds = [b"operator_limits", state.key().as_ref(), signer.key().as_ref()], bump)]
+    pub operator_limits: Option<Account<'info, OperatorLimits>>,
+    /// Present only if `create_audit_log` has been called for this vault.
+    #[account(mut, seeds = [b"audit_log", state.key().as_ref()], bump)]
+    pub audit_log: Option<AccountLoader<'info, AuditLog>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(new_protocol: u8, new_apy_bps: u16)]
+pub struct UpdateYieldSigned<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(mut, seeds = [b"yield_history", state.key().as_ref()], bump = history.load()?.bump)]
+    pub history: AccountLoader<'info, YieldHistory>,
+    #[account(mut, seeds = [b"strategy", state.key().as_ref(), &[new_protocol]], bump)]
+    pub strategy_info: Option<Account<'info, StrategyInfo>>,
+    /// CHECK: Pyth price account for the strategy's underlying asset; parsed manually and
+    /// required only when the strategy has an oracle configured.
+    pub oracle: Option<UncheckedAccount<'info>>,
+    /// CHECK: the native Instructions sysvar; only introspected for the preceding
+    /// instruction, never deserialized as account data.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    pub payer: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(new_protocol: u8, new_apy_bps: u16)]
+pub struct UpdateYieldAttestedEvm<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(mut, seeds = [b"yield_history", state.key().as_ref()], bump = history.load()?.bump)]
+    pub history: AccountLoader<'info, YieldHistory>,
+    #[account(mut, seeds = [b"strategy", state.key().as_ref(), &[new_protocol]], bump)]
+    pub strategy_info: Option<Account<'info, StrategyInfo>>,
+    /// CHECK: Pyth price account for the strategy's underlying asset; parsed manually and
+    /// required only when the strategy has an oracle configured.
+    pub oracle: Option<UncheckedAccount<'info>>,
+    /// CHECK: the native Instructions sysvar; only introspected for the preceding
+    /// instruction, never deserialized as account data.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(protocol: u8)]
+pub struct SubmitYieldReport<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = 8 + 1 + 32 + 1 + (32 + 2 + 8) * MAX_UPDATERS + 1,
+        seeds = [b"yield_reports", state.key().as_ref(), &[protocol]],
+        bump,
+    )]
+    pub board: Account<'info, YieldReportBoard>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(new_protocol: u8)]
+pub struct AggregateYield<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(mut, seeds = [b"yield_history", state.key().as_ref()], bump = history.load()?.bump)]
+    pub history: AccountLoader<'info, YieldHistory>,
+    #[account(mut, seeds = [b"strategy", state.key().as_ref(), &[new_protocol]], bump)]
+    pub strategy_info: Option<Account<'info, StrategyInfo>>,
+    #[account(seeds = [b"yield_reports", state.key().as_ref(), &[new_protocol]], bump = board.bump)]
+    pub board: Account<'info, YieldReportBoard>,
+    /// CHECK: Pyth price account for the strategy's underlying asset; parsed manually and
+    /// required only when the strategy has an oracle configured.
+    pub oracle: Option<UncheckedAccount<'info>>,
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u8)]
+pub struct RegisterStrategy<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 1 + 1 + 32 + 32 + 2 + 8 + 1 + 32 + 32 + 32 + 1 + 8 + 8 + 8 + 8 + 8 + 2 + 2 + 8 + 1 + 1 + 2 + 8 + 8 + 8 + 2,
+        seeds = [b"strategy", state.key().as_ref(), &[id]],
+        bump,
+    )]
+    pub strategy_info: Account<'info, StrategyInfo>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageStrategy<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(mut, seeds = [b"strategy", state.key().as_ref(), &[strategy_info.id]], bump = strategy_info.bump)]
+    pub strategy_info: Account<'info, StrategyInfo>,
+    pub authority: Signer<'info>,
+}
+
+/// Permissionless, unlike `ManageStrategy`: `payer` pays the transaction but isn't checked
+/// against `state.authority` or the updater registry, since the valuation comes straight off
+/// the protocol's own accounts via CPI rather than a caller-supplied number.
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(protocol: u8)]
+pub struct RefreshValuation<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(mut, seeds = [b"strategy", state.key().as_ref(), &[protocol]], bump = strategy_info.bump)]
+    pub strategy_info: Account<'info, StrategyInfo>,
+    pub payer: Signer<'info>,
+}
+
+/// `strategy_info` accounts for the protocols touched by this call aren't declared here —
+/// they, and their adapter accounts, ride in `remaining_accounts` since the set of protocols
+/// (and hence account count) varies call to call. See `refresh_valuations`.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RefreshValuations<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    pub payer: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(new_protocol: u8, new_apy_bps: u16)]
+pub struct Rebalance<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(mut, seeds = [b"yield_history", state.key().as_ref()], bump = history.load()?.bump)]
+    pub history: AccountLoader<'info, YieldHistory>,
+    #[account(seeds = [b"strategy", state.key().as_ref(), &[new_protocol]], bump)]
+    pub strategy_info: Option<Account<'info, StrategyInfo>>,
+    /// Present only if `queue_rebalance` has ever been called for this vault. When its
+    /// `target_protocol` matches `new_protocol`, the handler enforces the guardian's veto
+    /// window instead of executing immediately.
+    #[account(seeds = [b"queued_rebalance", state.key().as_ref()], bump)]
+    pub queued_rebalance: Option<Account<'info, QueuedRebalance>>,
+    /// Present only if `blacklist_protocol` has blocked `new_protocol`; its mere existence
+    /// makes the handler reject the rebalance.
+    #[account(seeds = [b"blacklist", state.key().as_ref(), &[new_protocol]], bump)]
+    pub protocol_blacklist: Option<Account<'info, ProtocolBlacklist>>,
+    /// CHECK: PDA used only as the vault's CPI signer; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Pyth price account for the strategy's underlying asset; parsed manually and
+    /// required only when the strategy has an oracle configured.
+    pub oracle: Option<UncheckedAccount<'info>>,
+    #[account(mut, seeds = [b"vault", state.key().as_ref()], bump)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub signer: Signer<'info>,
+    /// Present only if `register_operator` has registered `signer`; when absent `signer`
+    /// is unrestricted, same as before this registry existed.
+    #[account(mut, seeds = [b"operator_limits", state.key().as_ref(), signer.key().as_ref()], bump)]
+    pub operator_limits: Option<Account<'info, OperatorLimits>>,
+    /// Present only if `create_audit_log` has been called for this vault.
+    #[account(mut, seeds = [b"audit_log", state.key().as_ref()], bump)]
+    pub audit_log: Option<AccountLoader<'info, AuditLog>>,
+}
+
+/// Read-only subset of `Rebalance`'s accounts for `simulate_rebalance`: no `vault`/
+/// `vault_authority` since nothing is moved or signed for, and no `signer` since a dry run
+/// isn't authorized to act on anyone's behalf.
+#[derive(Accounts)]
+#[instruction(new_protocol: u8, new_apy_bps: u16)]
+pub struct SimulateRebalance<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(seeds = [b"yield_history", state.key().as_ref()], bump = history.load()?.bump)]
+    pub history: AccountLoader<'info, YieldHistory>,
+    #[account(seeds = [b"strategy", state.key().as_ref(), &[new_protocol]], bump)]
+    pub strategy_info: Option<Account<'info, StrategyInfo>>,
+    /// Present only if `blacklist_protocol` has blocked `new_protocol`; its mere existence
+    /// makes the handler reject the rebalance.
+    #[account(seeds = [b"blacklist", state.key().as_ref(), &[new_protocol]], bump)]
+    pub protocol_blacklist: Option<Account<'info, ProtocolBlacklist>>,
+    /// CHECK: Pyth price account for the strategy's underlying asset; parsed manually and
+    /// required only when the strategy has an oracle configured.
+    pub oracle: Option<UncheckedAccount<'info>>,
+}
+
+/// Identical account shape to `Rebalance`, minus any notion of a privileged signer:
+/// `cranker` pays the transaction but is never checked against `state.authority` or the
+/// updater registry, since `crank_rebalance`'s on-chain threshold checks are what gate it.
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(new_protocol: u8, new_apy_bps: u16)]
+pub struct CrankRebalance<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(mut, seeds = [b"yield_history", state.key().as_ref()], bump = history.load()?.bump)]
+    pub history: AccountLoader<'info, YieldHistory>,
+    #[account(seeds = [b"strategy", state.key().as_ref(), &[new_protocol]], bump)]
+    pub strategy_info: Option<Account<'info, StrategyInfo>>,
+    /// Present only if `blacklist_protocol` has blocked `new_protocol`; its mere existence
+    /// makes the handler reject the rebalance.
+    #[account(seeds = [b"blacklist", state.key().as_ref(), &[new_protocol]], bump)]
+    pub protocol_blacklist: Option<Account<'info, ProtocolBlacklist>>,
+    /// CHECK: PDA used only as the vault's CPI signer; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Pyth price account for the strategy's underlying asset; parsed manually and
+    /// required only when the strategy has an oracle configured.
+    pub oracle: Option<UncheckedAccount<'info>>,
+    #[account(mut, seeds = [b"vault", state.key().as_ref()], bump)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, constraint = cranker_token_account.mint == state.mint @ YieldPilotError::InvalidMint)]
+    pub cranker_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub cranker: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Same account shape as `Rebalance`, plus the `RebalanceInProgress` checkpoint
+/// `start_rebalance` opens.
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(new_protocol: u8, new_apy_bps: u16)]
+pub struct StartRebalance<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(seeds = [b"yield_history", state.key().as_ref()], bump = history.load()?.bump)]
+    pub history: AccountLoader<'info, YieldHistory>,
+    #[account(seeds = [b"strategy", state.key().as_ref(), &[new_protocol]], bump)]
+    pub strategy_info: Option<Account<'info, StrategyInfo>>,
+    /// Present only if `queue_rebalance` has ever been called for this vault. When its
+    /// `target_protocol` matches `new_protocol`, the handler enforces the guardian's veto
+    /// window instead of executing immediately.
+    #[account(seeds = [b"queued_rebalance", state.key().as_ref()], bump)]
+    pub queued_rebalance: Option<Account<'info, QueuedRebalance>>,
+    /// Present only if `blacklist_protocol` has blocked `new_protocol`; its mere existence
+    /// makes the handler reject the rebalance.
+    #[account(seeds = [b"blacklist", state.key().as_ref(), &[new_protocol]], bump)]
+    pub protocol_blacklist: Option<Account<'info, ProtocolBlacklist>>,
+    /// CHECK: Pyth price account for the strategy's underlying asset; parsed manually and
+    /// required only when the strategy has an oracle configured.
+    pub oracle: Option<UncheckedAccount<'info>>,
+    #[account(
+        init,
+        payer = signer,
+        space = 8 + 1 + 1 + 1 + 2 + 1 + 8 + 8 + 8 + 1 + 8 + 1,
+        seeds = [b"rebalance_in_progress", state.key().as_ref()],
+        bump,
+    )]
+    pub rebalance_in_progress: Account<'info, RebalanceInProgress>,
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Permissionless like `crank_rebalance`: `cranker` pays the transaction but `start_rebalance`
+/// already fixed `new_protocol`/`new_apy_bps`, so there's nothing left here for an unprivileged
+/// caller to redirect.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ContinueRebalance<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        mut,
+        seeds = [b"rebalance_in_progress", state.key().as_ref()],
+        bump = rebalance_in_progress.bump,
+    )]
+    pub rebalance_in_progress: Account<'info, RebalanceInProgress>,
+    /// CHECK: PDA used only as the vault's CPI signer; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"vault", state.key().as_ref()], bump)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub cranker: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FinishRebalance<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(mut, seeds = [b"yield_history", state.key().as_ref()], bump = history.load()?.bump)]
+    pub history: AccountLoader<'info, YieldHistory>,
+    #[account(
+        mut,
+        close = cranker,
+        seeds = [b"rebalance_in_progress", state.key().as_ref()],
+        bump = rebalance_in_progress.bump,
+    )]
+    pub rebalance_in_progress: Account<'info, RebalanceInProgress>,
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DeployIdle<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// Present only if `blacklist_protocol` has blocked `state.current_protocol`; its mere
+    /// existence makes the handler reject topping it up further.
+    #[account(seeds = [b"blacklist", state.key().as_ref(), &[state.current_protocol]], bump)]
+    pub protocol_blacklist: Option<Account<'info, ProtocolBlacklist>>,
+    /// CHECK: PDA used only as the vault's CPI signer; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    pub signer: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Harvest<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's CPI signer; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"vault", state.key().as_ref()], bump)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// Token account owned by `vault_authority` that protocol rewards are claimed into.
+    #[account(mut, constraint = reward_account.owner == vault_authority.key() @ YieldPilotError::Unauthorized)]
+    pub reward_account: InterfaceAccount<'info, TokenAccount>,
+    pub signer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        seeds = [b"vault", state.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, address = state.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        seeds = [b"share", state.key().as_ref(), depositor.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = depositor,
+        token::token_program = token_program,
+    )]
+    pub depositor_share_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, constraint = depositor_token_account.mint == state.mint @ YieldPilotError::InvalidMint)]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + 1 + 8 + 8 + 8 + 8 + 8 + 1 + 16 + 8 + 32 + 8 + 2 + 16 + 8 + 2,
+        seeds = [b"position", state.key().as_ref(), depositor.key().as_ref()],
+        bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    /// Present only when `depositor` has been added via `add_to_allowlist`; checked against
+    /// `state.allowlist_enabled` in the handler rather than required here, since the vault
+    /// may never turn allowlist mode on.
+    #[account(seeds = [b"allowlist", state.key().as_ref(), depositor.key().as_ref()], bump)]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    /// Present only once `register_strategy` has been called for `state.current_protocol`;
+    /// checked against `max_valuation_staleness_secs` in the handler.
+    #[account(seeds = [b"strategy", state.key().as_ref(), &[state.current_protocol]], bump)]
+    pub strategy_info: Option<Account<'info, StrategyInfo>>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for `deposit_with_delegate`. Unlike `Deposit`, the beneficiary (`owner`, read off
+/// `owner_token_account.owner`) never signs — `payer` fronts rent for any `init_if_needed`
+/// accounts, and the transfer is authorized by `owner_token_account`'s SPL delegate approval
+/// rather than a signature from `owner` itself.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DepositWithDelegate<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [b"vault", state.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, address = state.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        constraint = owner_token_account.mint == state.mint @ YieldPilotError::InvalidMint,
+        constraint = owner_token_account.delegate == COption::Some(vault_authority.key()) @ YieldPilotError::NotDelegatedToVault,
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [b"share", state.key().as_ref(), owner_token_account.owner.as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = owner_token_account.owner,
+        token::token_program = token_program,
+    )]
+    pub owner_share_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + 1 + 8 + 8 + 8 + 8 + 8 + 1 + 16 + 8 + 32 + 8 + 2 + 16 + 8 + 2,
+        seeds = [b"position", state.key().as_ref(), owner_token_account.owner.as_ref()],
+        bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    /// Present only when the owner has been added via `add_to_allowlist`; checked against
+    /// `state.allowlist_enabled` in the handler rather than required here, since the vault
+    /// may never turn allowlist mode on.
+    #[account(seeds = [b"allowlist", state.key().as_ref(), owner_token_account.owner.as_ref()], bump)]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    /// Present only once `register_strategy` has been called for `state.current_protocol`;
+    /// checked against `max_valuation_staleness_secs` in the handler.
+    #[account(seeds = [b"strategy", state.key().as_ref(), &[state.current_protocol]], bump)]
+    pub strategy_info: Option<Account<'info, StrategyInfo>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DepositAndDeploy<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        seeds = [b"vault", state.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, address = state.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        seeds = [b"share", state.key().as_ref(), depositor.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = depositor,
+        token::token_program = token_program,
+    )]
+    pub depositor_share_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, constraint = depositor_token_account.mint == state.mint @ YieldPilotError::InvalidMint)]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + 1 + 8 + 8 + 8 + 8 + 8 + 1 + 16 + 8 + 32 + 8 + 2 + 16 + 8 + 2,
+        seeds = [b"position", state.key().as_ref(), depositor.key().as_ref()],
+        bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    /// Present only when `depositor` has been added via `add_to_allowlist`; checked against
+    /// `state.allowlist_enabled` in the handler rather than required here, since the vault
+    /// may never turn allowlist mode on.
+    #[account(seeds = [b"allowlist", state.key().as_ref(), depositor.key().as_ref()], bump)]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DepositAsNft<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        seeds = [b"vault", state.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, address = state.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, constraint = depositor_token_account.mint == state.mint @ YieldPilotError::InvalidMint)]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + 1 + 32 + 8 + 32 + 32 + 8 + 1,
+        seeds = [b"nft_receipt", state.key().as_ref(), &state.next_nft_receipt_id.to_le_bytes()],
+        bump,
+    )]
+    pub receipt: Account<'info, NftReceipt>,
+    /// Escrows the shares `receipt` represents; authority is `vault_authority`, never
+    /// `depositor`, so only `redeem_nft` burning the matching `receipt_mint` token can move
+    /// them back out.
+    #[account(
+        init,
+        payer = depositor,
+        seeds = [b"nft_escrow", receipt.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub escrow_share_account: InterfaceAccount<'info, TokenAccount>,
+    /// Single-supply mint representing the escrowed position; `mint::authority` is
+    /// `vault_authority` so only this program can ever mint (here) or burn (in `redeem_nft`)
+    /// it, never the depositor directly.
+    #[account(
+        init,
+        payer = depositor,
+        mint::decimals = 0,
+        mint::authority = vault_authority,
+        mint::token_program = token_program,
+        seeds = [b"nft_receipt_mint", receipt.key().as_ref()],
+        bump,
+    )]
+    pub receipt_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = depositor,
+        associated_token::mint = receipt_mint,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program,
+    )]
+    pub depositor_nft_account: InterfaceAccount<'info, TokenAccount>,
+    /// Present only when `depositor` has been added via `add_to_allowlist`; checked against
+    /// `state.allowlist_enabled` in the handler rather than required here, since the vault
+    /// may never turn allowlist mode on.
+    #[account(seeds = [b"allowlist", state.key().as_ref(), depositor.key().as_ref()], bump)]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    /// Present only once `register_strategy` has been called for `state.current_protocol`;
+    /// checked against `max_valuation_staleness_secs` in the handler.
+    #[account(seeds = [b"strategy", state.key().as_ref(), &[state.current_protocol]], bump)]
+    pub strategy_info: Option<Account<'info, StrategyInfo>>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RedeemNft<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, address = state.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        close = redeemer,
+        seeds = [b"nft_receipt", state.key().as_ref(), &receipt.id.to_le_bytes()],
+        bump = receipt.bump,
+        has_one = receipt_mint @ YieldPilotError::InvalidMint,
+    )]
+    pub receipt: Account<'info, NftReceipt>,
+    #[account(mut, address = receipt.receipt_mint)]
+    pub receipt_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"nft_escrow", receipt.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub escrow_share_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, constraint = redeemer_nft_account.mint == receipt.receipt_mint @ YieldPilotError::InvalidMint)]
+    pub redeemer_nft_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = redeemer,
+        associated_token::mint = share_mint,
+        associated_token::authority = redeemer,
+        associated_token::token_program = token_program,
+    )]
+    pub redeemer_share_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub redeemer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"vault", state.key().as_ref()], bump)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, address = state.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"share", state.key().as_ref(), depositor.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = depositor,
+    )]
+    pub depositor_share_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        associated_token::mint = mint,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program,
+    )]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"position", state.key().as_ref(), depositor.key().as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    /// Present only once `register_strategy` has been called for `state.current_protocol`;
+    /// checked against `max_valuation_staleness_secs` in the handler.
+    #[account(seeds = [b"strategy", state.key().as_ref(), &[state.current_protocol]], bump)]
+    pub strategy_info: Option<Account<'info, StrategyInfo>>,
+    /// Only required when `max_withdrawal_bps_per_epoch` is configured and this withdrawal
+    /// would push `withdrawn_this_epoch` past the cap — pass the program id as a sentinel to
+    /// omit it otherwise. Seeded off `state.next_withdrawal_sequence` exactly like
+    /// `request_withdrawal`'s ticket, so a real withdrawal request never collides with one
+    /// queued here.
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + 1 + 32 + 8 + 8 + 8 + 8 + 1 + 1 + 1,
+        seeds = [
+            b"withdrawal_ticket",
+            state.key().as_ref(),
+            depositor.key().as_ref(),
+            &state.next_withdrawal_sequence.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub ticket: Option<Account<'info, WithdrawalTicket>>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Burns shares out of `state` (the predecessor vault linked via `migrate_to`) and mints
+/// the equivalent value back into `successor_state`, CPI-ing the underlying straight from
+/// one vault's token account to the other's. `successor_state` must be the exact account
+/// `state.successor_vault` points at and must share `state.mint` — both checked here rather
+/// than trusted from the handler, since this is the one instruction that moves funds
+/// between two otherwise-unrelated `YieldState` accounts.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigratePosition<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"vault", state.key().as_ref()], bump)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, address = state.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"share", state.key().as_ref(), depositor.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = depositor,
+    )]
+    pub depositor_share_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"position", state.key().as_ref(), depositor.key().as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(
+        mut,
+        constraint = successor_state.key() == state.successor_vault @ YieldPilotError::NoSuccessorVaultLinked,
+        constraint = successor_state.mint == state.mint @ YieldPilotError::SuccessorMintMismatch,
+    )]
+    pub successor_state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the successor vault's token authority; never read or written.
+    #[account(
+        seeds = [b"vault_authority", successor_state.authority.as_ref(), &successor_state.vault_index.to_le_bytes()],
+        bump,
+    )]
+    pub successor_vault_authority: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"vault", successor_state.key().as_ref()], bump)]
+    pub successor_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = successor_state.share_mint)]
+    pub successor_share_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        seeds = [b"share", successor_state.key().as_ref(), depositor.key().as_ref()],
+        bump,
+        token::mint = successor_share_mint,
+        token::authority = depositor,
+        token::token_program = token_program,
+    )]
+    pub depositor_successor_share_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + 1 + 8 + 8 + 8 + 8 + 8 + 1 + 16 + 8 + 32 + 8 + 2 + 16 + 8 + 2,
+        seeds = [b"position", successor_state.key().as_ref(), depositor.key().as_ref()],
+        bump,
+    )]
+    pub successor_user_position: Account<'info, UserPosition>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DepositSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        seeds = [b"vault", state.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, address = state.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        seeds = [b"share", state.key().as_ref(), depositor.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = depositor,
+        token::token_program = token_program,
+    )]
+    pub depositor_share_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + 1 + 8 + 8 + 8 + 8 + 8 + 1 + 16 + 8 + 32 + 8 + 2 + 16 + 8 + 2,
+        seeds = [b"position", state.key().as_ref(), depositor.key().as_ref()],
+        bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    /// Present only when `depositor` has been added via `add_to_allowlist`; checked against
+    /// `state.allowlist_enabled` in the handler rather than required here, since the vault
+    /// may never turn allowlist mode on.
+    #[account(seeds = [b"allowlist", state.key().as_ref(), depositor.key().as_ref()], bump)]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"vault", state.key().as_ref()], bump)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, address = state.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"share", state.key().as_ref(), depositor.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = depositor,
+    )]
+    pub depositor_share_account: InterfaceAccount<'info, TokenAccount>,
+    /// Temporary wSOL account that receives the withdrawn amount before being closed to
+    /// release it to `depositor` as native SOL; created and torn down within this single
+    /// instruction so depositors never need to hold a wSOL ATA themselves.
+    #[account(
+        init,
+        payer = depositor,
+        seeds = [b"temp_wsol", state.key().as_ref(), depositor.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = depositor,
+        token::token_program = token_program,
+    )]
+    pub temp_wsol_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"position", state.key().as_ref(), depositor.key().as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RequestWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(mut, address = state.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"share", state.key().as_ref(), depositor.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = depositor,
+    )]
+    pub depositor_share_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"position", state.key().as_ref(), depositor.key().as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + 1 + 32 + 8 + 8 + 8 + 8 + 1 + 1 + 1,
+        seeds = [
+            b"withdrawal_ticket",
+            state.key().as_ref(),
+            depositor.key().as_ref(),
+            &state.next_withdrawal_sequence.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub ticket: Account<'info, WithdrawalTicket>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(owner: Pubkey, sequence: u64)]
+pub struct ProcessWithdrawalQueue<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        mut,
+        seeds = [b"withdrawal_ticket", state.key().as_ref(), owner.as_ref(), &sequence.to_le_bytes()],
+        bump = ticket.bump,
+    )]
+    pub ticket: Account<'info, WithdrawalTicket>,
+    pub operator: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RollEpoch<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    pub signer: Signer<'info>,
+}
+
+/// Accounts for `publish_state`'s Wormhole Core Bridge `post_message` CPI. `wormhole_message`
+/// must be a fresh, never-before-used keypair signer — like Wormhole's own integrations, the
+/// Core Bridge initializes and owns it as part of the CPI rather than the caller `init`-ing
+/// it up front.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PublishState<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the Wormhole emitter/CPI signer; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Wormhole Core Bridge program; address-checked against `state.wormhole_program`.
+    pub wormhole_program: UncheckedAccount<'info>,
+    /// CHECK: Core Bridge's global config account; validated by Wormhole during the CPI.
+    #[account(mut)]
+    pub wormhole_bridge: UncheckedAccount<'info>,
+    /// CHECK: fresh message account the Core Bridge initializes and writes the payload into.
+    #[account(mut)]
+    pub wormhole_message: Signer<'info>,
+    /// CHECK: per-emitter sequence counter PDA; validated by Wormhole during the CPI.
+    #[account(mut)]
+    pub wormhole_sequence: UncheckedAccount<'info>,
+    /// CHECK: message fee collector; validated by Wormhole during the CPI.
+    #[account(mut)]
+    pub wormhole_fee_collector: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(epoch: u64, owner: Pubkey)]
+pub struct TakeSnapshot<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        seeds = [b"position", state.key().as_ref(), owner.as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 1 + 8 + 32 + 8 + 1,
+        seeds = [b"snapshot", state.key().as_ref(), &epoch.to_le_bytes(), owner.as_ref()],
+        bump,
+    )]
+    pub snapshot: Account<'info, Snapshot>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateHealth<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// Present only once `register_strategy` has been called for `state.current_protocol`;
+    /// `valuation_stale`/`apy_stale`/`allocation_drift_bps` all read zero/false when absent.
+    #[account(seeds = [b"strategy", state.key().as_ref(), &[state.current_protocol]], bump)]
+    pub strategy_info: Option<Account<'info, StrategyInfo>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + 1 + 32 + 8 + 8 + 1 + 1 + 2 + 4 + 8 + 1,
+        seeds = [b"health", state.key().as_ref()],
+        bump,
+    )]
+    pub health: Account<'info, VaultHealth>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(owner: Pubkey, sequence: u64)]
+pub struct ClaimWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"vault", state.key().as_ref()], bump)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"withdrawal_ticket", state.key().as_ref(), owner.as_ref(), &sequence.to_le_bytes()],
+        bump = ticket.bump,
+    )]
+    pub ticket: Account<'info, WithdrawalTicket>,
+    #[account(mut, constraint = depositor_token_account.mint == state.mint @ YieldPilotError::InvalidMint)]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"position", state.key().as_ref(), owner.as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(address = owner)]
+    pub depositor: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(owner: Pubkey, sequence: u64)]
+pub struct WithdrawFromStrategyAndClaim<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"vault", state.key().as_ref()], bump)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"withdrawal_ticket", state.key().as_ref(), owner.as_ref(), &sequence.to_le_bytes()],
+        bump = ticket.bump,
+    )]
+    pub ticket: Account<'info, WithdrawalTicket>,
+    #[account(mut, constraint = depositor_token_account.mint == state.mint @ YieldPilotError::InvalidMint)]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"position", state.key().as_ref(), owner.as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(address = owner)]
+    pub depositor: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        mut,
+        close = depositor,
+        seeds = [b"position", state.key().as_ref(), depositor.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.shares == 0 @ YieldPilotError::PositionNotEmpty,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(sequence: u64)]
+pub struct CloseWithdrawalTicket<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        mut,
+        close = depositor,
+        seeds = [b"withdrawal_ticket", state.key().as_ref(), depositor.key().as_ref(), &sequence.to_le_bytes()],
+        bump = ticket.bump,
+        constraint = ticket.claimed @ YieldPilotError::TicketNotClaimed,
+    )]
+    pub ticket: Account<'info, WithdrawalTicket>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseStrategy<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"strategy", state.key().as_ref(), &[strategy_info.id]],
+        bump = strategy_info.bump,
+        constraint = !strategy_info.enabled @ YieldPilotError::StrategyStillEnabled,
+        constraint = strategy_info.tvl == 0 @ YieldPilotError::StrategyNotEmpty,
+    )]
+    pub strategy_info: Account<'info, StrategyInfo>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageUpdaters<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    pub authority: Signer<'info>,
+    /// Present only if `create_audit_log` has been called for this vault. Only
+    /// `add_updater`/`remove_updater` currently log to it; the rest of the setters sharing
+    /// this context don't reference the account.
+    #[account(mut, seeds = [b"audit_log", state.key().as_ref()], bump)]
+    pub audit_log: Option<AccountLoader<'info, AuditLog>>,
+}
+
+#[derive(Accounts)]
+pub struct ManageGuardian<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = guardian @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    pub guardian: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct EmergencyExit<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = guardian @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's CPI signer; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"vault", state.key().as_ref()], bump)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub guardian: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddToAllowlist<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 1 + 1,
+        seeds = [b"allowlist", state.key().as_ref(), wallet.as_ref()],
+        bump,
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct RemoveFromAllowlist<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"allowlist", state.key().as_ref(), wallet.as_ref()],
+        bump = allowlist_entry.bump,
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct SetFeeTier<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        mut,
+        seeds = [b"position", state.key().as_ref(), wallet.as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(protocol: u8)]
+pub struct BlacklistProtocol<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = guardian @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        init,
+        payer = guardian,
+        space = 8 + 1 + 1 + 1,
+        seeds = [b"blacklist", state.key().as_ref(), &[protocol]],
+        bump,
+    )]
+    pub protocol_blacklist: Account<'info, ProtocolBlacklist>,
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(protocol: u8)]
+pub struct UnblacklistProtocol<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = guardian @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        mut,
+        close = guardian,
+        seeds = [b"blacklist", state.key().as_ref(), &[protocol]],
+        bump = protocol_blacklist.bump,
+    )]
+    pub protocol_blacklist: Account<'info, ProtocolBlacklist>,
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateAuditLog<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 1 + 1 + 1 + 1 + 8 + (8 + 8 + 32 + 1 + 32) * AUDIT_LOG_LEN,
+        seeds = [b"audit_log", state.key().as_ref()],
+        bump,
+    )]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(operator: Pubkey)]
+pub struct RegisterOperator<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 1 + 32 + 2 + 8 + 8 + 2 + 8 + 1,
+        seeds = [b"operator_limits", state.key().as_ref(), operator.as_ref()],
+        bump,
+    )]
+    pub operator_limits: Account<'info, OperatorLimits>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Present only if `create_audit_log` has been called for this vault.
+    #[account(mut, seeds = [b"audit_log", state.key().as_ref()], bump)]
+    pub audit_log: Option<AccountLoader<'info, AuditLog>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetOperatorLimits<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        mut,
+        seeds = [b"operator_limits", state.key().as_ref(), operator_limits.operator.as_ref()],
+        bump = operator_limits.bump,
+    )]
+    pub operator_limits: Account<'info, OperatorLimits>,
+    pub authority: Signer<'info>,
+    /// Present only if `create_audit_log` has been called for this vault.
+    #[account(mut, seeds = [b"audit_log", state.key().as_ref()], bump)]
+    pub audit_log: Option<AccountLoader<'info, AuditLog>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DeregisterOperator<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"operator_limits", state.key().as_ref(), operator_limits.operator.as_ref()],
+        bump = operator_limits.bump,
+    )]
+    pub operator_limits: Account<'info, OperatorLimits>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Present only if `create_audit_log` has been called for this vault.
+    #[account(mut, seeds = [b"audit_log", state.key().as_ref()], bump)]
+    pub audit_log: Option<AccountLoader<'info, AuditLog>>,
+}
+
+#[derive(Accounts)]
+pub struct QueueParamChange<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 1 + 1 + 32 + 8 + 1,
+        seeds = [b"param_change", state.key().as_ref()],
+        bump,
+    )]
+    pub pending_change: Account<'info, PendingParamChange>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteParamChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"param_change", state.key().as_ref()],
+        bump = pending_change.bump,
+    )]
+    pub pending_change: Account<'info, PendingParamChange>,
+    /// CHECK: rent destination only; must be the vault's authority.
+    #[account(mut, address = state.authority)]
+    pub authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelParamChange<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = guardian @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"param_change", state.key().as_ref()],
+        bump = pending_change.bump,
+    )]
+    pub pending_change: Account<'info, PendingParamChange>,
+    /// CHECK: rent destination only; must be the vault's authority.
+    #[account(mut, address = state.authority)]
+    pub authority: UncheckedAccount<'info>,
+    pub guardian: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, address = state.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + 1 + 8 + 32 + (1 + 32) + 8 + 8 + 8 + 1 + 1,
+        seeds = [b"proposal", state.key().as_ref(), &state.next_proposal_id.to_le_bytes()],
+        bump,
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + 1 + 8 + 1 + 1,
+        seeds = [b"vote", proposal.key().as_ref(), proposer.key().as_ref()],
+        bump,
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    #[account(
+        init_if_needed,
+        payer = proposer,
+        seeds = [b"governance_escrow", state.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub governance_escrow: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, constraint = proposer_share_account.mint == state.share_mint @ YieldPilotError::InvalidMint)]
+    pub proposer_share_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct VoteProposal<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(mut, address = state.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"proposal", state.key().as_ref(), &proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + 1 + 8 + 1 + 1,
+        seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()],
+        bump,
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    #[account(mut, seeds = [b"governance_escrow", state.key().as_ref()], bump)]
+    pub governance_escrow: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, constraint = voter_share_account.mint == state.share_mint @ YieldPilotError::InvalidMint)]
+    pub voter_share_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ReclaimVote<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, address = state.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        seeds = [b"proposal", state.key().as_ref(), &proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+    #[account(
+        mut,
+        close = voter,
+        seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()],
+        bump = vote_record.bump,
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    #[account(mut, seeds = [b"governance_escrow", state.key().as_ref()], bump)]
+    pub governance_escrow: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, constraint = voter_share_account.mint == state.share_mint @ YieldPilotError::InvalidMint)]
+    pub voter_share_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct QueueProposalExecution<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        mut,
+        seeds = [b"proposal", state.key().as_ref(), &proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 1 + 1 + 32 + 8 + 1,
+        seeds = [b"param_change", state.key().as_ref()],
+        bump,
+    )]
+    pub pending_change: Account<'info, PendingParamChange>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct QueueLossReport<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 1 + 8 + 32 + 8 + 1,
+        seeds = [b"loss_report", state.key().as_ref()],
+        bump,
+    )]
+    pub pending_report: Account<'info, PendingLossReport>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteLossReport<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"loss_report", state.key().as_ref()],
+        bump = pending_report.bump,
+    )]
+    pub pending_report: Account<'info, PendingLossReport>,
+    /// CHECK: rent destination only; must be the vault's authority.
+    #[account(mut, address = state.authority)]
+    pub authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelLossReport<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = guardian @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"loss_report", state.key().as_ref()],
+        bump = pending_report.bump,
+    )]
+    pub pending_report: Account<'info, PendingLossReport>,
+    /// CHECK: rent destination only; must be the vault's authority.
+    #[account(mut, address = state.authority)]
+    pub authority: UncheckedAccount<'info>,
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QueueRebalance<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = 8 + 1 + 1 + 8 + 8 + 1 + 1,
+        seeds = [b"queued_rebalance", state.key().as_ref()],
+        bump,
+    )]
+    pub queued_rebalance: Account<'info, QueuedRebalance>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VetoRebalance<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = guardian @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        mut,
+        seeds = [b"queued_rebalance", state.key().as_ref()],
+        bump = queued_rebalance.bump,
+    )]
+    pub queued_rebalance: Account<'info, QueuedRebalance>,
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateAdapterLookupTable<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's CPI signer; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: the Address Lookup Table program derives this PDA from
+    /// `(vault_authority, recent_slot)`; checked against that derivation before the CPI
+    /// that initializes it runs.
+    #[account(mut)]
+    pub lookup_table: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: the native Address Lookup Table program; address-checked below.
+    #[account(address = address_lookup_table::program::ID)]
+    pub address_lookup_table_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendAdapterLookupTable<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's CPI signer; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: the lookup table being extended; the Address Lookup Table program rejects
+    /// the CPI outright if `vault_authority` isn't its recorded authority.
+    #[account(mut)]
+    pub lookup_table: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: the native Address Lookup Table program; address-checked below.
+    #[account(address = address_lookup_table::program::ID)]
+    pub address_lookup_table_program: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetShareMetadata<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's CPI signer; never read or written. Also
+    /// `share_mint`'s mint authority, so it doubles as Metaplex's update authority.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(address = state.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: Metaplex-owned metadata PDA; validated by Metaplex during the CPI, derived
+    /// here only to pass the right address in.
+    #[account(
+        mut,
+        seeds = [b"metadata", METAPLEX_TOKEN_METADATA_PROGRAM_ID.as_ref(), share_mint.key().as_ref()],
+        bump,
+        seeds::program = METAPLEX_TOKEN_METADATA_PROGRAM_ID,
+    )]
+    pub metadata: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: the Metaplex Token Metadata program; address-checked below.
+    #[account(address = METAPLEX_TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, address = state.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, address = state.fee_recipient)]
+    pub fee_recipient_share_account: InterfaceAccount<'info, TokenAccount>,
+    /// Present only once `initialize_insurance_fund` has been called for this vault. When
+    /// absent (or `insurance_bps` is zero) the full fee mints to `fee_recipient_share_account`
+    /// as before.
+    #[account(mut, seeds = [b"insurance_fund", state.key().as_ref()], bump)]
+    pub insurance_fund_share_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Present only once a referrer has been recorded for this vault (see `record_referrer`).
+    /// When absent (or `referral_bps` is zero) no referral shares are split out.
+    #[account(
+        mut,
+        constraint = referrer_share_account.owner == state.referrer @ YieldPilotError::Unauthorized,
+    )]
+    pub referrer_share_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// A depositor's own position, supplied so `collect_fees` can rebate its
+    /// `fee_discount_bps` share of this mint back to `fee_tier_share_account`. Absent (or
+    /// `fee_discount_bps` zero) means no rebate this call — `collect_fees` never enumerates
+    /// positions, so a tiered depositor's caller is responsible for supplying it.
+    pub fee_tier_position: Option<Account<'info, UserPosition>>,
+    #[account(mut, constraint = fee_tier_share_account.mint == share_mint.key() @ YieldPilotError::InvalidMint)]
+    pub fee_tier_share_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeInsuranceFund<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [b"insurance_fund", state.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub insurance_fund_share_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = state.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CoverLoss<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, address = state.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, seeds = [b"insurance_fund", state.key().as_ref()], bump)]
+    pub insurance_fund_share_account: InterfaceAccount<'info, TokenAccount>,
+    pub authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRewardsVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [b"rewards_vault", state.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub rewards_vault: InterfaceAccount<'info, TokenAccount>,
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundRewards<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(mut, address = state.reward_mint)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, seeds = [b"rewards_vault", state.key().as_ref()], bump)]
+    pub rewards_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, constraint = authority_reward_account.mint == state.reward_mint @ YieldPilotError::InvalidMint)]
+    pub authority_reward_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateDistributor<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 1 + 8 + 32 + 32 + 8 + 8 + 1,
+        seeds = [b"distributor", state.key().as_ref(), &state.next_distributor_id.to_le_bytes()],
+        bump,
+    )]
+    pub distributor: Account<'info, MerkleDistributor>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"distributor_vault", distributor.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub distributor_vault: InterfaceAccount<'info, TokenAccount>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(distributor_id: u64)]
+pub struct FundDistributor<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        mut,
+        seeds = [b"distributor", state.key().as_ref(), &distributor_id.to_le_bytes()],
+        bump = distributor.bump,
+    )]
+    pub distributor: Account<'info, MerkleDistributor>,
+    #[account(mut, address = distributor.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, seeds = [b"distributor_vault", distributor.key().as_ref()], bump)]
+    pub distributor_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, constraint = funder_token_account.mint == distributor.mint @ YieldPilotError::InvalidMint)]
+    pub funder_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(distributor_id: u64, index: u64)]
+pub struct Claim<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"distributor", state.key().as_ref(), &distributor_id.to_le_bytes()],
+        bump = distributor.bump,
+    )]
+    pub distributor: Account<'info, MerkleDistributor>,
+    #[account(mut, address = distributor.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, seeds = [b"distributor_vault", distributor.key().as_ref()], bump)]
+    pub distributor_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = claimant,
+        space = 8 + 1 + 1,
+        seeds = [b"claim_receipt", distributor.key().as_ref(), &index.to_le_bytes()],
+        bump,
+    )]
+    pub claim_receipt: Account<'info, ClaimReceipt>,
+    #[account(mut, constraint = claimant_token_account.mint == distributor.mint @ YieldPilotError::InvalidMint)]
+    pub claimant_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// CHECK: PDA used only as the vault's token authority; never read or written.
+    #[account(seeds = [b"vault_authority", state.authority.as_ref(), &state.vault_index.to_le_bytes()], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, address = state.reward_mint)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, seeds = [b"rewards_vault", state.key().as_ref()], bump)]
+    pub rewards_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"position", state.key().as_ref(), depositor.key().as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        associated_token::mint = reward_mint,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program,
+    )]
+    pub depositor_reward_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    /// Must match `state.pending_authority`. A multisig handing off control proposes its
+    /// vault PDA via `propose_authority`, then has its own program CPI into this
+    /// instruction signing for that PDA, exactly as a keypair-controlled wallet would sign
+    /// directly.
+    pub pending_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateState<'info> {
+    #[account(
+        mut,
+        realloc = state.to_account_info().data_len() + STATE_MIGRATION_SLACK_BYTES,
+        realloc::payer = authority,
+        realloc::zero = false,
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+        has_one = authority @ YieldPilotError::Unauthorized,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReadYieldState<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(seeds = [b"yield_history", state.key().as_ref()], bump = history.load()?.bump)]
+    pub history: AccountLoader<'info, YieldHistory>,
+}
+
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct PreviewAccruedYield<'info> {
+    #[account(
+        seeds = [b"yield_state", state.authority.as_ref(), &state.vault_index.to_le_bytes()],
+        bump = state.bump,
+    )]
+    pub state: Account<'info, YieldState>,
+    #[account(
+        seeds = [b"position", state.key().as_ref(), owner.as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+}
+
+#[error_code]
+pub enum YieldPilotError {
+    #[msg("Unauthorized caller")]
+    Unauthorized,
+    #[msg("Signer does not match the pending authority")]
+    PendingAuthorityMismatch,
+    #[msg("Updater is already registered")]
+    UpdaterAlreadyRegistered,
+    #[msg("Updater registry is full")]
+    UpdaterCapacityExceeded,
+    #[msg("Updater was not found in the registry")]
+    UpdaterNotFound,
+    #[msg("New protocol APY does not clear the minimum improvement threshold")]
+    ApyImprovementTooLow,
+    #[msg("Amount must be greater than zero")]
+    ZeroAmount,
+    #[msg("Depositor does not hold enough shares")]
+    InsufficientShares,
+    #[msg("Strategy id has not been registered")]
+    UnknownStrategy,
+    #[msg("Strategy has been disabled by the authority")]
+    StrategyDisabled,
+    #[msg("Protocol has been blacklisted by the guardian")]
+    ProtocolBlacklisted,
+    #[msg("Metadata name/symbol/uri exceeds Metaplex's field length limit")]
+    MetadataFieldTooLong,
+    #[msg("Soul-bound share mints require the Token-2022 program")]
+    NonTransferableRequiresToken2022,
+    #[msg("Reported APY exceeds the protocol's registered maximum")]
+    ApyOutOfBounds,
+    #[msg("Arithmetic overflow while updating vault accounting")]
+    ArithmeticOverflow,
+    #[msg("Arithmetic underflow while updating vault accounting")]
+    ArithmeticUnderflow,
+    #[msg("Token account mint does not match the vault's mint")]
+    InvalidMint,
+    #[msg("Adapter account split does not match the accounts supplied")]
+    InvalidAdapterAccounts,
+    #[msg("Strategy requires an oracle account but none was supplied")]
+    MissingOracle,
+    #[msg("Oracle price has not been updated recently enough")]
+    OracleStale,
+    #[msg("Oracle price falls outside the strategy's configured bounds")]
+    PriceOutOfBounds,
+    #[msg("Oracle account is too short to contain a price at the expected offset")]
+    MalformedOracleAccount,
+    #[msg("Rebalance cooldown has not elapsed since the last rebalance")]
+    RebalanceCooldownActive,
+    #[msg("Fee recipient has not been configured")]
+    MissingFeeRecipient,
+    #[msg("Deposits are currently paused")]
+    DepositsPaused,
+    #[msg("Withdrawals are currently paused")]
+    WithdrawalsPaused,
+    #[msg("Rebalances are currently paused")]
+    RebalancesPaused,
+    #[msg("Queued parameter change has not cleared its timelock yet")]
+    ParamChangeNotReady,
+    #[msg("Strategy's last reported APY is older than its configured max staleness")]
+    StaleYieldData,
+    #[msg("Allocation weight exceeds 10_000 bps or target exceeds max")]
+    WeightOutOfBounds,
+    #[msg("Withdrawal ticket is not at the front of the queue")]
+    OutOfOrderWithdrawal,
+    #[msg("Withdrawal ticket has not been marked ready by the operator yet")]
+    WithdrawalNotReady,
+    #[msg("Withdrawal ticket has already been claimed")]
+    WithdrawalAlreadyClaimed,
+    #[msg("Idle balance does not exceed the configured buffer")]
+    NoExcessLiquidity,
+    #[msg("Harvest claimed zero reward tokens")]
+    NothingToHarvest,
+    #[msg("Reward mint differs from the vault asset and no swap_program is configured")]
+    SwapProgramNotConfigured,
+    #[msg("First swap account does not match the configured swap_program")]
+    InvalidSwapProgram,
+    #[msg("Realized amount from a swap or unstake CPI was below the caller's min_amount_out")]
+    SlippageExceeded,
+    #[msg("Deposit would push total_assets past max_total_deposits")]
+    TotalDepositCapExceeded,
+    #[msg("Deposit would push the depositor's cumulative deposits past max_deposit_per_user")]
+    UserDepositCapExceeded,
+    #[msg("Depositor has not been approved via add_to_allowlist")]
+    NotAllowlisted,
+    #[msg("deposit_sol/withdraw_sol require the vault's mint to be wrapped SOL")]
+    NotNativeMint,
+    #[msg("Protocol id 0 is reserved for the idle/no-strategy state and cannot be registered")]
+    InvalidProtocol,
+    #[msg("Attempted to divide by a zero total_shares/total_assets")]
+    DivisionByZero,
+    #[msg("Account is already on the latest schema version; migrate_state has nothing to do")]
+    AlreadyOnLatestVersion,
+    #[msg("A CPI-heavy instruction is already in progress for this vault")]
+    ReentrancyDetected,
+    #[msg("Withdrawals must wait MIN_WITHDRAWAL_DELAY_SLOTS after the depositor's last deposit")]
+    WithdrawalTooSoonAfterDeposit,
+    #[msg("User position still holds shares; withdraw in full before closing it")]
+    PositionNotEmpty,
+    #[msg("Withdrawal ticket has not been claimed yet")]
+    TicketNotClaimed,
+    #[msg("Strategy must be disabled before it can be closed")]
+    StrategyStillEnabled,
+    #[msg("Strategy still has non-zero TVL; unwind it before closing")]
+    StrategyNotEmpty,
+    #[msg("Guardian has vetoed this queued rebalance")]
+    RebalanceVetoed,
+    #[msg("Queued rebalance is still inside its REBALANCE_VETO_WINDOW_SLOTS window")]
+    RebalanceVetoWindowActive,
+    #[msg("Rebalance would move more than max_move_per_rebalance in a single call; use start_rebalance instead")]
+    RebalanceExceedsMoveLimit,
+    #[msg("RebalanceInProgress has not finished redeploying yet")]
+    RebalanceStillInProgress,
+    #[msg("Insurance fund does not hold enough shares to cover this loss")]
+    InsufficientInsuranceFund,
+    #[msg("No rewards have accrued to claim")]
+    NothingToClaim,
+    #[msg("lock_duration_secs must be 0 or one of the supported LOCK_TIER_*_SECS values")]
+    InvalidLockDuration,
+    #[msg("Position is still inside its lockup period")]
+    PositionLocked,
+    #[msg("epoch_length_secs has not elapsed since the current epoch started")]
+    EpochNotElapsed,
+    #[msg("collect_fees must crystallize this epoch's performance fee before roll_epoch")]
+    FeesNotCrystallizedThisEpoch,
+    #[msg("The withdrawal queue must be fully drained before roll_epoch")]
+    WithdrawalQueueNotDrained,
+    #[msg("apy_oracle_signer has not been configured for this vault")]
+    ApyOracleSignerNotConfigured,
+    #[msg("signed_at is in the future, too old, or not newer than the strategy's last update")]
+    SignedApyUpdateExpired,
+    #[msg("Preceding instruction is not a well-formed single-signature Ed25519Program check")]
+    InvalidEd25519Instruction,
+    #[msg("Ed25519 instruction's pubkey does not match apy_oracle_signer")]
+    Ed25519SignerMismatch,
+    #[msg("Ed25519 instruction's signed message does not match the expected APY update payload")]
+    Ed25519MessageMismatch,
+    #[msg("Fewer fresh, still-registered YieldReportBoard samples than min_report_quorum requires")]
+    InsufficientReports,
+    #[msg("evm_apy_attester has not been configured for this vault")]
+    EvmApyAttesterNotConfigured,
+    #[msg("Preceding instruction is not a well-formed single-signature Secp256k1Program check")]
+    InvalidSecp256k1Instruction,
+    #[msg("Secp256k1 instruction's Ethereum address does not match evm_apy_attester")]
+    Secp256k1SignerMismatch,
+    #[msg("Secp256k1 instruction's signed message does not match the expected APY update payload")]
+    Secp256k1MessageMismatch,
+    #[msg("lookup_table does not match the PDA the Address Lookup Table program would derive")]
+    InvalidLookupTableAddress,
+    #[msg("Proposal's voting period has already ended")]
+    VotingPeriodEnded,
+    #[msg("Proposal's voting period has not ended yet")]
+    VotingStillOpen,
+    #[msg("Proposal has already been queued into the param change timelock")]
+    ProposalAlreadyQueued,
+    #[msg("Total votes cast did not clear GOVERNANCE_QUORUM_BPS of total_shares")]
+    QuorumNotMet,
+    #[msg("Proposal did not receive a majority of votes_for over votes_against")]
+    ProposalRejected,
+    #[msg("epoch must equal the vault's current_epoch")]
+    InvalidSnapshotEpoch,
+    #[msg("SNAPSHOT_WINDOW_SECS has elapsed since the current epoch started")]
+    SnapshotWindowClosed,
+    #[msg("Merkle proof does not verify against the distributor's root")]
+    InvalidMerkleProof,
+    #[msg("Adapter's value_position CPI did not return an 8-byte u64 via return data")]
+    MissingValuationReturnData,
+    #[msg("current_protocol's last refresh_valuation is older than its configured max staleness")]
+    StaleValuation,
+    #[msg("Every registered strategy must be refreshed via refresh_valuation(s) before roll_epoch")]
+    ValuationsNotRefreshedThisEpoch,
+    #[msg("max_withdrawal_bps_per_epoch is exhausted; pass a ticket account to queue this withdrawal instead")]
+    WithdrawalEpochCapExceeded,
+    #[msg("decimals_offset exceeds MAX_DECIMALS_OFFSET")]
+    DecimalsOffsetOutOfBounds,
+    #[msg("NFT receipt's token account must hold exactly one token to redeem")]
+    NftReceiptNotHeld,
+    #[msg("Adapter program is already on the allowlist")]
+    AdapterProgramAlreadyAllowed,
+    #[msg("Allowed adapter program registry is full")]
+    AdapterProgramCapacityExceeded,
+    #[msg("Adapter program was not found in the allowlist")]
+    AdapterProgramNotFound,
+    #[msg("Adapter program is not on the vault's allowed_adapter_programs allowlist")]
+    UnknownAdapterProgram,
+    #[msg("wormhole_program is unset, or the supplied account doesn't match it")]
+    WormholeProgramNotConfigured,
+    #[msg("Operator has exceeded its max_apy_updates_per_day cap")]
+    OperatorApyUpdateCapExceeded,
+    #[msg("Operator has exceeded its max_rebalance_volume_per_day cap")]
+    OperatorRebalanceVolumeCapExceeded,
+    #[msg("successor_vault is unset, or the supplied account doesn't match it")]
+    NoSuccessorVaultLinked,
+    #[msg("Successor vault's mint doesn't match this vault's mint")]
+    SuccessorMintMismatch,
+    #[msg("Pending rewards must be claimed before migrating this position")]
+    PendingRewardsMustBeClaimedFirst,
+    #[msg("Token account's delegate is not this vault's vault_authority, or delegated_amount is too low")]
+    NotDelegatedToVault,
+    #[msg("insurance_bps, referral_bps, and the fee-tier discount together claim more shares than collect_fees minted")]
+    FeeSharesExceedMinted,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_chronological_returns_partial_buffer_before_wrap() {
+        let mut history = YieldHistory::default();
+        history.record_snapshot(1, 100, 10);
+        history.record_snapshot(2, 200, 20);
+
+        let snapshots = history.history_chronological();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].protocol, 1);
+        assert_eq!(snapshots[1].protocol, 2);
+    }
+
+    #[test]
+    fn history_chronological_wraps_and_keeps_oldest_first() {
+        let mut history = YieldHistory::default();
+        for i in 0..(HISTORY_LEN as u8 + 5) {
+            history.record_snapshot(i, i as u16, i as i64);
+        }
+
+        let snapshots = history.history_chronological();
+        assert_eq!(snapshots.len(), HISTORY_LEN);
+        assert_eq!(snapshots.first().unwrap().protocol, 5);
+        assert_eq!(snapshots.last().unwrap().protocol, HISTORY_LEN as u8 + 4);
+    }
+
+    #[test]
+    fn add_updater_rejects_duplicates_and_enforces_capacity() {
+        let mut state = YieldState::default();
+        let key = Pubkey::new_unique();
+
+        state.add_updater(key).unwrap();
+        assert!(state.is_updater(&key));
+        assert!(state.add_updater(key).is_err());
+
+        for _ in state.updater_count as usize..MAX_UPDATERS {
+            state.add_updater(Pubkey::new_unique()).unwrap();
+        }
+        assert!(state.add_updater(Pubkey::new_unique()).is_err());
+    }
+
+    #[test]
+    fn remove_updater_swap_removes_and_clears_vacated_slot() {
+        let mut state = YieldState::default();
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        state.add_updater(a).unwrap();
+        state.add_updater(b).unwrap();
+
+        state.remove_updater(a).unwrap();
+
+        assert!(!state.is_updater(&a));
+        assert!(state.is_updater(&b));
+        assert_eq!(state.updater_count, 1);
+        assert!(state.remove_updater(a).is_err());
+    }
+
+    #[test]
+    fn add_allowed_adapter_program_rejects_duplicates_and_enforces_capacity() {
+        let mut state = YieldState::default();
+        let program = Pubkey::new_unique();
+
+        state.add_allowed_adapter_program(program).unwrap();
+        assert!(state.is_allowed_adapter_program(&program));
+        assert!(state.add_allowed_adapter_program(program).is_err());
+
+        for _ in state.allowed_adapter_program_count as usize..MAX_ALLOWED_ADAPTER_PROGRAMS {
+            state.add_allowed_adapter_program(Pubkey::new_unique()).unwrap();
+        }
+        assert!(state.add_allowed_adapter_program(Pubkey::new_unique()).is_err());
+    }
+
+    #[test]
+    fn remove_allowed_adapter_program_swap_removes_and_clears_vacated_slot() {
+        let mut state = YieldState::default();
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        state.add_allowed_adapter_program(a).unwrap();
+        state.add_allowed_adapter_program(b).unwrap();
+
+        state.remove_allowed_adapter_program(a).unwrap();
+
+        assert!(!state.is_allowed_adapter_program(&a));
+        assert!(state.is_allowed_adapter_program(&b));
+        assert_eq!(state.allowed_adapter_program_count, 1);
+        assert!(state.remove_allowed_adapter_program(a).is_err());
+    }
 }
@@ -0,0 +1,272 @@
+//! Pure share-price, fee-accrual, and weight-capping math shared by the instruction
+//! handlers in `lib.rs`. Factored out so the arithmetic invariants that matter most for
+//! depositor safety can be property-tested independently of any `Context`/account plumbing.
+//!
+//! Rounding policy: every division that moves value between a depositor and the vault must
+//! round in the vault's favor, never the depositor's. `shares_for_amount`/`amount_for_shares`
+//! round down (mint fewer shares, pay out less) via `div_round_down`; `accrued_fee_value`
+//! rounds up via `div_round_up` so the treasury never loses a fraction of a unit to
+//! truncation. Never use a bare `/` on a value derived from a depositor's assets or shares
+//! here — always go through one of the two helpers below so the direction is explicit at the
+//! call site.
+//!
+//! Inflation-attack mitigation: `shares_for_amount`/`amount_for_shares` price against
+//! `total_shares + VIRTUAL_SHARES` and `total_assets + 1` rather than the raw totals, where
+//! `VIRTUAL_SHARES` is `10^decimals_offset`. A handful of phantom shares the attacker can
+//! never withdraw means donating directly to the vault token account ahead of the first real
+//! deposit no longer buys them an outsized share of whatever the next depositor puts in —
+//! the classic ERC-4626 first-depositor inflation attack. `YieldState.decimals_offset` is
+//! also added onto `share_mint`'s own decimals at `create_vault` time, which is what makes
+//! the offset large enough to matter for a low-decimal asset like 6-decimal USDC without
+//! vaults on a 9-decimal asset like wrapped SOL needing to opt in.
+
+use crate::{YieldPilotError, SECONDS_PER_YEAR, SHARE_PRICE_SCALE};
+use anchor_lang::prelude::*;
+
+/// Floors `numerator / denominator`. Thin wrapper over integer division that exists purely
+/// so every rounding-sensitive division in this module names its direction explicitly
+/// instead of relying on a bare `/` that a future edit could flip by accident.
+fn div_round_down(numerator: u128, denominator: u128) -> u128 {
+    numerator / denominator
+}
+
+/// Ceils `numerator / denominator`. Used wherever rounding down would shortchange the vault
+/// (fee accrual) rather than a depositor.
+fn div_round_up(numerator: u128, denominator: u128) -> u128 {
+    (numerator + denominator - 1) / denominator
+}
+
+/// `10^decimals_offset` phantom shares folded into `total_shares` for `shares_for_amount`/
+/// `amount_for_shares`'s pricing, per the inflation-attack mitigation described above.
+fn virtual_shares(decimals_offset: u8) -> u128 {
+    10u128.pow(decimals_offset as u32)
+}
+
+/// Converts an underlying-asset amount into shares at the current vault exchange rate,
+/// via a `u128` intermediate so the multiply can't overflow `u64` before the divide.
+/// Prices against `total_shares + virtual_shares(decimals_offset)` and `total_assets + 1`
+/// rather than the raw totals, so an attacker who donates directly to the vault token
+/// account ahead of the first real deposit can't inflate the share price enough to round
+/// the next depositor down to zero shares.
+pub(crate) fn shares_for_amount(
+    amount: u64,
+    total_shares: u64,
+    total_assets: u64,
+    decimals_offset: u8,
+) -> Result<u64> {
+    let numerator = (amount as u128)
+        .checked_mul(total_shares as u128 + virtual_shares(decimals_offset))
+        .ok_or(YieldPilotError::ArithmeticOverflow)?;
+    Ok(div_round_down(numerator, total_assets as u128 + 1) as u64)
+}
+
+/// Inverse of `shares_for_amount`: converts shares back into an underlying-asset amount
+/// at the current vault exchange rate, via the same virtual-shares/assets offset.
+pub(crate) fn amount_for_shares(
+    shares: u64,
+    total_shares: u64,
+    total_assets: u64,
+    decimals_offset: u8,
+) -> Result<u64> {
+    let numerator = (shares as u128)
+        .checked_mul(total_assets as u128 + 1)
+        .ok_or(YieldPilotError::ArithmeticOverflow)?;
+    Ok(div_round_down(numerator, total_shares as u128 + virtual_shares(decimals_offset)) as u64)
+}
+
+/// Underlying-per-share, scaled by `SHARE_PRICE_SCALE`. Defined as exactly
+/// `SHARE_PRICE_SCALE` (one-to-one) while the vault is empty, matching the one-to-one
+/// first deposit in `deposit`/`deposit_and_deploy`.
+pub(crate) fn current_share_price(total_assets: u64, total_shares: u64) -> u64 {
+    if total_shares == 0 {
+        SHARE_PRICE_SCALE
+    } else {
+        (total_assets as u128 * SHARE_PRICE_SCALE as u128 / total_shares as u128) as u64
+    }
+}
+
+/// How much of `total_assets` should sit deployed in a strategy at `target_weight_bps`,
+/// clamped by `max_weight_bps` and, if set, by an absolute `max_tvl_lamports` ceiling.
+/// Shared by `rebalance`/`crank_rebalance` (via `apply_rebalance`) for computing the
+/// *new* protocol's target allocation.
+pub(crate) fn weight_capped_deployment(
+    total_assets: u64,
+    target_weight_bps: u16,
+    max_weight_bps: u16,
+    max_tvl_lamports: u64,
+) -> u64 {
+    let effective_weight_bps = target_weight_bps.min(max_weight_bps);
+    let weight_capped_amount =
+        (total_assets as u128 * effective_weight_bps as u128 / crate::MAX_WEIGHT_BPS as u128) as u64;
+    if max_tvl_lamports == 0 {
+        weight_capped_amount
+    } else {
+        weight_capped_amount.min(max_tvl_lamports)
+    }
+}
+
+/// `collect_fees`'s management + performance fee calculation, and `preview_withdraw`'s
+/// `projected_total_shares_after_fees` replay of the same math, both route through here so
+/// the two can't silently drift apart. Returns the fee value in underlying-asset terms,
+/// capped at `total_assets` — `collect_fees` can never claim more than the vault holds.
+pub(crate) fn accrued_fee_value(
+    total_assets: u64,
+    management_fee_bps: u16,
+    elapsed_secs: i64,
+    performance_fee_bps: u16,
+    high_water_mark: u64,
+    total_shares: u64,
+) -> u64 {
+    let elapsed = elapsed_secs.max(0) as u128;
+
+    let management_fee_value = if elapsed == 0 || management_fee_bps == 0 {
+        0u128
+    } else {
+        div_round_up(
+            total_assets as u128 * management_fee_bps as u128 * elapsed,
+            10_000u128 * SECONDS_PER_YEAR as u128,
+        )
+    };
+
+    let share_price = current_share_price(total_assets, total_shares);
+    let performance_fee_value = if share_price > high_water_mark && performance_fee_bps > 0 && total_shares > 0
+    {
+        let profit_per_share = (share_price - high_water_mark) as u128;
+        let total_profit = div_round_up(profit_per_share * total_shares as u128, SHARE_PRICE_SCALE as u128);
+        div_round_up(total_profit * performance_fee_bps as u128, 10_000)
+    } else {
+        0u128
+    };
+
+    (management_fee_value + performance_fee_value).min(total_assets as u128) as u64
+}
+
+/// Projects the APY a lending venue would offer after depositing `deposit_amount` into a
+/// pool currently holding `pool_liquidity` in total supply, given `rate_slope_bps` bps of
+/// APY decay per 10_000 bps (100%) growth in supply — a linear local approximation of the
+/// venue's supply-rate curve around its current utilization. Cheap enough to run on every
+/// `rebalance` without needing the venue's full interest-rate curve on-chain, and close
+/// enough near the current point to gate the improvement check it feeds. Zero
+/// `pool_liquidity` or `rate_slope_bps` means the curve isn't configured for this strategy,
+/// so this is a no-op and returns `spot_apy_bps` unchanged.
+pub(crate) fn projected_apy_after_deposit(
+    spot_apy_bps: u16,
+    deposit_amount: u64,
+    pool_liquidity: u64,
+    rate_slope_bps: u16,
+) -> u16 {
+    if pool_liquidity == 0 || rate_slope_bps == 0 {
+        return spot_apy_bps;
+    }
+
+    let supply_growth_bps = (deposit_amount as u128 * 10_000 / pool_liquidity as u128).min(10_000) as u64;
+    let decay_bps = (supply_growth_bps * rate_slope_bps as u64) / 10_000;
+    spot_apy_bps.saturating_sub(decay_bps as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        /// Depositing `amount` and immediately redeeming the shares it bought should never
+        /// hand back more than was put in — rounding in `shares_for_amount` always favors
+        /// the vault, never the depositor.
+        #[test]
+        fn round_trip_never_returns_more_than_deposited(
+            amount in 1u64..1_000_000_000_000,
+            total_shares in 1u64..1_000_000_000_000,
+            total_assets in 1u64..1_000_000_000_000,
+            decimals_offset in 0u8..=6,
+        ) {
+            let shares = shares_for_amount(amount, total_shares, total_assets, decimals_offset).unwrap();
+            let new_total_shares = total_shares + shares;
+            let new_total_assets = total_assets + amount;
+            let redeemed = amount_for_shares(shares, new_total_shares, new_total_assets, decimals_offset).unwrap();
+            prop_assert!(redeemed <= amount);
+        }
+
+        /// The virtual shares folded into the denominator for the inflation-attack mitigation
+        /// don't back any real claim on the vault, so no holder redeeming any amount of real
+        /// shares — even every last one — can ever pull out more than `total_assets` actually
+        /// holds, regardless of how large a donation inflated it beforehand.
+        #[test]
+        fn amount_for_shares_never_exceeds_total_assets(
+            shares in 0u64..1_000_000_000_000,
+            total_shares in 1u64..1_000_000_000_000,
+            total_assets in 0u64..1_000_000_000_000,
+            decimals_offset in 0u8..=6,
+        ) {
+            let shares = shares.min(total_shares);
+            let redeemed = amount_for_shares(shares, total_shares, total_assets, decimals_offset).unwrap();
+            prop_assert!(redeemed <= total_assets);
+        }
+
+        /// `weight_capped_deployment` never deploys more than `total_assets` itself, and
+        /// never exceeds an explicit `max_tvl_lamports` ceiling when one is set.
+        #[test]
+        fn weight_capped_deployment_respects_both_caps(
+            total_assets in 0u64..1_000_000_000_000,
+            target_weight_bps in 0u16..=10_000,
+            max_weight_bps in 0u16..=10_000,
+            max_tvl_lamports in 0u64..1_000_000_000_000,
+        ) {
+            let deployed = weight_capped_deployment(
+                total_assets,
+                target_weight_bps,
+                max_weight_bps,
+                max_tvl_lamports,
+            );
+            prop_assert!(deployed <= total_assets);
+            if max_tvl_lamports > 0 {
+                prop_assert!(deployed <= max_tvl_lamports);
+            }
+        }
+
+        /// `div_round_up` never under-counts relative to plain floor division — the
+        /// fee-accrual side of the rounding policy can't quietly regress to rounding down.
+        #[test]
+        fn div_round_up_never_rounds_below_floor(
+            numerator in 0u128..1_000_000_000_000_000,
+            denominator in 1u128..1_000_000_000,
+        ) {
+            prop_assert!(div_round_up(numerator, denominator) >= div_round_down(numerator, denominator));
+        }
+
+        /// `projected_apy_after_deposit` never projects an APY above the spot rate it
+        /// started from — depositing into a pool can only push its rate down, never up.
+        #[test]
+        fn projected_apy_after_deposit_never_exceeds_spot(
+            spot_apy_bps in 0u16..=100_000,
+            deposit_amount in 0u64..1_000_000_000_000,
+            pool_liquidity in 0u64..1_000_000_000_000,
+            rate_slope_bps in 0u16..=10_000,
+        ) {
+            let projected = projected_apy_after_deposit(spot_apy_bps, deposit_amount, pool_liquidity, rate_slope_bps);
+            prop_assert!(projected <= spot_apy_bps);
+        }
+
+        /// `accrued_fee_value` can never claim more than the vault actually holds,
+        /// regardless of how large the nominal management/performance fee computes to.
+        #[test]
+        fn accrued_fee_value_never_exceeds_total_assets(
+            total_assets in 0u64..1_000_000_000_000,
+            management_fee_bps in 0u16..=10_000,
+            elapsed_secs in 0i64..(50 * crate::SECONDS_PER_YEAR),
+            performance_fee_bps in 0u16..=10_000,
+            high_water_mark in 0u64..10 * SHARE_PRICE_SCALE,
+            total_shares in 0u64..1_000_000_000_000,
+        ) {
+            let fee = accrued_fee_value(
+                total_assets,
+                management_fee_bps,
+                elapsed_secs,
+                performance_fee_bps,
+                high_water_mark,
+                total_shares,
+            );
+            prop_assert!(fee <= total_assets);
+        }
+    }
+}